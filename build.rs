@@ -0,0 +1,491 @@
+//! Generates the reflection over the syn syntax tree used by the queries, and the nodes of the walk shared by the
+//! collectors, from the description in `syn.json`
+//!
+//! See `src/collector/metrics/query/reflect.rs` and `src/collector/metrics/util/walk.rs` for what the generated code
+//! implements.
+
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::path::Path;
+
+const SYN_JSON: &str = "crates/par-syn/syn.json";
+
+/// The types syn has no `ToTokens` for, their text is made of the text of their children
+const NOT_PRINTABLE: &[&str] = &[
+    "AttrStyle",
+    "Data",
+    "DataEnum",
+    "DataStruct",
+    "DataUnion",
+    "FieldMutability",
+    "ImplRestriction",
+    "LocalInit",
+    "MacroDelimiter",
+    "QSelf",
+];
+
+/// The nodes whose children are walked by a function of `walk.rs` instead of `syn::visit`
+const WALK_CHILDREN: &[(&str, &str)] = &[("Macro", "walk_macro")];
+
+fn main() {
+    println!("cargo:rerun-if-changed={}", SYN_JSON);
+    println!("cargo:rerun-if-changed=build.rs");
+
+    let json = std::fs::read_to_string(SYN_JSON).expect("Reading syn.json");
+    let json: Value = serde_json::from_str(&json).expect("Parsing syn.json");
+    let tokens = json["tokens"].as_object().expect("No tokens in syn.json");
+    let types = json["types"]
+        .as_array()
+        .expect("No types in syn.json")
+        .iter()
+        .map(|ty| (ty["ident"].as_str().unwrap().to_string(), ty))
+        .collect::<BTreeMap<_, _>>();
+
+    let gen = Gen { tokens, types };
+    let mut out = String::new();
+    for (ident, ty) in &gen.types {
+        gen.reflect_impl(&mut out, ident, ty);
+    }
+    gen.kinds(&mut out);
+
+    let out_dir = std::env::var("OUT_DIR").unwrap();
+    std::fs::write(Path::new(&out_dir).join("reflect.rs"), out).expect("Writing reflect.rs");
+
+    let mut out = String::new();
+    gen.nodes(&mut out);
+    std::fs::write(Path::new(&out_dir).join("nodes.rs"), out).expect("Writing nodes.rs");
+}
+
+struct Gen<'a> {
+    tokens: &'a Map<String, Value>,
+    types: BTreeMap<String, &'a Value>,
+}
+
+/// The name syn gives to the `Visit` method of a type, like `visit_expr_if` for `ExprIf`
+fn visit_method(ident: &str) -> String {
+    let mut name = "visit".to_string();
+    let mut lower = true;
+    for c in ident.chars() {
+        if c.is_uppercase() && lower {
+            name.push('_');
+        }
+        lower = c.is_lowercase();
+        name.push(c.to_ascii_lowercase());
+    }
+    name
+}
+
+/// `(key, value)` of a single-key object, which is how `syn.json` describes the types of fields
+fn shape(ty: &Value) -> (&str, &Value) {
+    let (key, value) = ty.as_object().unwrap().iter().next().unwrap();
+    (key.as_str(), value)
+}
+
+/// Whether a value of the type is just punctuation, which the queries don't look at
+fn is_punctuation(ty: &Value) -> bool {
+    match shape(ty) {
+        ("token" | "group", _) => true,
+        ("option", inner) => is_punctuation(inner),
+        _ => false,
+    }
+}
+
+fn is_span(ty: &Value) -> bool {
+    shape(ty) == ("proc_macro2", &Value::String("Span".into()))
+}
+
+/// The elements of a tuple or a variant that are visible to the queries, with their indices
+fn significant(elements: &[Value]) -> Vec<(usize, &Value)> {
+    elements
+        .iter()
+        .enumerate()
+        .filter(|(_, ty)| !is_punctuation(ty) && !is_span(ty))
+        .collect()
+}
+
+/// The syn type of a variant that is a plain wrapper of another node, like `Expr::If(ExprIf)`
+fn wrapped_node(elements: &[Value]) -> Option<(usize, bool)> {
+    match significant(elements)[..] {
+        [(index, ty)] => match shape(ty) {
+            ("syn", _) => Some((index, false)),
+            ("box", inner) if shape(inner).0 == "syn" => Some((index, true)),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+fn variant_pattern(variant: &str, elements: &[Value], bound: &[usize]) -> String {
+    if elements.is_empty() {
+        return format!("Self::{}", variant);
+    }
+    let bindings = (0..elements.len())
+        .map(|i| {
+            if bound.contains(&i) {
+                format!("x{}", i)
+            } else {
+                "_".to_string()
+            }
+        })
+        .collect::<Vec<_>>();
+    format!("Self::{}({})", variant, bindings.join(", "))
+}
+
+impl Gen<'_> {
+    /// An expression making a `Value` of `access`, which is a reference to a value of type `ty`
+    fn value(&self, ty: &Value, access: &str, depth: usize) -> Option<String> {
+        let var = format!("v{}", depth);
+        Some(match shape(ty) {
+            ("syn", _) => format!("Value::Node({})", access),
+            ("box", inner) => return self.value(inner, &format!("&**{}", access), depth),
+            ("option", inner) => {
+                let value = self.value(inner, &var, depth + 1)?;
+                let binding = if value.contains(&var) {
+                    var.as_str()
+                } else {
+                    "_"
+                };
+                format!(
+                    "match {} {{ Some({}) => {}, None => Value::None }}",
+                    access, binding, value
+                )
+            }
+            ("vec", inner) => format!(
+                "Value::List(({}).iter().map(|{}| {}).collect())",
+                access,
+                var,
+                self.value(inner, &var, depth + 1)?
+            ),
+            ("punctuated", p) => format!(
+                "Value::List(({}).iter().map(|{}| {}).collect())",
+                access,
+                var,
+                self.value(&p["element"], &var, depth + 1)?
+            ),
+            ("token", Value::String(token)) => {
+                format!("Value::Token({:?})", self.tokens[token].as_str().unwrap())
+            }
+            ("group", Value::String(group)) => {
+                let delimiters = match group.as_str() {
+                    "Brace" => "{}",
+                    "Bracket" => "[]",
+                    "Paren" => "()",
+                    // invisible delimiters
+                    "Group" => "",
+                    _ => panic!("Unknown group {}", group),
+                };
+                format!("Value::Token({:?})", delimiters)
+            }
+            ("proc_macro2", Value::String(ty)) => match ty.as_str() {
+                "Ident" => format!("Value::Ident({})", access),
+                "TokenStream" => format!("Value::Tokens({})", access),
+                "Literal" => format!("Value::Literal({})", access),
+                "Span" => return None,
+                _ => panic!("Unknown proc_macro2 type {}", ty),
+            },
+            ("std", Value::String(ty)) => match ty.as_str() {
+                "String" => format!("Value::Str({})", access),
+                "bool" => format!("Value::Bool(*{})", access),
+                "u32" | "usize" => format!("Value::Int(*{} as u64)", access),
+                _ => panic!("Unknown std type {}", ty),
+            },
+            ("tuple", Value::Array(elements)) => match significant(elements)[..] {
+                [(index, ty)] => return self.value(ty, &format!("&({}).{}", access, index), depth),
+                ref elements => format!(
+                    "Value::List(vec![{}])",
+                    elements
+                        .iter()
+                        .filter_map(|(index, ty)| self.value(
+                            ty,
+                            &format!("&({}).{}", access, index),
+                            depth
+                        ))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ),
+            },
+            _ => panic!("Unknown type {}", ty),
+        })
+    }
+
+    /// Statements calling `f` on the nodes in `access`, which is a reference to a value of type `ty`
+    fn children(&self, ty: &Value, access: &str, depth: usize) -> String {
+        let var = format!("v{}", depth);
+        match shape(ty) {
+            ("syn", _) => format!("f({});", access),
+            ("box", inner) => self.children(inner, &format!("&**{}", access), depth),
+            ("option", inner) => match self.children(inner, &var, depth + 1) {
+                body if body.is_empty() => body,
+                body => format!("if let Some({}) = {} {{ {} }}", var, access, body),
+            },
+            ("vec", inner) => match self.children(inner, &var, depth + 1) {
+                body if body.is_empty() => body,
+                body => format!("for {} in {} {{ {} }}", var, access, body),
+            },
+            ("punctuated", p) => match self.children(&p["element"], &var, depth + 1) {
+                body if body.is_empty() => body,
+                body => format!("for {} in {} {{ {} }}", var, access, body),
+            },
+            ("tuple", Value::Array(elements)) => elements
+                .iter()
+                .enumerate()
+                .map(|(index, ty)| self.children(ty, &format!("&({}).{}", access, index), depth))
+                .collect(),
+            _ => String::new(),
+        }
+    }
+
+    fn reflect_impl(&self, out: &mut String, ident: &str, ty: &Value) {
+        let (is, field, children, as_any) = if let Some(variants) = ty.get("variants") {
+            self.enum_impl(ident, ty, variants.as_object().unwrap())
+        } else {
+            self.struct_impl(ident, ty)
+        };
+        let to_tokens = if NOT_PRINTABLE.contains(&ident) {
+            String::new()
+        } else {
+            "fn to_tokens(&self, tokens: &mut TokenStream) { ToTokens::to_tokens(self, tokens) }"
+                .to_string()
+        };
+        writeln!(
+            out,
+            "impl Reflect for syn::{ident} {{
+                fn is(&self, kind: &str) -> bool {{ {is} }}
+                fn field(&self, {name}: &str) -> Option<Value<'_>> {{ {field} }}
+                fn children<'a>(&'a self, {f}: &mut dyn FnMut(&'a dyn Reflect)) {{ {children} }}
+                fn as_any(&self) -> &dyn Any {{ {as_any} }}
+                {to_tokens}
+            }}",
+            name = if field.contains("name") {
+                "name"
+            } else {
+                "_name"
+            },
+            f = if children.is_empty() { "_f" } else { "f" },
+        )
+        .unwrap();
+    }
+
+    fn struct_impl(&self, ident: &str, ty: &Value) -> (String, String, String, String) {
+        let is = format!("kind == {:?}", ident);
+        let Some(fields) = ty.get("fields").and_then(Value::as_object) else {
+            // literals and lifetimes, they are only looked at as text
+            return (is, "None".into(), String::new(), "self".into());
+        };
+        let mut arms = String::new();
+        let mut children = String::new();
+        for (name, ty) in fields {
+            let access = format!("&self.{}", name);
+            if let Some(value) = self.value(ty, &access, 0) {
+                writeln!(arms, "{:?} => {},", name, value).unwrap();
+            }
+            children += &self.children(ty, &access, 0);
+        }
+        let field = format!("Some(match name {{ {} _ => return None }})", arms);
+        (is, field, children, "self".into())
+    }
+
+    fn enum_impl(
+        &self,
+        ident: &str,
+        ty: &Value,
+        variants: &Map<String, Value>,
+    ) -> (String, String, String, String) {
+        // the arms of the matches, with the number of variants they cover
+        let mut is = (String::new(), 0);
+        let mut field = (String::new(), 0);
+        let mut children = (String::new(), 0);
+        let mut as_any = (String::new(), 0);
+        let arm = |(arms, count): &mut (String, usize), arm: String| {
+            arms.push_str(&arm);
+            *count += 1;
+        };
+        for (variant, elements) in variants {
+            let elements = elements.as_array().unwrap();
+            let kind = format!("{}::{}", ident, variant);
+            if let Some((index, boxed)) = wrapped_node(elements) {
+                let pattern = variant_pattern(variant, elements, &[index]);
+                let node = if boxed {
+                    format!("(**x{})", index)
+                } else {
+                    format!("x{}", index)
+                };
+                arm(
+                    &mut is,
+                    format!("{} => kind == {:?} || {}.is(kind),", pattern, kind, node),
+                );
+                arm(&mut field, format!("{} => {}.field(name),", pattern, node));
+                arm(
+                    &mut children,
+                    format!("{} => {}.children(f),", pattern, node),
+                );
+                arm(&mut as_any, format!("{} => {}.as_any(),", pattern, node));
+                continue;
+            }
+
+            let significant = significant(elements);
+            let indices = significant.iter().map(|(i, _)| *i).collect::<Vec<_>>();
+            arm(
+                &mut is,
+                format!(
+                    "{} => kind == {:?},",
+                    variant_pattern(variant, elements, &[]),
+                    kind
+                ),
+            );
+            let pattern = variant_pattern(variant, elements, &indices);
+            let arms = significant
+                .iter()
+                .filter_map(|(index, ty)| {
+                    let value = self.value(ty, &format!("x{}", index), 0)?;
+                    Some(format!("{:?} => Some({}),", index.to_string(), value))
+                })
+                .collect::<String>();
+            if !arms.is_empty() {
+                arm(
+                    &mut field,
+                    format!("{} => match name {{ {} _ => None }},", pattern, arms),
+                );
+            }
+            let body = significant
+                .iter()
+                .map(|(index, ty)| self.children(ty, &format!("x{}", index), 0))
+                .collect::<String>();
+            if !body.is_empty() {
+                arm(&mut children, format!("{} => {{ {} }}", pattern, body));
+            }
+        }
+        let exhaustive = ty.get("exhaustive") != Some(&Value::Bool(false));
+        let wildcard = |(arms, count): &(String, usize), default: &str| -> String {
+            if exhaustive && *count == variants.len() {
+                arms.clone()
+            } else {
+                format!("{} _ => {},", arms, default)
+            }
+        };
+        (
+            format!(
+                "kind == {:?} || match self {{ {} }}",
+                ident,
+                wildcard(&is, "false")
+            ),
+            format!("match self {{ {} }}", wildcard(&field, "None")),
+            if children.1 == 0 {
+                String::new()
+            } else {
+                format!("match self {{ {} }}", wildcard(&children, "{}"))
+            },
+            format!("match self {{ {} }}", wildcard(&as_any, "self")),
+        )
+    }
+
+    /// The field names of a kind, for the variants of enums it's the fields of the nodes they wrap
+    fn fields_of(&self, ident: &str, variant: Option<&str>) -> Vec<String> {
+        let ty = self.types[ident];
+        if let Some(variants) = ty.get("variants").and_then(Value::as_object) {
+            let mut fields = variants
+                .iter()
+                .filter(|(name, _)| variant.is_none_or(|v| v == name.as_str()))
+                .flat_map(|(_, elements)| {
+                    let elements = elements.as_array().unwrap();
+                    match wrapped_node(elements) {
+                        Some((index, _)) => {
+                            let mut node = &elements[index];
+                            while let ("box", inner) = shape(node) {
+                                node = inner;
+                            }
+                            self.fields_of(shape(node).1.as_str().unwrap(), None)
+                        }
+                        None => significant(elements)
+                            .iter()
+                            .map(|(index, _)| index.to_string())
+                            .collect(),
+                    }
+                })
+                .collect::<Vec<_>>();
+            fields.sort();
+            fields.dedup();
+            return fields;
+        }
+        ty.get("fields")
+            .and_then(Value::as_object)
+            .into_iter()
+            .flatten()
+            .filter(|(_, ty)| !is_span(ty))
+            .map(|(name, _)| name.clone())
+            .collect()
+    }
+
+    /// `Node`, with a variant for every type, and the `Visit` impl of the shared walk calling the visitors on them
+    fn nodes(&self, out: &mut String) {
+        let variants = self
+            .types
+            .keys()
+            .map(|ident| format!("{ident}(&'ast syn::{ident}),"))
+            .collect::<String>();
+        let arms = self
+            .types
+            .keys()
+            .map(|ident| format!("Self::{ident}(i) => i,"))
+            .collect::<String>();
+        let methods = self
+            .types
+            .keys()
+            .map(|ident| {
+                let visit = visit_method(ident);
+                let children = match WALK_CHILDREN.iter().find(|(node, _)| node == ident) {
+                    Some((_, walk)) => format!("{walk}(walk, i)"),
+                    None => format!("syn::visit::{visit}(walk, i)"),
+                };
+                format!(
+                    "fn {visit}(&mut self, i: &'ast syn::{ident}) {{
+                        self.node(Node::{ident}(i), |walk| {children});
+                    }}"
+                )
+            })
+            .collect::<String>();
+        writeln!(
+            out,
+            "/// The syntax tree nodes the visitors are called on, there is one for every type of syn
+            ///
+            /// The variants of enums that wrap another node (like `Expr::If(ExprIf)`) are entered right before the node
+            /// they wrap. More kinds of nodes may be added, without it being a breaking change.
+            #[derive(Clone, Copy)]
+            #[non_exhaustive]
+            pub enum Node<'ast> {{ {variants} }}
+
+            impl<'ast> Node<'ast> {{
+                /// The node for the queries
+                pub(crate) fn reflect(self) -> &'ast dyn Reflect {{
+                    match self {{ {arms} }}
+                }}
+            }}
+
+            impl<'ast> Visit<'ast> for FusedWalk<'_, '_> {{ {methods} }}"
+        )
+        .unwrap();
+    }
+
+    /// `KINDS`: every kind a query can mention, sorted, with the fields it can have
+    fn kinds(&self, out: &mut String) {
+        let mut kinds = BTreeMap::new();
+        for (ident, ty) in &self.types {
+            kinds.insert(ident.clone(), self.fields_of(ident, None));
+            for variant in ty
+                .get("variants")
+                .and_then(Value::as_object)
+                .into_iter()
+                .flatten()
+            {
+                let kind = format!("{}::{}", ident, variant.0);
+                kinds.insert(kind, self.fields_of(ident, Some(variant.0)));
+            }
+        }
+        writeln!(out, "pub const KINDS: &[(&str, &[&str])] = &[").unwrap();
+        for (kind, fields) in kinds {
+            writeln!(out, "({:?}, &{:?}),", kind, fields).unwrap();
+        }
+        writeln!(out, "];").unwrap();
+    }
+}
@@ -4,13 +4,17 @@ use crate::parse::{self, Cursor};
 use crate::rcvec::{RcVec, RcVecBuilder, RcVecIntoIter, RcVecMut};
 use crate::{Delimiter, Spacing, TokenTree};
 #[cfg(all(span_locations, not(fuzzing)))]
-use core::cell::RefCell;
+use core::cell::Cell;
 #[cfg(span_locations)]
 use core::cmp;
 use core::fmt::{self, Debug, Display, Write};
 use core::ops::RangeBounds;
 use core::str::FromStr;
 use std::path::PathBuf;
+#[cfg(all(span_locations, not(fuzzing)))]
+use std::collections::BTreeMap;
+#[cfg(all(span_locations, not(fuzzing)))]
+use std::sync::{PoisonError, RwLock};
 
 /// Force use of proc-macro2's fallback implementation of the API for now, even
 /// if the compiler's implementation is available.
@@ -125,41 +129,97 @@ impl TokenStreamBuilder {
 }
 
 #[cfg(span_locations)]
-fn get_cursor(src: &str) -> Cursor {
+fn get_cursor(src: &str, path: Option<PathBuf>) -> Cursor {
     #[cfg(fuzzing)]
-    return Cursor { rest: src, off: 1 };
+    return {
+        let _ = path;
+        Cursor {
+            rest: src,
+            off: 1,
+            file: 0,
+        }
+    };
 
-    // Create a dummy file & add it to the source map
+    // Create a (possibly dummy) file & add it to the source map
     #[cfg(not(fuzzing))]
-    SOURCE_MAP.with(|cm| {
-        let mut cm = cm.borrow_mut();
-        let span = cm.add_file(src);
+    {
+        let file = FileInfo::new(src, path);
+        let span = file.span;
+        let id = locked(|| {
+            SOURCE_MAP
+                .write()
+                .unwrap_or_else(PoisonError::into_inner)
+                .add_file(file)
+        });
         Cursor {
             rest: src,
             off: span.lo,
+            file: id,
         }
-    })
+    }
 }
 
 #[cfg(not(span_locations))]
-fn get_cursor(src: &str) -> Cursor {
+fn get_cursor(src: &str, _path: Option<PathBuf>) -> Cursor {
     Cursor { rest: src }
 }
 
+fn lex(mut cursor: Cursor) -> Result<TokenStream, LexError> {
+    // Strip a byte order mark if present
+    const BYTE_ORDER_MARK: &str = "\u{feff}";
+    if cursor.starts_with(BYTE_ORDER_MARK) {
+        cursor = cursor.advance(BYTE_ORDER_MARK.len());
+    }
+
+    parse::token_stream(cursor)
+}
+
+impl TokenStream {
+    /// Same as `FromStr`, but spans of the resulting tokens will report
+    /// `path` as their source file.
+    #[cfg(span_locations)]
+    pub(crate) fn from_str_with_path(src: &str, path: PathBuf) -> Result<TokenStream, LexError> {
+        lex(get_cursor(src, Some(path)))
+    }
+
+    /// Same as `from_str_with_path`, but the file is removed from the source
+    /// map once the returned guard is dropped.
+    #[cfg(span_locations)]
+    pub(crate) fn from_str_with_path_guarded(
+        src: &str,
+        path: PathBuf,
+    ) -> (Result<TokenStream, LexError>, SourceFileGuard) {
+        let cursor = get_cursor(src, Some(path));
+        let guard = SourceFileGuard { file: cursor.file };
+        (lex(cursor), guard)
+    }
+}
+
+/// Removes a file from the source map when dropped, see
+/// `crate::SourceFileGuard`.
+#[cfg(span_locations)]
+#[derive(Debug)]
+pub(crate) struct SourceFileGuard {
+    file: u32,
+}
+
+#[cfg(all(span_locations, not(fuzzing)))]
+impl Drop for SourceFileGuard {
+    fn drop(&mut self) {
+        locked(|| {
+            SOURCE_MAP
+                .write()
+                .unwrap_or_else(PoisonError::into_inner)
+                .remove_file(self.file);
+        });
+    }
+}
+
 impl FromStr for TokenStream {
     type Err = LexError;
 
     fn from_str(src: &str) -> Result<TokenStream, LexError> {
-        // Create a dummy file & add it to the source map
-        let mut cursor = get_cursor(src);
-
-        // Strip a byte order mark if present
-        const BYTE_ORDER_MARK: &str = "\u{feff}";
-        if cursor.starts_with(BYTE_ORDER_MARK) {
-            cursor = cursor.advance(BYTE_ORDER_MARK.len());
-        }
-
-        parse::token_stream(cursor)
+        lex(get_cursor(src, None))
     }
 }
 
@@ -300,30 +360,91 @@ impl Debug for SourceFile {
     }
 }
 
+// NOTE: Unlike upstream, where the source map is thread-local and spans are
+// offsets into one big per-thread address space, the source map here is shared
+// by the whole process and every span names the file it points into. This way
+// a syntax tree parsed on one thread can be inspected on any other, and the
+// lookups from different threads only ever contend for a read lock.
+//
+// The files lexed with a `SourceFileGuard` are removed once it is dropped, so
+// that parsing many files one after another does not keep all of their text.
+//
+// The lock is only ever held for a lookup or an update of the map, and never
+// while running anything that could take it again: the lexing happens outside
+// of it, and nothing is called back while it's held. A thread taking it again
+// would deadlock (even for reading, once a writer on another thread waits in
+// between), which is easy to hit when the files are lexed and inspected on a
+// thread pool that runs other tasks while waiting. `locked` turns that into a
+// panic instead.
+#[cfg(all(span_locations, not(fuzzing)))]
+static SOURCE_MAP: RwLock<SourceMap> = RwLock::new(SourceMap {
+    files: BTreeMap::new(),
+    next_file: 1,
+});
+
 #[cfg(all(span_locations, not(fuzzing)))]
 thread_local! {
-    static SOURCE_MAP: RefCell<SourceMap> = RefCell::new(SourceMap {
-        // NOTE: We start with a single dummy file which all call_site() and
-        // def_site() spans reference.
-        files: vec![FileInfo {
-            source_text: String::new(),
-            span: Span { lo: 0, hi: 0 },
-            lines: vec![0],
-        }],
-    });
+    // Whether this thread holds the lock of the source map.
+    static LOCKED: Cell<bool> = Cell::new(false);
+}
+
+/// Runs `f`, which takes the lock of the source map, panicking if this thread
+/// already holds it
+#[cfg(all(span_locations, not(fuzzing)))]
+fn locked<R>(f: impl FnOnce() -> R) -> R {
+    struct Unlock;
+
+    impl Drop for Unlock {
+        fn drop(&mut self) {
+            LOCKED.with(|locked| locked.set(false));
+        }
+    }
+
+    if LOCKED.with(|locked| locked.replace(true)) {
+        panic!("The source map is already locked by this thread");
+    }
+    let _unlock = Unlock;
+    f()
+}
+
+#[cfg(all(span_locations, not(fuzzing)))]
+fn with_source_map<R>(f: impl FnOnce(&SourceMap) -> R) -> R {
+    locked(|| {
+        let cm = SOURCE_MAP.read().unwrap_or_else(PoisonError::into_inner);
+        f(&cm)
+    })
 }
 
 #[cfg(all(span_locations, not(fuzzing)))]
 struct FileInfo {
     source_text: String,
+    path: Option<PathBuf>,
     span: Span,
     lines: Vec<usize>,
 }
 
 #[cfg(all(span_locations, not(fuzzing)))]
 impl FileInfo {
+    /// Creates a file that is not yet placed in the source map
+    fn new(src: &str, path: Option<PathBuf>) -> Self {
+        let (len, lines) = lines_offsets(src);
+        FileInfo {
+            source_text: src.to_owned(),
+            path,
+            // Offsets within a file start from 1, so that they are never
+            // confused with call_site()
+            span: Span {
+                file: 0,
+                lo: 1,
+                hi: 1 + len as u32,
+            },
+            lines,
+        }
+    }
+
     fn offset_line_column(&self, offset: usize) -> LineColumn {
         assert!(self.span_within(Span {
+            file: self.span.file,
             lo: offset as u32,
             hi: offset as u32
         }));
@@ -341,7 +462,7 @@ impl FileInfo {
     }
 
     fn span_within(&self, span: Span) -> bool {
-        span.lo >= self.span.lo && span.hi <= self.span.hi
+        span.file == self.span.file && span.lo >= self.span.lo && span.hi <= self.span.hi
     }
 
     fn source_text(&self, span: Span) -> String {
@@ -375,62 +496,58 @@ fn lines_offsets(s: &str) -> (usize, Vec<usize>) {
 
 #[cfg(all(span_locations, not(fuzzing)))]
 struct SourceMap {
-    files: Vec<FileInfo>,
+    // Keyed by the file id; id 0 is reserved for the call_site() and
+    // def_site() spans, which point into no file at all. The ids are never
+    // reused, so the spans into a removed file don't resolve to another one.
+    files: BTreeMap<u32, FileInfo>,
+    next_file: u32,
 }
 
 #[cfg(all(span_locations, not(fuzzing)))]
 impl SourceMap {
-    fn next_start_pos(&self) -> u32 {
-        // Add 1 so there's always space between files.
-        //
-        // We'll always have at least 1 file, as we initialize our files list
-        // with a dummy file.
-        self.files.last().unwrap().span.hi + 1
+    /// Adds a file to the source map, returning its id
+    fn add_file(&mut self, mut file: FileInfo) -> u32 {
+        let id = self.next_file;
+        self.next_file = id.checked_add(1).expect("too many files in the source map");
+        file.span.file = id;
+        self.files.insert(id, file);
+        id
     }
 
-    fn add_file(&mut self, src: &str) -> Span {
-        let (len, lines) = lines_offsets(src);
-        let lo = self.next_start_pos();
-        let span = Span {
-            lo,
-            hi: lo + (len as u32),
-        };
-
-        self.files.push(FileInfo {
-            source_text: src.to_owned(),
-            span,
-            lines,
-        });
-
-        span
+    fn remove_file(&mut self, id: u32) {
+        self.files.remove(&id);
     }
 
-    #[cfg(procmacro2_semver_exempt)]
+    #[cfg(any(procmacro2_semver_exempt, span_locations))]
     fn filepath(&self, span: Span) -> PathBuf {
-        for (i, file) in self.files.iter().enumerate() {
-            if file.span_within(span) {
-                return PathBuf::from(if i == 0 {
-                    "<unspecified>".to_owned()
-                } else {
-                    format!("<parsed string {}>", i)
-                });
-            }
+        match self.fileinfo(span) {
+            None => PathBuf::from("<unspecified>"),
+            Some(FileInfo {
+                path: Some(path), ..
+            }) => path.clone(),
+            Some(_) => PathBuf::from(format!("<parsed string {}>", span.file)),
         }
-        unreachable!("Invalid span with no related FileInfo!");
     }
 
-    fn fileinfo(&self, span: Span) -> &FileInfo {
-        for file in &self.files {
-            if file.span_within(span) {
-                return file;
-            }
+    /// The file of a span, `None` for the call_site() spans and the spans
+    /// into the files that were removed
+    fn fileinfo(&self, span: Span) -> Option<&FileInfo> {
+        if span.file == 0 {
+            return None;
         }
-        unreachable!("Invalid span with no related FileInfo!");
+        if span.file >= self.next_file {
+            unreachable!("Invalid span with no related FileInfo!");
+        }
+        let file = self.files.get(&span.file)?;
+        assert!(file.span_within(span), "Span out of the bounds of its file");
+        Some(file)
     }
 }
 
 #[derive(Clone, Copy, PartialEq, Eq)]
 pub(crate) struct Span {
+    #[cfg(span_locations)]
+    pub(crate) file: u32,
     #[cfg(span_locations)]
     pub(crate) lo: u32,
     #[cfg(span_locations)]
@@ -445,7 +562,11 @@ impl Span {
 
     #[cfg(span_locations)]
     pub fn call_site() -> Self {
-        Span { lo: 0, hi: 0 }
+        Span {
+            file: 0,
+            lo: 0,
+            hi: 0,
+        }
     }
 
     pub fn mixed_site() -> Self {
@@ -468,7 +589,7 @@ impl Span {
         other
     }
 
-    #[cfg(procmacro2_semver_exempt)]
+    #[cfg(any(procmacro2_semver_exempt, span_locations))]
     pub fn source_file(&self) -> SourceFile {
         #[cfg(fuzzing)]
         return SourceFile {
@@ -476,8 +597,7 @@ impl Span {
         };
 
         #[cfg(not(fuzzing))]
-        SOURCE_MAP.with(|cm| {
-            let cm = cm.borrow();
+        with_source_map(|cm| {
             let path = cm.filepath(*self);
             SourceFile { path }
         })
@@ -489,10 +609,9 @@ impl Span {
         return LineColumn { line: 0, column: 0 };
 
         #[cfg(not(fuzzing))]
-        SOURCE_MAP.with(|cm| {
-            let cm = cm.borrow();
-            let fi = cm.fileinfo(*self);
-            fi.offset_line_column(self.lo as usize)
+        with_source_map(|cm| match cm.fileinfo(*self) {
+            Some(fi) => fi.offset_line_column(self.lo as usize),
+            None => LineColumn { line: 1, column: 0 },
         })
     }
 
@@ -502,10 +621,9 @@ impl Span {
         return LineColumn { line: 0, column: 0 };
 
         #[cfg(not(fuzzing))]
-        SOURCE_MAP.with(|cm| {
-            let cm = cm.borrow();
-            let fi = cm.fileinfo(*self);
-            fi.offset_line_column(self.hi as usize)
+        with_source_map(|cm| match cm.fileinfo(*self) {
+            Some(fi) => fi.offset_line_column(self.hi as usize),
+            None => LineColumn { line: 1, column: 0 },
         })
     }
 
@@ -522,17 +640,17 @@ impl Span {
             None
         };
 
+        // If `other` is not within the same FileInfo as us, return None.
         #[cfg(not(fuzzing))]
-        SOURCE_MAP.with(|cm| {
-            let cm = cm.borrow();
-            // If `other` is not within the same FileInfo as us, return None.
-            if !cm.fileinfo(*self).span_within(other) {
-                return None;
-            }
-            Some(Span {
-                lo: cmp::min(self.lo, other.lo),
-                hi: cmp::max(self.hi, other.hi),
-            })
+        if self.file != other.file {
+            return None;
+        }
+
+        #[cfg(not(fuzzing))]
+        Some(Span {
+            file: self.file,
+            lo: cmp::min(self.lo, other.lo),
+            hi: cmp::max(self.hi, other.hi),
         })
     }
 
@@ -551,7 +669,7 @@ impl Span {
             if self.is_call_site() {
                 None
             } else {
-                Some(SOURCE_MAP.with(|cm| cm.borrow().fileinfo(*self).source_text(*self)))
+                with_source_map(|cm| cm.fileinfo(*self).map(|fi| fi.source_text(*self)))
             }
         }
     }
@@ -564,6 +682,7 @@ impl Span {
     #[cfg(span_locations)]
     pub(crate) fn first_byte(self) -> Self {
         Span {
+            file: self.file,
             lo: self.lo,
             hi: cmp::min(self.lo.saturating_add(1), self.hi),
         }
@@ -577,6 +696,7 @@ impl Span {
     #[cfg(span_locations)]
     pub(crate) fn last_byte(self) -> Self {
         Span {
+            file: self.file,
             lo: cmp::max(self.hi.saturating_sub(1), self.lo),
             hi: self.hi,
         }
@@ -584,7 +704,7 @@ impl Span {
 
     #[cfg(span_locations)]
     fn is_call_site(&self) -> bool {
-        self.lo == 0 && self.hi == 0
+        self.file == 0
     }
 }
 
@@ -1013,7 +1133,11 @@ impl Literal {
                 Bound::Unbounded => self.span.hi,
             };
             if lo <= hi && hi <= self.span.hi {
-                Some(Span { lo, hi })
+                Some(Span {
+                    file: self.span.file,
+                    lo,
+                    hi,
+                })
             } else {
                 None
             }
@@ -1025,7 +1149,7 @@ impl FromStr for Literal {
     type Err = LexError;
 
     fn from_str(repr: &str) -> Result<Self, Self::Err> {
-        let mut cursor = get_cursor(repr);
+        let mut cursor = get_cursor(repr, None);
         #[cfg(span_locations)]
         let lo = cursor.off;
 
@@ -1043,6 +1167,8 @@ impl FromStr for Literal {
                     literal.repr.insert(0, '-');
                 }
                 literal.span = Span {
+                    #[cfg(span_locations)]
+                    file: rest.file,
                     #[cfg(span_locations)]
                     lo,
                     #[cfg(span_locations)]
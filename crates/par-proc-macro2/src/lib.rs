@@ -144,7 +144,7 @@ use core::hash::{Hash, Hasher};
 use core::ops::RangeBounds;
 use core::str::FromStr;
 use std::error::Error;
-#[cfg(procmacro2_semver_exempt)]
+#[cfg(any(procmacro2_semver_exempt, span_locations))]
 use std::path::PathBuf;
 
 #[cfg(span_locations)]
@@ -193,6 +193,59 @@ impl TokenStream {
     pub fn is_empty(&self) -> bool {
         self.inner.is_empty()
     }
+
+    /// Parses a string into a token stream, like the `FromStr` impl does, but
+    /// records `path` as the [`SourceFile`] of the resulting spans.
+    ///
+    /// This method requires the `"span-locations"` feature to be enabled.
+    #[cfg(span_locations)]
+    #[cfg_attr(doc_cfg, doc(cfg(feature = "span-locations")))]
+    pub fn from_str_with_path(src: &str, path: PathBuf) -> Result<TokenStream, LexError> {
+        let e = imp::TokenStream::from_str_with_path(src, path).map_err(|e| LexError {
+            inner: e,
+            _marker: Marker,
+        })?;
+        Ok(TokenStream::_new(e))
+    }
+
+    /// Same as [`from_str_with_path`](Self::from_str_with_path), but the file
+    /// is only kept in the source map while the returned guard is alive.
+    ///
+    /// The guard should be kept for as long as the spans of the tokens are
+    /// used, including the span of the error if lexing fails.
+    ///
+    /// This method requires the `"span-locations"` feature to be enabled.
+    #[cfg(span_locations)]
+    #[cfg_attr(doc_cfg, doc(cfg(feature = "span-locations")))]
+    pub fn from_str_with_path_guarded(
+        src: &str,
+        path: PathBuf,
+    ) -> (Result<TokenStream, LexError>, SourceFileGuard) {
+        let (tokens, guard) = imp::TokenStream::from_str_with_path_guarded(src, path);
+        let tokens = tokens.map(TokenStream::_new).map_err(|e| LexError {
+            inner: e,
+            _marker: Marker,
+        });
+        (tokens, SourceFileGuard { _inner: guard })
+    }
+}
+
+/// Keeps a file lexed with [`TokenStream::from_str_with_path_guarded`] in the
+/// source map.
+///
+/// The line/column and the source text of a span are looked up in the text of
+/// its file, so the source map keeps the text of every file lexed from a
+/// string for the rest of the process. A file lexed with a guard is removed as
+/// soon as the guard is dropped instead, after which the spans into it behave
+/// like [`Span::call_site`]: they are at line 1, column 0, and have no source
+/// text.
+///
+/// This type requires the `"span-locations"` feature to be enabled.
+#[cfg(span_locations)]
+#[cfg_attr(doc_cfg, doc(cfg(feature = "span-locations")))]
+#[derive(Debug)]
+pub struct SourceFileGuard {
+    _inner: imp::SourceFileGuard,
 }
 
 /// `TokenStream::default()` returns an empty stream,
@@ -310,7 +363,7 @@ impl Error for LexError {}
 /// The source file of a given `Span`.
 ///
 /// This type is semver exempt and not exposed by default.
-#[cfg(procmacro2_semver_exempt)]
+#[cfg(any(procmacro2_semver_exempt, span_locations))]
 #[cfg_attr(doc_cfg, doc(cfg(procmacro2_semver_exempt)))]
 #[derive(Clone, PartialEq, Eq)]
 pub struct SourceFile {
@@ -318,7 +371,7 @@ pub struct SourceFile {
     _marker: Marker,
 }
 
-#[cfg(procmacro2_semver_exempt)]
+#[cfg(any(procmacro2_semver_exempt, span_locations))]
 impl SourceFile {
     fn _new(inner: imp::SourceFile) -> Self {
         SourceFile {
@@ -351,7 +404,7 @@ impl SourceFile {
     }
 }
 
-#[cfg(procmacro2_semver_exempt)]
+#[cfg(any(procmacro2_semver_exempt, span_locations))]
 impl Debug for SourceFile {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         Debug::fmt(&self.inner, f)
@@ -419,8 +472,9 @@ impl Span {
 
     /// The original source file into which this span points.
     ///
-    /// This method is semver exempt and not exposed by default.
-    #[cfg(procmacro2_semver_exempt)]
+    /// This method is semver exempt and not exposed by default, unless the
+    /// `"span-locations"` feature is enabled.
+    #[cfg(any(procmacro2_semver_exempt, span_locations))]
     #[cfg_attr(doc_cfg, doc(cfg(procmacro2_semver_exempt)))]
     pub fn source_file(&self) -> SourceFile {
         SourceFile::_new(self.inner.source_file())
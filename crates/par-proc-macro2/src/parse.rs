@@ -11,6 +11,8 @@ pub(crate) struct Cursor<'a> {
     pub rest: &'a str,
     #[cfg(span_locations)]
     pub off: u32,
+    #[cfg(span_locations)]
+    pub file: u32,
 }
 
 impl<'a> Cursor<'a> {
@@ -20,6 +22,8 @@ impl<'a> Cursor<'a> {
             rest,
             #[cfg(span_locations)]
             off: self.off + _front.chars().count() as u32,
+            #[cfg(span_locations)]
+            file: self.file,
         }
     }
 
@@ -187,7 +191,11 @@ pub(crate) fn token_stream(mut input: Cursor) -> Result<TokenStream, LexError> {
                 #[cfg(span_locations)]
                 Some((lo, _frame)) => {
                     return Err(LexError {
-                        span: Span { lo: *lo, hi: *lo },
+                        span: Span {
+                            file: input.file,
+                            lo: *lo,
+                            hi: *lo,
+                        },
                     })
                 }
                 #[cfg(not(span_locations))]
@@ -226,6 +234,8 @@ pub(crate) fn token_stream(mut input: Cursor) -> Result<TokenStream, LexError> {
             input = input.advance(1);
             let mut g = Group::new(open_delimiter, trees.build());
             g.set_span(Span {
+                #[cfg(span_locations)]
+                file: input.file,
                 #[cfg(span_locations)]
                 lo,
                 #[cfg(span_locations)]
@@ -239,6 +249,8 @@ pub(crate) fn token_stream(mut input: Cursor) -> Result<TokenStream, LexError> {
                 Err(Reject) => return Err(lex_error(input)),
             };
             tt.set_span(crate::Span::_new_fallback(Span {
+                #[cfg(span_locations)]
+                file: rest.file,
                 #[cfg(span_locations)]
                 lo,
                 #[cfg(span_locations)]
@@ -255,6 +267,8 @@ fn lex_error(cursor: Cursor) -> LexError {
     let _ = cursor;
     LexError {
         span: Span {
+            #[cfg(span_locations)]
+            file: cursor.file,
             #[cfg(span_locations)]
             lo: cursor.off,
             #[cfg(span_locations)]
@@ -909,6 +923,8 @@ fn doc_comment<'a>(input: Cursor<'a>, trees: &mut TokenStreamBuilder) -> PResult
     let lo = input.off;
     let (rest, (comment, inner)) = doc_comment_contents(input)?;
     let span = crate::Span::_new_fallback(Span {
+        #[cfg(span_locations)]
+        file: rest.file,
         #[cfg(span_locations)]
         lo,
         #[cfg(span_locations)]
@@ -44,14 +44,15 @@ macro_rules! assert_impl {
 assert_impl!(Delimiter is Send and Sync);
 assert_impl!(Spacing is Send and Sync);
 
-assert_impl!(Group is not Send or Sync);
-assert_impl!(Ident is not Send or Sync);
-assert_impl!(LexError is not Send or Sync);
-assert_impl!(Literal is not Send or Sync);
-assert_impl!(Punct is not Send or Sync);
-assert_impl!(Span is not Send or Sync);
-assert_impl!(TokenStream is not Send or Sync);
-assert_impl!(TokenTree is not Send or Sync);
+// Always the fallback implementation, whose types can be sent across threads
+assert_impl!(Group is Send and Sync);
+assert_impl!(Ident is Send and Sync);
+assert_impl!(LexError is Send and Sync);
+assert_impl!(Literal is Send and Sync);
+assert_impl!(Punct is Send and Sync);
+assert_impl!(Span is Send and Sync);
+assert_impl!(TokenStream is Send and Sync);
+assert_impl!(TokenTree is Send and Sync);
 
 #[cfg(procmacro2_semver_exempt)]
 mod semver_exempt {
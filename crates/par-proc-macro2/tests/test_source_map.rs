@@ -0,0 +1,70 @@
+#![cfg(span_locations)]
+
+use proc_macro2::{Span, TokenStream};
+use std::path::PathBuf;
+
+fn first_span(tokens: &TokenStream) -> Span {
+    tokens.clone().into_iter().next().unwrap().span()
+}
+
+#[test]
+fn span_across_threads() {
+    let handles = (0..8)
+        .map(|i| {
+            std::thread::spawn(move || {
+                let src = format!("{}\nfoo_{} + {}", "\n".repeat(i), i, i);
+                let path = PathBuf::from(format!("file_{}.rs", i));
+                let ts = TokenStream::from_str_with_path(&src, path).unwrap();
+                ts.into_iter().map(|tt| tt.span()).collect::<Vec<_>>()
+            })
+        })
+        .collect::<Vec<_>>();
+
+    for (i, handle) in handles.into_iter().enumerate() {
+        let spans = handle.join().unwrap();
+        let spans = spans
+            .iter()
+            .map(|span| {
+                let (start, end) = (span.start(), span.end());
+                (start.line, start.column, end.line, end.column)
+            })
+            .collect::<Vec<_>>();
+        let line = i + 2;
+        assert_eq!(
+            spans,
+            [(line, 0, line, 5), (line, 6, line, 7), (line, 8, line, 9)]
+        );
+    }
+
+    // Spans from different files never join
+    let a = TokenStream::from_str_with_path("a", "a.rs".into()).unwrap();
+    let b = TokenStream::from_str_with_path("b", "b.rs".into()).unwrap();
+    let (a, b) = (first_span(&a), first_span(&b));
+    assert!(a.join(a).is_some());
+    assert!(a.join(b).is_none());
+    assert_eq!(a.source_file().path(), PathBuf::from("a.rs"));
+}
+
+#[test]
+fn guarded_file_is_removed() {
+    let (tokens, guard) = TokenStream::from_str_with_path_guarded("\n  foo", "foo.rs".into());
+    let span = first_span(&tokens.unwrap());
+    assert_eq!((span.start().line, span.start().column), (2, 2));
+    assert_eq!(span.source_text().as_deref(), Some("foo"));
+    assert_eq!(span.source_file().path(), PathBuf::from("foo.rs"));
+
+    // the other files are not affected
+    let other = TokenStream::from_str_with_path("\n\nbar", "bar.rs".into()).unwrap();
+    drop(guard);
+    assert_eq!((span.start().line, span.start().column), (1, 0));
+    assert_eq!(span.source_text(), None);
+    assert_eq!(span.source_file().path(), PathBuf::from("<unspecified>"));
+    assert_eq!(first_span(&other).start().line, 3);
+
+    // the error keeps its location as long as the guard is alive
+    let (error, guard) = TokenStream::from_str_with_path_guarded("\n(]", "err.rs".into());
+    let error = error.unwrap_err();
+    assert_eq!(error.span().start().line, 2);
+    drop(guard);
+    assert_eq!(error.span().start().line, 1);
+}
@@ -2,7 +2,7 @@ use crate::{cfg, file, lookup};
 use anyhow::Result;
 use proc_macro2::{Ident, Span, TokenStream};
 use quote::{format_ident, quote};
-use syn_codegen::{Data, Definitions, Node, Type};
+use syn_codegen::{Data, Definitions, Node};
 
 const CLONE_SRC: &str = "src/gen/clone.rs";
 
@@ -28,12 +28,8 @@ fn expand_impl_body(defs: &Definitions, node: &Node) -> TokenStream {
                         pats.push(pat);
                     }
                     let mut cfg = None;
-                    if node.ident == "Expr" {
-                        if let Type::Syn(ty) = &fields[0] {
-                            if !lookup::node(defs, ty).features.any.contains("derive") {
-                                cfg = Some(quote!(#[cfg(feature = "full")]));
-                            }
-                        }
+                    if lookup::is_full_variant(defs, node, fields) {
+                        cfg = Some(quote!(#[cfg(feature = "full")]));
                     }
                     quote! {
                         #cfg
@@ -41,7 +37,7 @@ fn expand_impl_body(defs: &Definitions, node: &Node) -> TokenStream {
                     }
                 }
             });
-            let nonexhaustive = if node.ident == "Expr" {
+            let nonexhaustive = if lookup::has_full_variants(defs, node) {
                 Some(quote! {
                     #[cfg(not(feature = "full"))]
                     _ => unreachable!(),
@@ -61,12 +61,8 @@ fn expand_impl_body(
                     }
                 } else {
                     let mut cfg = None;
-                    if node.ident == "Expr" {
-                        if let Type::Syn(ty) = &fields[0] {
-                            if !lookup::node(defs, ty).features.any.contains("derive") {
-                                cfg = Some(quote!(#[cfg(feature = "full")]));
-                            }
-                        }
+                    if lookup::is_full_variant(defs, node, fields) {
+                        cfg = Some(quote!(#[cfg(feature = "full")]));
                     }
                     if syntax_tree_enum(type_name, variant_name, fields).is_some() {
                         quote! {
@@ -88,7 +84,7 @@ fn expand_impl_body(
                     }
                 }
             });
-            let nonexhaustive = if node.ident == "Expr" {
+            let nonexhaustive = if lookup::has_full_variants(defs, node) {
                 Some(quote! {
                     #[cfg(not(feature = "full"))]
                     _ => unreachable!(),
@@ -57,12 +57,8 @@ fn expand_impl_body(defs: &Definitions, node: &Node) -> TokenStream {
                         comparisons.push(quote!(true));
                     }
                     let mut cfg = None;
-                    if node.ident == "Expr" {
-                        if let Type::Syn(ty) = &fields[0] {
-                            if !lookup::node(defs, ty).features.any.contains("derive") {
-                                cfg = Some(quote!(#[cfg(feature = "full")]));
-                            }
-                        }
+                    if lookup::is_full_variant(defs, node, fields) {
+                        cfg = Some(quote!(#[cfg(feature = "full")]));
                     }
                     quote! {
                         #cfg
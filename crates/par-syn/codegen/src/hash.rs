@@ -60,12 +60,8 @@ fn expand_impl_body(defs: &Definitions, node: &Node) -> TokenStream {
                             pats.push(var);
                         }
                         let mut cfg = None;
-                        if node.ident == "Expr" {
-                            if let Type::Syn(ty) = &fields[0] {
-                                if !lookup::node(defs, ty).features.any.contains("derive") {
-                                    cfg = Some(quote!(#[cfg(feature = "full")]));
-                                }
-                            }
+                        if lookup::is_full_variant(defs, node, fields) {
+                            cfg = Some(quote!(#[cfg(feature = "full")]));
                         }
                         quote! {
                             #cfg
@@ -76,7 +72,7 @@ fn expand_impl_body(defs: &Definitions, node: &Node) -> TokenStream {
                         }
                     }
                 });
-            let nonexhaustive = if node.ident == "Expr" {
+            let nonexhaustive = if lookup::has_full_variants(defs, node) {
                 Some(quote! {
                     #[cfg(not(feature = "full"))]
                     _ => unreachable!(),
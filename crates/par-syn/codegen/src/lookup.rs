@@ -1,4 +1,4 @@
-use syn_codegen::{Definitions, Node};
+use syn_codegen::{Data, Definitions, Node, Type};
 
 pub fn node<'a>(defs: &'a Definitions, name: &str) -> &'a Node {
     for node in &defs.types {
@@ -8,3 +8,24 @@ pub fn node<'a>(defs: &'a Definitions, name: &str) -> &'a Node {
     }
     panic!("not found: {}", name)
 }
+
+/// Whether the variant of an enum that is there without the "full" feature
+/// holds a type that is only there with it, so its arms need a cfg
+pub fn is_full_variant(defs: &Definitions, enum_node: &Node, fields: &[Type]) -> bool {
+    let Some(Type::Syn(ty)) = fields.first() else {
+        return false;
+    };
+    let features = &node(defs, ty).features.any;
+    enum_node.features.any.contains("derive") && features.len() == 1 && features.contains("full")
+}
+
+/// Whether a match on the enum needs an arm for the variants left out without
+/// the "full" feature
+pub fn has_full_variants(defs: &Definitions, enum_node: &Node) -> bool {
+    match &enum_node.data {
+        Data::Enum(variants) => variants
+            .values()
+            .any(|fields| is_full_variant(defs, enum_node, fields)),
+        _ => false,
+    }
+}
@@ -131,6 +131,9 @@ ast_enum_of_structs! {
         /// A for loop: `for pat in expr { ... }`.
         ForLoop(ExprForLoop),
 
+        /// A gen block: `gen { ... }`, or `async gen { ... }`.
+        Gen(ExprGen),
+
         /// An expression contained within invisible delimiters.
         ///
         /// This variant is important for faithfully representing the precedence
@@ -271,6 +274,18 @@ ast_struct! {
     }
 }
 
+ast_struct! {
+    /// A gen block: `gen { ... }`, or `async gen { ... }`.
+    #[cfg_attr(doc_cfg, doc(cfg(feature = "full")))]
+    pub struct ExprGen #full {
+        pub attrs: Vec<Attribute>,
+        pub async_token: Option<Token![async]>,
+        pub gen_token: Token![gen],
+        pub capture: Option<Token![move]>,
+        pub block: Block,
+    }
+}
+
 ast_struct! {
     /// An await expression: `fut.await`.
     #[cfg_attr(doc_cfg, doc(cfg(feature = "full")))]
@@ -699,6 +714,7 @@ impl Expr {
             | Expr::Continue(ExprContinue { attrs, .. })
             | Expr::Field(ExprField { attrs, .. })
             | Expr::ForLoop(ExprForLoop { attrs, .. })
+            | Expr::Gen(ExprGen { attrs, .. })
             | Expr::Group(ExprGroup { attrs, .. })
             | Expr::If(ExprIf { attrs, .. })
             | Expr::Index(ExprIndex { attrs, .. })
@@ -932,6 +948,7 @@ pub(crate) fn requires_terminator(expr: &Expr) -> bool {
         | Expr::Closure(_)
         | Expr::Continue(_)
         | Expr::Field(_)
+        | Expr::Gen(_)
         | Expr::Group(_)
         | Expr::Index(_)
         | Expr::Infer(_)
@@ -1586,6 +1603,8 @@ pub(crate) mod parsing {
             && (input.peek2(token::Brace) || input.peek2(Token![move]) && input.peek3(token::Brace))
         {
             input.parse().map(Expr::Async)
+        } else if peek_gen_block(input, allow_struct) {
+            input.parse().map(Expr::Gen)
         } else if input.peek(Token![try]) && input.peek2(token::Brace) {
             input.parse().map(Expr::TryBlock)
         } else if input.peek(Token![|])
@@ -2329,6 +2348,53 @@ pub(crate) mod parsing {
         }
     }
 
+    #[cfg(feature = "full")]
+    fn peek_gen_block(input: ParseStream, allow_struct: AllowStruct) -> bool {
+        if input.peek(Token![async]) {
+            return input.peek2(Token![gen])
+                && (input.peek3(token::Brace) || input.peek3(Token![move]));
+        }
+        // `gen` is only reserved since edition 2024, so in a condition or a
+        // scrutinee `gen {` is an identifier followed by the body, and
+        // `gen { a: 1 }` is a struct literal of a struct named `gen`
+        allow_struct.0
+            && input.peek(Token![gen])
+            && (input.peek2(token::Brace) && !peek_gen_struct(input)
+                || input.peek2(Token![move]) && input.peek3(token::Brace))
+    }
+
+    #[cfg(feature = "full")]
+    fn peek_gen_struct(input: ParseStream) -> bool {
+        let ahead = input.fork();
+        if ahead.parse::<Token![gen]>().is_err() {
+            return false;
+        }
+        let content = match crate::group::parse_braces(&ahead) {
+            Ok(braces) => braces.content,
+            Err(_) => return false,
+        };
+        // `a:`, `a,` and `0:` only start the fields of a struct literal, and a
+        // leading `..` is taken as its base rather than as a range
+        content.peek(Token![..])
+            || (content.peek(Ident) || content.peek(LitInt))
+                && (content.peek2(Token![:]) && !content.peek2(Token![::])
+                    || content.peek2(Token![,]))
+    }
+
+    #[cfg(feature = "full")]
+    #[cfg_attr(doc_cfg, doc(cfg(feature = "parsing")))]
+    impl Parse for ExprGen {
+        fn parse(input: ParseStream) -> Result<Self> {
+            Ok(ExprGen {
+                attrs: Vec::new(),
+                async_token: input.parse()?,
+                gen_token: input.parse()?,
+                capture: input.parse()?,
+                block: input.parse()?,
+            })
+        }
+    }
+
     #[cfg(feature = "full")]
     fn closure_arg(input: ParseStream) -> Result<Pat> {
         let attrs = input.call(Attribute::parse_outer)?;
@@ -2883,6 +2949,18 @@ pub(crate) mod printing {
         }
     }
 
+    #[cfg(feature = "full")]
+    #[cfg_attr(doc_cfg, doc(cfg(feature = "printing")))]
+    impl ToTokens for ExprGen {
+        fn to_tokens(&self, tokens: &mut TokenStream) {
+            outer_attrs_to_tokens(&self.attrs, tokens);
+            self.async_token.to_tokens(tokens);
+            self.gen_token.to_tokens(tokens);
+            self.capture.to_tokens(tokens);
+            self.block.to_tokens(tokens);
+        }
+    }
+
     #[cfg(feature = "full")]
     #[cfg_attr(doc_cfg, doc(cfg(feature = "printing")))]
     impl ToTokens for ExprAwait {
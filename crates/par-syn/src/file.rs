@@ -88,7 +88,11 @@ ast_struct! {
 #[cfg(feature = "parsing")]
 pub(crate) mod parsing {
     use super::*;
-    use crate::parse::{Parse, ParseStream, Result};
+    use crate::buffer::Cursor;
+    use crate::parse::discouraged::Speculative;
+    use crate::parse::{Parse, ParseStream, Parser, Result, StepCursor};
+    use proc_macro2::{Delimiter, Group, Spacing, TokenStream, TokenTree};
+    use std::iter;
 
     #[cfg_attr(doc_cfg, doc(cfg(feature = "parsing")))]
     impl Parse for File {
@@ -106,6 +110,180 @@ pub(crate) mod parsing {
             })
         }
     }
+
+    /// Parses a file like `File::parse`, but keeps the items that fail to
+    /// parse as `Item::Verbatim` instead of failing the whole file.
+    ///
+    /// An unparseable item is assumed to end with its body, i.e. the first
+    /// brace-delimited group at its top level that is not a generic argument
+    /// or part of an initializer, or else with the first `;` at its top level.
+    ///
+    /// The items of modules, impls, traits and extern blocks are recovered the
+    /// same way when the header of the block parses.
+    pub(crate) fn parse_recovering(input: ParseStream) -> Result<(File, Vec<Error>)> {
+        let attrs = input.call(Attribute::parse_inner)?;
+        let mut errors = Vec::new();
+        let items = parse_items_recovering(input, &mut errors, recover_nested, Item::Verbatim)?;
+        let file = File {
+            shebang: None,
+            attrs,
+            items,
+        };
+        Ok((file, errors))
+    }
+
+    fn parse_items_recovering<T: Parse>(
+        input: ParseStream,
+        errors: &mut Vec<Error>,
+        recover: fn(TokenStream, &mut Vec<Error>) -> Option<T>,
+        verbatim: fn(TokenStream) -> T,
+    ) -> Result<Vec<T>> {
+        let mut items = Vec::new();
+        while !input.is_empty() {
+            let fork = input.fork();
+            match fork.parse::<T>() {
+                Ok(item) => {
+                    input.advance_to(&fork);
+                    items.push(item);
+                }
+                Err(err) => {
+                    let tokens = input.step(skip_item)?;
+                    items.push(match recover(tokens.clone(), errors) {
+                        Some(item) => item,
+                        None => {
+                            errors.push(err);
+                            verbatim(tokens)
+                        }
+                    });
+                }
+            }
+        }
+        Ok(items)
+    }
+
+    /// Parses an item that failed to parse as a whole by its header and the
+    /// items of its body separately, if the errors are in the items of the
+    /// body.
+    fn recover_nested(tokens: TokenStream, errors: &mut Vec<Error>) -> Option<Item> {
+        let mut header: Vec<TokenTree> = tokens.into_iter().collect();
+        let body = match header.pop() {
+            Some(TokenTree::Group(group)) if group.delimiter() == Delimiter::Brace => group,
+            _ => return None,
+        };
+        let mut empty = Group::new(Delimiter::Brace, TokenStream::new());
+        empty.set_span(body.span());
+        header.push(TokenTree::Group(empty));
+        let mut item = Item::parse.parse2(header.into_iter().collect()).ok()?;
+
+        let brace_token = token::Brace(body.delim_span());
+        let mut nested = Vec::new();
+        match &mut item {
+            Item::Mod(item) => {
+                let items = (|input: ParseStream| {
+                    item.attrs.extend(input.call(Attribute::parse_inner)?);
+                    parse_items_recovering(input, &mut nested, recover_nested, Item::Verbatim)
+                })
+                .parse2(body.stream())
+                .ok()?;
+                item.content = Some((brace_token, items));
+            }
+            Item::Impl(item) => {
+                item.items = (|input: ParseStream| {
+                    item.attrs.extend(input.call(Attribute::parse_inner)?);
+                    parse_items_recovering(input, &mut nested, |_, _| None, ImplItem::Verbatim)
+                })
+                .parse2(body.stream())
+                .ok()?;
+                item.brace_token = brace_token;
+            }
+            Item::Trait(item) => {
+                item.items = (|input: ParseStream| {
+                    item.attrs.extend(input.call(Attribute::parse_inner)?);
+                    parse_items_recovering(input, &mut nested, |_, _| None, TraitItem::Verbatim)
+                })
+                .parse2(body.stream())
+                .ok()?;
+                item.brace_token = brace_token;
+            }
+            Item::ForeignMod(item) => {
+                item.items = (|input: ParseStream| {
+                    item.attrs.extend(input.call(Attribute::parse_inner)?);
+                    parse_items_recovering(input, &mut nested, |_, _| None, ForeignItem::Verbatim)
+                })
+                .parse2(body.stream())
+                .ok()?;
+                item.brace_token = brace_token;
+            }
+            _ => return None,
+        }
+        if nested.is_empty() {
+            // the error is somewhere else, e.g. in the attributes of the body
+            return None;
+        }
+        errors.append(&mut nested);
+        Some(item)
+    }
+
+    fn skip_item<'c>(cursor: StepCursor<'c, '_>) -> Result<(TokenStream, Cursor<'c>)> {
+        let mut rest = *cursor;
+        let mut tokens = TokenStream::new();
+        // the `<` `>` nesting, to tell the body from a brace in the generics,
+        // as in `fn f() where T: Tr<{ N }> {}`
+        let mut angles = 0usize;
+        // whether the item has an initializer, as in `const A: S = S {};`
+        let mut initializer = false;
+        let mut previous: Option<(char, Spacing)> = None;
+        while let Some((tt, next)) = rest.token_tree() {
+            rest = next;
+            let end = match &tt {
+                TokenTree::Punct(punct) => match punct.as_char() {
+                    ';' => true,
+                    '<' if !initializer => {
+                        angles += 1;
+                        false
+                    }
+                    // not the end of `->` or `=>`
+                    '>' if !matches!(previous, Some(('-' | '=', Spacing::Joint))) => {
+                        angles = angles.saturating_sub(1);
+                        false
+                    }
+                    // not a part of `==`, `<=` and the like
+                    '=' if angles == 0
+                        && punct.spacing() == Spacing::Alone
+                        && !matches!(previous, Some((_, Spacing::Joint))) =>
+                    {
+                        initializer = true;
+                        false
+                    }
+                    _ => false,
+                },
+                // a block as a generic argument follows `<`, `,` or `=`, which
+                // also keeps a stray `<` from hiding the body
+                TokenTree::Group(group) => {
+                    group.delimiter() == Delimiter::Brace
+                        && !initializer
+                        && (angles == 0 || !matches!(previous, Some(('<' | ',' | '=', _))))
+                }
+                _ => false,
+            };
+            previous = match &tt {
+                TokenTree::Punct(punct) => Some((punct.as_char(), punct.spacing())),
+                _ => None,
+            };
+            tokens.extend(iter::once(tt));
+            if end {
+                break;
+            }
+        }
+        // the braces can be followed by a `;`, as in `use a::{b, c};`
+        if let Some((TokenTree::Punct(punct), next)) = rest.token_tree() {
+            if punct.as_char() == ';' {
+                tokens.extend(iter::once(TokenTree::Punct(punct)));
+                rest = next;
+            }
+        }
+        Ok((tokens, rest))
+    }
 }
 
 #[cfg(feature = "printing")]
@@ -140,6 +140,16 @@ impl Clone for BoundLifetimes {
         }
     }
 }
+#[cfg(feature = "full")]
+#[cfg_attr(doc_cfg, doc(cfg(feature = "clone-impls")))]
+impl Clone for CapturedParam {
+    fn clone(&self) -> Self {
+        match self {
+            CapturedParam::Lifetime(v0) => CapturedParam::Lifetime(v0.clone()),
+            CapturedParam::Ident(v0) => CapturedParam::Ident(v0.clone()),
+        }
+    }
+}
 #[cfg(any(feature = "derive", feature = "full"))]
 #[cfg_attr(doc_cfg, doc(cfg(feature = "clone-impls")))]
 impl Clone for ConstParam {
@@ -252,6 +262,8 @@ impl Clone for Expr {
             Expr::Field(v0) => Expr::Field(v0.clone()),
             #[cfg(feature = "full")]
             Expr::ForLoop(v0) => Expr::ForLoop(v0.clone()),
+            #[cfg(feature = "full")]
+            Expr::Gen(v0) => Expr::Gen(v0.clone()),
             Expr::Group(v0) => Expr::Group(v0.clone()),
             #[cfg(feature = "full")]
             Expr::If(v0) => Expr::If(v0.clone()),
@@ -473,6 +485,19 @@ impl Clone for ExprForLoop {
         }
     }
 }
+#[cfg(feature = "full")]
+#[cfg_attr(doc_cfg, doc(cfg(feature = "clone-impls")))]
+impl Clone for ExprGen {
+    fn clone(&self) -> Self {
+        ExprGen {
+            attrs: self.attrs.clone(),
+            async_token: self.async_token.clone(),
+            gen_token: self.gen_token.clone(),
+            capture: self.capture.clone(),
+            block: self.block.clone(),
+        }
+    }
+}
 #[cfg(any(feature = "derive", feature = "full"))]
 #[cfg_attr(doc_cfg, doc(cfg(feature = "clone-impls")))]
 impl Clone for ExprGroup {
@@ -875,6 +900,7 @@ impl Clone for ForeignItemFn {
         ForeignItemFn {
             attrs: self.attrs.clone(),
             vis: self.vis.clone(),
+            safety: self.safety.clone(),
             sig: self.sig.clone(),
             semi_token: self.semi_token.clone(),
         }
@@ -898,6 +924,7 @@ impl Clone for ForeignItemStatic {
         ForeignItemStatic {
             attrs: self.attrs.clone(),
             vis: self.vis.clone(),
+            safety: self.safety.clone(),
             static_token: self.static_token.clone(),
             mutability: self.mutability.clone(),
             ident: self.ident.clone(),
@@ -1330,6 +1357,7 @@ impl Clone for Lit {
         match self {
             Lit::Str(v0) => Lit::Str(v0.clone()),
             Lit::ByteStr(v0) => Lit::ByteStr(v0.clone()),
+            Lit::CStr(v0) => Lit::CStr(v0.clone()),
             Lit::Byte(v0) => Lit::Byte(v0.clone()),
             Lit::Char(v0) => Lit::Char(v0.clone()),
             Lit::Int(v0) => Lit::Int(v0.clone()),
@@ -1635,6 +1663,18 @@ impl Clone for PathSegment {
         }
     }
 }
+#[cfg(feature = "full")]
+#[cfg_attr(doc_cfg, doc(cfg(feature = "clone-impls")))]
+impl Clone for PreciseCapture {
+    fn clone(&self) -> Self {
+        PreciseCapture {
+            use_token: self.use_token.clone(),
+            lt_token: self.lt_token.clone(),
+            params: self.params.clone(),
+            gt_token: self.gt_token.clone(),
+        }
+    }
+}
 #[cfg(any(feature = "derive", feature = "full"))]
 #[cfg_attr(doc_cfg, doc(cfg(feature = "clone-impls")))]
 impl Clone for PredicateLifetime {
@@ -1763,6 +1803,7 @@ impl Clone for TraitBound {
     fn clone(&self) -> Self {
         TraitBound {
             paren_token: self.paren_token.clone(),
+            asyncness: self.asyncness.clone(),
             modifier: self.modifier.clone(),
             lifetimes: self.lifetimes.clone(),
             path: self.path.clone(),
@@ -1964,7 +2005,13 @@ impl Clone for TypeParamBound {
         match self {
             TypeParamBound::Trait(v0) => TypeParamBound::Trait(v0.clone()),
             TypeParamBound::Lifetime(v0) => TypeParamBound::Lifetime(v0.clone()),
+            #[cfg(feature = "full")]
+            TypeParamBound::PreciseCapture(v0) => {
+                TypeParamBound::PreciseCapture(v0.clone())
+            }
             TypeParamBound::Verbatim(v0) => TypeParamBound::Verbatim(v0.clone()),
+            #[cfg(not(feature = "full"))]
+            _ => unreachable!(),
         }
     }
 }
@@ -289,6 +289,25 @@ impl Debug for BoundLifetimes {
         formatter.finish()
     }
 }
+#[cfg(feature = "full")]
+#[cfg_attr(doc_cfg, doc(cfg(feature = "extra-traits")))]
+impl Debug for CapturedParam {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("CapturedParam::")?;
+        match self {
+            CapturedParam::Lifetime(v0) => {
+                let mut formatter = formatter.debug_tuple("Lifetime");
+                formatter.field(v0);
+                formatter.finish()
+            }
+            CapturedParam::Ident(v0) => {
+                let mut formatter = formatter.debug_tuple("Ident");
+                formatter.field(v0);
+                formatter.finish()
+            }
+        }
+    }
+}
 #[cfg(any(feature = "derive", feature = "full"))]
 #[cfg_attr(doc_cfg, doc(cfg(feature = "extra-traits")))]
 impl Debug for ConstParam {
@@ -418,6 +437,8 @@ impl Debug for Expr {
             Expr::Field(v0) => v0.debug(formatter, "Field"),
             #[cfg(feature = "full")]
             Expr::ForLoop(v0) => v0.debug(formatter, "ForLoop"),
+            #[cfg(feature = "full")]
+            Expr::Gen(v0) => v0.debug(formatter, "Gen"),
             Expr::Group(v0) => v0.debug(formatter, "Group"),
             #[cfg(feature = "full")]
             Expr::If(v0) => v0.debug(formatter, "If"),
@@ -713,6 +734,24 @@ impl Debug for ExprForLoop {
         self.debug(formatter, "ExprForLoop")
     }
 }
+#[cfg(feature = "full")]
+#[cfg_attr(doc_cfg, doc(cfg(feature = "extra-traits")))]
+impl Debug for ExprGen {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        impl ExprGen {
+            fn debug(&self, formatter: &mut fmt::Formatter, name: &str) -> fmt::Result {
+                let mut formatter = formatter.debug_struct(name);
+                formatter.field("attrs", &self.attrs);
+                formatter.field("async_token", &self.async_token);
+                formatter.field("gen_token", &self.gen_token);
+                formatter.field("capture", &self.capture);
+                formatter.field("block", &self.block);
+                formatter.finish()
+            }
+        }
+        self.debug(formatter, "ExprGen")
+    }
+}
 #[cfg(any(feature = "derive", feature = "full"))]
 #[cfg_attr(doc_cfg, doc(cfg(feature = "extra-traits")))]
 impl Debug for ExprGroup {
@@ -1263,6 +1302,7 @@ impl Debug for ForeignItemFn {
                 let mut formatter = formatter.debug_struct(name);
                 formatter.field("attrs", &self.attrs);
                 formatter.field("vis", &self.vis);
+                formatter.field("safety", &self.safety);
                 formatter.field("sig", &self.sig);
                 formatter.field("semi_token", &self.semi_token);
                 formatter.finish()
@@ -1296,6 +1336,7 @@ impl Debug for ForeignItemStatic {
                 let mut formatter = formatter.debug_struct(name);
                 formatter.field("attrs", &self.attrs);
                 formatter.field("vis", &self.vis);
+                formatter.field("safety", &self.safety);
                 formatter.field("static_token", &self.static_token);
                 formatter.field("mutability", &self.mutability);
                 formatter.field("ident", &self.ident);
@@ -1894,6 +1935,7 @@ impl Debug for Lit {
         match self {
             Lit::Str(v0) => v0.debug(formatter, "Str"),
             Lit::ByteStr(v0) => v0.debug(formatter, "ByteStr"),
+            Lit::CStr(v0) => v0.debug(formatter, "CStr"),
             Lit::Byte(v0) => v0.debug(formatter, "Byte"),
             Lit::Char(v0) => v0.debug(formatter, "Char"),
             Lit::Int(v0) => v0.debug(formatter, "Int"),
@@ -2301,6 +2343,18 @@ impl Debug for PathSegment {
         formatter.finish()
     }
 }
+#[cfg(feature = "full")]
+#[cfg_attr(doc_cfg, doc(cfg(feature = "extra-traits")))]
+impl Debug for PreciseCapture {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        let mut formatter = formatter.debug_struct("PreciseCapture");
+        formatter.field("use_token", &self.use_token);
+        formatter.field("lt_token", &self.lt_token);
+        formatter.field("params", &self.params);
+        formatter.field("gt_token", &self.gt_token);
+        formatter.finish()
+    }
+}
 #[cfg(any(feature = "derive", feature = "full"))]
 #[cfg_attr(doc_cfg, doc(cfg(feature = "extra-traits")))]
 impl Debug for PredicateLifetime {
@@ -2464,6 +2518,7 @@ impl Debug for TraitBound {
     fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
         let mut formatter = formatter.debug_struct("TraitBound");
         formatter.field("paren_token", &self.paren_token);
+        formatter.field("asyncness", &self.asyncness);
         formatter.field("modifier", &self.modifier);
         formatter.field("lifetimes", &self.lifetimes);
         formatter.field("path", &self.path);
@@ -2742,11 +2797,19 @@ impl Debug for TypeParamBound {
                 formatter.finish()
             }
             TypeParamBound::Lifetime(v0) => v0.debug(formatter, "Lifetime"),
+            #[cfg(feature = "full")]
+            TypeParamBound::PreciseCapture(v0) => {
+                let mut formatter = formatter.debug_tuple("PreciseCapture");
+                formatter.field(v0);
+                formatter.finish()
+            }
             TypeParamBound::Verbatim(v0) => {
                 let mut formatter = formatter.debug_tuple("Verbatim");
                 formatter.field(v0);
                 formatter.finish()
             }
+            #[cfg(not(feature = "full"))]
+            _ => unreachable!(),
         }
     }
 }
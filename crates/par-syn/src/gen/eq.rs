@@ -161,6 +161,24 @@ impl PartialEq for BoundLifetimes {
         self.lifetimes == other.lifetimes
     }
 }
+#[cfg(feature = "full")]
+#[cfg_attr(doc_cfg, doc(cfg(feature = "extra-traits")))]
+impl Eq for CapturedParam {}
+#[cfg(feature = "full")]
+#[cfg_attr(doc_cfg, doc(cfg(feature = "extra-traits")))]
+impl PartialEq for CapturedParam {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (CapturedParam::Lifetime(self0), CapturedParam::Lifetime(other0)) => {
+                self0 == other0
+            }
+            (CapturedParam::Ident(self0), CapturedParam::Ident(other0)) => {
+                self0 == other0
+            }
+            _ => false,
+        }
+    }
+}
 #[cfg(any(feature = "derive", feature = "full"))]
 #[cfg_attr(doc_cfg, doc(cfg(feature = "extra-traits")))]
 impl Eq for ConstParam {}
@@ -271,6 +289,8 @@ impl PartialEq for Expr {
             (Expr::Field(self0), Expr::Field(other0)) => self0 == other0,
             #[cfg(feature = "full")]
             (Expr::ForLoop(self0), Expr::ForLoop(other0)) => self0 == other0,
+            #[cfg(feature = "full")]
+            (Expr::Gen(self0), Expr::Gen(other0)) => self0 == other0,
             (Expr::Group(self0), Expr::Group(other0)) => self0 == other0,
             #[cfg(feature = "full")]
             (Expr::If(self0), Expr::If(other0)) => self0 == other0,
@@ -468,6 +488,17 @@ impl PartialEq for ExprForLoop {
             && self.expr == other.expr && self.body == other.body
     }
 }
+#[cfg(feature = "full")]
+#[cfg_attr(doc_cfg, doc(cfg(feature = "extra-traits")))]
+impl Eq for ExprGen {}
+#[cfg(feature = "full")]
+#[cfg_attr(doc_cfg, doc(cfg(feature = "extra-traits")))]
+impl PartialEq for ExprGen {
+    fn eq(&self, other: &Self) -> bool {
+        self.attrs == other.attrs && self.async_token == other.async_token
+            && self.capture == other.capture && self.block == other.block
+    }
+}
 #[cfg(any(feature = "derive", feature = "full"))]
 #[cfg_attr(doc_cfg, doc(cfg(feature = "extra-traits")))]
 impl Eq for ExprGroup {}
@@ -849,7 +880,8 @@ impl Eq for ForeignItemFn {}
 #[cfg_attr(doc_cfg, doc(cfg(feature = "extra-traits")))]
 impl PartialEq for ForeignItemFn {
     fn eq(&self, other: &Self) -> bool {
-        self.attrs == other.attrs && self.vis == other.vis && self.sig == other.sig
+        self.attrs == other.attrs && self.vis == other.vis && self.safety == other.safety
+            && self.sig == other.sig
     }
 }
 #[cfg(feature = "full")]
@@ -870,7 +902,7 @@ impl Eq for ForeignItemStatic {}
 #[cfg_attr(doc_cfg, doc(cfg(feature = "extra-traits")))]
 impl PartialEq for ForeignItemStatic {
     fn eq(&self, other: &Self) -> bool {
-        self.attrs == other.attrs && self.vis == other.vis
+        self.attrs == other.attrs && self.vis == other.vis && self.safety == other.safety
             && self.mutability == other.mutability && self.ident == other.ident
             && self.ty == other.ty
     }
@@ -1254,6 +1286,7 @@ impl PartialEq for Lit {
         match (self, other) {
             (Lit::Str(self0), Lit::Str(other0)) => self0 == other0,
             (Lit::ByteStr(self0), Lit::ByteStr(other0)) => self0 == other0,
+            (Lit::CStr(self0), Lit::CStr(other0)) => self0 == other0,
             (Lit::Byte(self0), Lit::Byte(other0)) => self0 == other0,
             (Lit::Char(self0), Lit::Char(other0)) => self0 == other0,
             (Lit::Int(self0), Lit::Int(other0)) => self0 == other0,
@@ -1279,6 +1312,8 @@ impl Eq for LitByte {}
 #[cfg_attr(doc_cfg, doc(cfg(feature = "extra-traits")))]
 impl Eq for LitByteStr {}
 #[cfg_attr(doc_cfg, doc(cfg(feature = "extra-traits")))]
+impl Eq for LitCStr {}
+#[cfg_attr(doc_cfg, doc(cfg(feature = "extra-traits")))]
 impl Eq for LitChar {}
 #[cfg_attr(doc_cfg, doc(cfg(feature = "extra-traits")))]
 impl Eq for LitFloat {}
@@ -1566,6 +1601,16 @@ impl PartialEq for PathSegment {
         self.ident == other.ident && self.arguments == other.arguments
     }
 }
+#[cfg(feature = "full")]
+#[cfg_attr(doc_cfg, doc(cfg(feature = "extra-traits")))]
+impl Eq for PreciseCapture {}
+#[cfg(feature = "full")]
+#[cfg_attr(doc_cfg, doc(cfg(feature = "extra-traits")))]
+impl PartialEq for PreciseCapture {
+    fn eq(&self, other: &Self) -> bool {
+        self.params == other.params
+    }
+}
 #[cfg(any(feature = "derive", feature = "full"))]
 #[cfg_attr(doc_cfg, doc(cfg(feature = "extra-traits")))]
 impl Eq for PredicateLifetime {}
@@ -1702,8 +1747,9 @@ impl Eq for TraitBound {}
 #[cfg_attr(doc_cfg, doc(cfg(feature = "extra-traits")))]
 impl PartialEq for TraitBound {
     fn eq(&self, other: &Self) -> bool {
-        self.paren_token == other.paren_token && self.modifier == other.modifier
-            && self.lifetimes == other.lifetimes && self.path == other.path
+        self.paren_token == other.paren_token && self.asyncness == other.asyncness
+            && self.modifier == other.modifier && self.lifetimes == other.lifetimes
+            && self.path == other.path
     }
 }
 #[cfg(any(feature = "derive", feature = "full"))]
@@ -1912,6 +1958,11 @@ impl PartialEq for TypeParamBound {
             (TypeParamBound::Lifetime(self0), TypeParamBound::Lifetime(other0)) => {
                 self0 == other0
             }
+            #[cfg(feature = "full")]
+            (
+                TypeParamBound::PreciseCapture(self0),
+                TypeParamBound::PreciseCapture(other0),
+            ) => self0 == other0,
             (TypeParamBound::Verbatim(self0), TypeParamBound::Verbatim(other0)) => {
                 TokenStreamHelper(self0) == TokenStreamHelper(other0)
             }
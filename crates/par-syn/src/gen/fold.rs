@@ -80,6 +80,10 @@ pub trait Fold {
     fn fold_bound_lifetimes(&mut self, i: BoundLifetimes) -> BoundLifetimes {
         fold_bound_lifetimes(self, i)
     }
+    #[cfg(feature = "full")]
+    fn fold_captured_param(&mut self, i: CapturedParam) -> CapturedParam {
+        fold_captured_param(self, i)
+    }
     #[cfg(any(feature = "derive", feature = "full"))]
     fn fold_const_param(&mut self, i: ConstParam) -> ConstParam {
         fold_const_param(self, i)
@@ -168,6 +172,10 @@ pub trait Fold {
     fn fold_expr_for_loop(&mut self, i: ExprForLoop) -> ExprForLoop {
         fold_expr_for_loop(self, i)
     }
+    #[cfg(feature = "full")]
+    fn fold_expr_gen(&mut self, i: ExprGen) -> ExprGen {
+        fold_expr_gen(self, i)
+    }
     #[cfg(any(feature = "derive", feature = "full"))]
     fn fold_expr_group(&mut self, i: ExprGroup) -> ExprGroup {
         fold_expr_group(self, i)
@@ -450,6 +458,9 @@ pub trait Fold {
     fn fold_lit_byte_str(&mut self, i: LitByteStr) -> LitByteStr {
         fold_lit_byte_str(self, i)
     }
+    fn fold_lit_cstr(&mut self, i: LitCStr) -> LitCStr {
+        fold_lit_cstr(self, i)
+    }
     fn fold_lit_char(&mut self, i: LitChar) -> LitChar {
         fold_lit_char(self, i)
     }
@@ -561,6 +572,10 @@ pub trait Fold {
     fn fold_path_segment(&mut self, i: PathSegment) -> PathSegment {
         fold_path_segment(self, i)
     }
+    #[cfg(feature = "full")]
+    fn fold_precise_capture(&mut self, i: PreciseCapture) -> PreciseCapture {
+        fold_precise_capture(self, i)
+    }
     #[cfg(any(feature = "derive", feature = "full"))]
     fn fold_predicate_lifetime(&mut self, i: PredicateLifetime) -> PredicateLifetime {
         fold_predicate_lifetime(self, i)
@@ -922,6 +937,20 @@ where
         gt_token: node.gt_token,
     }
 }
+#[cfg(feature = "full")]
+pub fn fold_captured_param<F>(f: &mut F, node: CapturedParam) -> CapturedParam
+where
+    F: Fold + ?Sized,
+{
+    match node {
+        CapturedParam::Lifetime(_binding_0) => {
+            CapturedParam::Lifetime(f.fold_lifetime(_binding_0))
+        }
+        CapturedParam::Ident(_binding_0) => {
+            CapturedParam::Ident(f.fold_ident(_binding_0))
+        }
+    }
+}
 #[cfg(any(feature = "derive", feature = "full"))]
 pub fn fold_const_param<F>(f: &mut F, node: ConstParam) -> ConstParam
 where
@@ -1031,6 +1060,7 @@ where
         Expr::ForLoop(_binding_0) => {
             Expr::ForLoop(full!(f.fold_expr_for_loop(_binding_0)))
         }
+        Expr::Gen(_binding_0) => Expr::Gen(full!(f.fold_expr_gen(_binding_0))),
         Expr::Group(_binding_0) => Expr::Group(f.fold_expr_group(_binding_0)),
         Expr::If(_binding_0) => Expr::If(full!(f.fold_expr_if(_binding_0))),
         Expr::Index(_binding_0) => Expr::Index(f.fold_expr_index(_binding_0)),
@@ -1238,6 +1268,19 @@ where
         body: f.fold_block(node.body),
     }
 }
+#[cfg(feature = "full")]
+pub fn fold_expr_gen<F>(f: &mut F, node: ExprGen) -> ExprGen
+where
+    F: Fold + ?Sized,
+{
+    ExprGen {
+        attrs: FoldHelper::lift(node.attrs, |it| f.fold_attribute(it)),
+        async_token: node.async_token,
+        gen_token: node.gen_token,
+        capture: node.capture,
+        block: f.fold_block(node.block),
+    }
+}
 #[cfg(any(feature = "derive", feature = "full"))]
 pub fn fold_expr_group<F>(f: &mut F, node: ExprGroup) -> ExprGroup
 where
@@ -1651,6 +1694,7 @@ where
     ForeignItemFn {
         attrs: FoldHelper::lift(node.attrs, |it| f.fold_attribute(it)),
         vis: f.fold_visibility(node.vis),
+        safety: node.safety,
         sig: f.fold_signature(node.sig),
         semi_token: node.semi_token,
     }
@@ -1677,6 +1721,7 @@ where
     ForeignItemStatic {
         attrs: FoldHelper::lift(node.attrs, |it| f.fold_attribute(it)),
         vis: f.fold_visibility(node.vis),
+        safety: node.safety,
         static_token: node.static_token,
         mutability: f.fold_static_mutability(node.mutability),
         ident: f.fold_ident(node.ident),
@@ -2159,6 +2204,7 @@ where
     match node {
         Lit::Str(_binding_0) => Lit::Str(f.fold_lit_str(_binding_0)),
         Lit::ByteStr(_binding_0) => Lit::ByteStr(f.fold_lit_byte_str(_binding_0)),
+        Lit::CStr(_binding_0) => Lit::CStr(f.fold_lit_cstr(_binding_0)),
         Lit::Byte(_binding_0) => Lit::Byte(f.fold_lit_byte(_binding_0)),
         Lit::Char(_binding_0) => Lit::Char(f.fold_lit_char(_binding_0)),
         Lit::Int(_binding_0) => Lit::Int(f.fold_lit_int(_binding_0)),
@@ -2194,6 +2240,15 @@ where
     node.set_span(span);
     node
 }
+pub fn fold_lit_cstr<F>(f: &mut F, node: LitCStr) -> LitCStr
+where
+    F: Fold + ?Sized,
+{
+    let span = f.fold_span(node.span());
+    let mut node = node;
+    node.set_span(span);
+    node
+}
 pub fn fold_lit_char<F>(f: &mut F, node: LitChar) -> LitChar
 where
     F: Fold + ?Sized,
@@ -2530,6 +2585,18 @@ where
         arguments: f.fold_path_arguments(node.arguments),
     }
 }
+#[cfg(feature = "full")]
+pub fn fold_precise_capture<F>(f: &mut F, node: PreciseCapture) -> PreciseCapture
+where
+    F: Fold + ?Sized,
+{
+    PreciseCapture {
+        use_token: node.use_token,
+        lt_token: node.lt_token,
+        params: FoldHelper::lift(node.params, |it| f.fold_captured_param(it)),
+        gt_token: node.gt_token,
+    }
+}
 #[cfg(any(feature = "derive", feature = "full"))]
 pub fn fold_predicate_lifetime<F>(
     f: &mut F,
@@ -2673,6 +2740,7 @@ where
 {
     TraitBound {
         paren_token: node.paren_token,
+        asyncness: node.asyncness,
         modifier: f.fold_trait_bound_modifier(node.modifier),
         lifetimes: (node.lifetimes).map(|it| f.fold_bound_lifetimes(it)),
         path: f.fold_path(node.path),
@@ -2893,6 +2961,9 @@ where
         TypeParamBound::Lifetime(_binding_0) => {
             TypeParamBound::Lifetime(f.fold_lifetime(_binding_0))
         }
+        TypeParamBound::PreciseCapture(_binding_0) => {
+            TypeParamBound::PreciseCapture(full!(f.fold_precise_capture(_binding_0)))
+        }
         TypeParamBound::Verbatim(_binding_0) => TypeParamBound::Verbatim(_binding_0),
     }
 }
@@ -231,6 +231,25 @@ impl Hash for BoundLifetimes {
         self.lifetimes.hash(state);
     }
 }
+#[cfg(feature = "full")]
+#[cfg_attr(doc_cfg, doc(cfg(feature = "extra-traits")))]
+impl Hash for CapturedParam {
+    fn hash<H>(&self, state: &mut H)
+    where
+        H: Hasher,
+    {
+        match self {
+            CapturedParam::Lifetime(v0) => {
+                state.write_u8(0u8);
+                v0.hash(state);
+            }
+            CapturedParam::Ident(v0) => {
+                state.write_u8(1u8);
+                v0.hash(state);
+            }
+        }
+    }
+}
 #[cfg(any(feature = "derive", feature = "full"))]
 #[cfg_attr(doc_cfg, doc(cfg(feature = "extra-traits")))]
 impl Hash for ConstParam {
@@ -399,121 +418,126 @@ impl Hash for Expr {
                 state.write_u8(13u8);
                 v0.hash(state);
             }
-            Expr::Group(v0) => {
+            #[cfg(feature = "full")]
+            Expr::Gen(v0) => {
                 state.write_u8(14u8);
                 v0.hash(state);
             }
+            Expr::Group(v0) => {
+                state.write_u8(15u8);
+                v0.hash(state);
+            }
             #[cfg(feature = "full")]
             Expr::If(v0) => {
-                state.write_u8(15u8);
+                state.write_u8(16u8);
                 v0.hash(state);
             }
             Expr::Index(v0) => {
-                state.write_u8(16u8);
+                state.write_u8(17u8);
                 v0.hash(state);
             }
             #[cfg(feature = "full")]
             Expr::Infer(v0) => {
-                state.write_u8(17u8);
+                state.write_u8(18u8);
                 v0.hash(state);
             }
             #[cfg(feature = "full")]
             Expr::Let(v0) => {
-                state.write_u8(18u8);
+                state.write_u8(19u8);
                 v0.hash(state);
             }
             Expr::Lit(v0) => {
-                state.write_u8(19u8);
+                state.write_u8(20u8);
                 v0.hash(state);
             }
             #[cfg(feature = "full")]
             Expr::Loop(v0) => {
-                state.write_u8(20u8);
+                state.write_u8(21u8);
                 v0.hash(state);
             }
             Expr::Macro(v0) => {
-                state.write_u8(21u8);
+                state.write_u8(22u8);
                 v0.hash(state);
             }
             #[cfg(feature = "full")]
             Expr::Match(v0) => {
-                state.write_u8(22u8);
+                state.write_u8(23u8);
                 v0.hash(state);
             }
             #[cfg(feature = "full")]
             Expr::MethodCall(v0) => {
-                state.write_u8(23u8);
+                state.write_u8(24u8);
                 v0.hash(state);
             }
             Expr::Paren(v0) => {
-                state.write_u8(24u8);
+                state.write_u8(25u8);
                 v0.hash(state);
             }
             Expr::Path(v0) => {
-                state.write_u8(25u8);
+                state.write_u8(26u8);
                 v0.hash(state);
             }
             #[cfg(feature = "full")]
             Expr::Range(v0) => {
-                state.write_u8(26u8);
+                state.write_u8(27u8);
                 v0.hash(state);
             }
             #[cfg(feature = "full")]
             Expr::Reference(v0) => {
-                state.write_u8(27u8);
+                state.write_u8(28u8);
                 v0.hash(state);
             }
             #[cfg(feature = "full")]
             Expr::Repeat(v0) => {
-                state.write_u8(28u8);
+                state.write_u8(29u8);
                 v0.hash(state);
             }
             #[cfg(feature = "full")]
             Expr::Return(v0) => {
-                state.write_u8(29u8);
+                state.write_u8(30u8);
                 v0.hash(state);
             }
             #[cfg(feature = "full")]
             Expr::Struct(v0) => {
-                state.write_u8(30u8);
+                state.write_u8(31u8);
                 v0.hash(state);
             }
             #[cfg(feature = "full")]
             Expr::Try(v0) => {
-                state.write_u8(31u8);
+                state.write_u8(32u8);
                 v0.hash(state);
             }
             #[cfg(feature = "full")]
             Expr::TryBlock(v0) => {
-                state.write_u8(32u8);
+                state.write_u8(33u8);
                 v0.hash(state);
             }
             #[cfg(feature = "full")]
             Expr::Tuple(v0) => {
-                state.write_u8(33u8);
+                state.write_u8(34u8);
                 v0.hash(state);
             }
             Expr::Unary(v0) => {
-                state.write_u8(34u8);
+                state.write_u8(35u8);
                 v0.hash(state);
             }
             #[cfg(feature = "full")]
             Expr::Unsafe(v0) => {
-                state.write_u8(35u8);
+                state.write_u8(36u8);
                 v0.hash(state);
             }
             Expr::Verbatim(v0) => {
-                state.write_u8(36u8);
+                state.write_u8(37u8);
                 TokenStreamHelper(v0).hash(state);
             }
             #[cfg(feature = "full")]
             Expr::While(v0) => {
-                state.write_u8(37u8);
+                state.write_u8(38u8);
                 v0.hash(state);
             }
             #[cfg(feature = "full")]
             Expr::Yield(v0) => {
-                state.write_u8(38u8);
+                state.write_u8(39u8);
                 v0.hash(state);
             }
             #[cfg(not(feature = "full"))]
@@ -694,6 +718,19 @@ impl Hash for ExprForLoop {
         self.body.hash(state);
     }
 }
+#[cfg(feature = "full")]
+#[cfg_attr(doc_cfg, doc(cfg(feature = "extra-traits")))]
+impl Hash for ExprGen {
+    fn hash<H>(&self, state: &mut H)
+    where
+        H: Hasher,
+    {
+        self.attrs.hash(state);
+        self.async_token.hash(state);
+        self.capture.hash(state);
+        self.block.hash(state);
+    }
+}
 #[cfg(any(feature = "derive", feature = "full"))]
 #[cfg_attr(doc_cfg, doc(cfg(feature = "extra-traits")))]
 impl Hash for ExprGroup {
@@ -1146,6 +1183,7 @@ impl Hash for ForeignItemFn {
     {
         self.attrs.hash(state);
         self.vis.hash(state);
+        self.safety.hash(state);
         self.sig.hash(state);
     }
 }
@@ -1170,6 +1208,7 @@ impl Hash for ForeignItemStatic {
     {
         self.attrs.hash(state);
         self.vis.hash(state);
+        self.safety.hash(state);
         self.mutability.hash(state);
         self.ident.hash(state);
         self.ty.hash(state);
@@ -1686,28 +1725,32 @@ impl Hash for Lit {
                 state.write_u8(1u8);
                 v0.hash(state);
             }
-            Lit::Byte(v0) => {
+            Lit::CStr(v0) => {
                 state.write_u8(2u8);
                 v0.hash(state);
             }
-            Lit::Char(v0) => {
+            Lit::Byte(v0) => {
                 state.write_u8(3u8);
                 v0.hash(state);
             }
-            Lit::Int(v0) => {
+            Lit::Char(v0) => {
                 state.write_u8(4u8);
                 v0.hash(state);
             }
-            Lit::Float(v0) => {
+            Lit::Int(v0) => {
                 state.write_u8(5u8);
                 v0.hash(state);
             }
-            Lit::Bool(v0) => {
+            Lit::Float(v0) => {
                 state.write_u8(6u8);
                 v0.hash(state);
             }
-            Lit::Verbatim(v0) => {
+            Lit::Bool(v0) => {
                 state.write_u8(7u8);
+                v0.hash(state);
+            }
+            Lit::Verbatim(v0) => {
+                state.write_u8(8u8);
                 v0.to_string().hash(state);
             }
         }
@@ -2087,6 +2130,16 @@ impl Hash for PathSegment {
         self.arguments.hash(state);
     }
 }
+#[cfg(feature = "full")]
+#[cfg_attr(doc_cfg, doc(cfg(feature = "extra-traits")))]
+impl Hash for PreciseCapture {
+    fn hash<H>(&self, state: &mut H)
+    where
+        H: Hasher,
+    {
+        self.params.hash(state);
+    }
+}
 #[cfg(any(feature = "derive", feature = "full"))]
 #[cfg_attr(doc_cfg, doc(cfg(feature = "extra-traits")))]
 impl Hash for PredicateLifetime {
@@ -2254,6 +2307,7 @@ impl Hash for TraitBound {
         H: Hasher,
     {
         self.paren_token.hash(state);
+        self.asyncness.hash(state);
         self.modifier.hash(state);
         self.lifetimes.hash(state);
         self.path.hash(state);
@@ -2535,10 +2589,17 @@ impl Hash for TypeParamBound {
                 state.write_u8(1u8);
                 v0.hash(state);
             }
-            TypeParamBound::Verbatim(v0) => {
+            #[cfg(feature = "full")]
+            TypeParamBound::PreciseCapture(v0) => {
                 state.write_u8(2u8);
+                v0.hash(state);
+            }
+            TypeParamBound::Verbatim(v0) => {
+                state.write_u8(3u8);
                 TokenStreamHelper(v0).hash(state);
             }
+            #[cfg(not(feature = "full"))]
+            _ => unreachable!(),
         }
     }
 }
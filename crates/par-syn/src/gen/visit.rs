@@ -77,6 +77,10 @@ pub trait Visit<'ast> {
     fn visit_bound_lifetimes(&mut self, i: &'ast BoundLifetimes) {
         visit_bound_lifetimes(self, i);
     }
+    #[cfg(feature = "full")]
+    fn visit_captured_param(&mut self, i: &'ast CapturedParam) {
+        visit_captured_param(self, i);
+    }
     #[cfg(any(feature = "derive", feature = "full"))]
     fn visit_const_param(&mut self, i: &'ast ConstParam) {
         visit_const_param(self, i);
@@ -165,6 +169,10 @@ pub trait Visit<'ast> {
     fn visit_expr_for_loop(&mut self, i: &'ast ExprForLoop) {
         visit_expr_for_loop(self, i);
     }
+    #[cfg(feature = "full")]
+    fn visit_expr_gen(&mut self, i: &'ast ExprGen) {
+        visit_expr_gen(self, i);
+    }
     #[cfg(any(feature = "derive", feature = "full"))]
     fn visit_expr_group(&mut self, i: &'ast ExprGroup) {
         visit_expr_group(self, i);
@@ -447,6 +455,9 @@ pub trait Visit<'ast> {
     fn visit_lit_byte_str(&mut self, i: &'ast LitByteStr) {
         visit_lit_byte_str(self, i);
     }
+    fn visit_lit_cstr(&mut self, i: &'ast LitCStr) {
+        visit_lit_cstr(self, i);
+    }
     fn visit_lit_char(&mut self, i: &'ast LitChar) {
         visit_lit_char(self, i);
     }
@@ -555,6 +566,10 @@ pub trait Visit<'ast> {
     fn visit_path_segment(&mut self, i: &'ast PathSegment) {
         visit_path_segment(self, i);
     }
+    #[cfg(feature = "full")]
+    fn visit_precise_capture(&mut self, i: &'ast PreciseCapture) {
+        visit_precise_capture(self, i);
+    }
     #[cfg(any(feature = "derive", feature = "full"))]
     fn visit_predicate_lifetime(&mut self, i: &'ast PredicateLifetime) {
         visit_predicate_lifetime(self, i);
@@ -979,6 +994,20 @@ where
     }
     skip!(node.gt_token);
 }
+#[cfg(feature = "full")]
+pub fn visit_captured_param<'ast, V>(v: &mut V, node: &'ast CapturedParam)
+where
+    V: Visit<'ast> + ?Sized,
+{
+    match node {
+        CapturedParam::Lifetime(_binding_0) => {
+            v.visit_lifetime(_binding_0);
+        }
+        CapturedParam::Ident(_binding_0) => {
+            v.visit_ident(_binding_0);
+        }
+    }
+}
 #[cfg(any(feature = "derive", feature = "full"))]
 pub fn visit_const_param<'ast, V>(v: &mut V, node: &'ast ConstParam)
 where
@@ -1118,6 +1147,9 @@ where
         Expr::ForLoop(_binding_0) => {
             full!(v.visit_expr_for_loop(_binding_0));
         }
+        Expr::Gen(_binding_0) => {
+            full!(v.visit_expr_gen(_binding_0));
+        }
         Expr::Group(_binding_0) => {
             v.visit_expr_group(_binding_0);
         }
@@ -1390,6 +1422,19 @@ where
     v.visit_expr(&*node.expr);
     v.visit_block(&node.body);
 }
+#[cfg(feature = "full")]
+pub fn visit_expr_gen<'ast, V>(v: &mut V, node: &'ast ExprGen)
+where
+    V: Visit<'ast> + ?Sized,
+{
+    for it in &node.attrs {
+        v.visit_attribute(it);
+    }
+    skip!(node.async_token);
+    skip!(node.gen_token);
+    skip!(node.capture);
+    v.visit_block(&node.block);
+}
 #[cfg(any(feature = "derive", feature = "full"))]
 pub fn visit_expr_group<'ast, V>(v: &mut V, node: &'ast ExprGroup)
 where
@@ -1854,6 +1899,7 @@ where
         v.visit_attribute(it);
     }
     v.visit_visibility(&node.vis);
+    skip!(node.safety);
     v.visit_signature(&node.sig);
     skip!(node.semi_token);
 }
@@ -1877,6 +1923,7 @@ where
         v.visit_attribute(it);
     }
     v.visit_visibility(&node.vis);
+    skip!(node.safety);
     skip!(node.static_token);
     v.visit_static_mutability(&node.mutability);
     v.visit_ident(&node.ident);
@@ -2418,6 +2465,9 @@ where
         Lit::ByteStr(_binding_0) => {
             v.visit_lit_byte_str(_binding_0);
         }
+        Lit::CStr(_binding_0) => {
+            v.visit_lit_cstr(_binding_0);
+        }
         Lit::Byte(_binding_0) => {
             v.visit_lit_byte(_binding_0);
         }
@@ -2455,6 +2505,11 @@ where
     V: Visit<'ast> + ?Sized,
 {
 }
+pub fn visit_lit_cstr<'ast, V>(v: &mut V, node: &'ast LitCStr)
+where
+    V: Visit<'ast> + ?Sized,
+{
+}
 pub fn visit_lit_char<'ast, V>(v: &mut V, node: &'ast LitChar)
 where
     V: Visit<'ast> + ?Sized,
@@ -2837,6 +2892,19 @@ where
     v.visit_ident(&node.ident);
     v.visit_path_arguments(&node.arguments);
 }
+#[cfg(feature = "full")]
+pub fn visit_precise_capture<'ast, V>(v: &mut V, node: &'ast PreciseCapture)
+where
+    V: Visit<'ast> + ?Sized,
+{
+    skip!(node.use_token);
+    skip!(node.lt_token);
+    for el in Punctuated::pairs(&node.params) {
+        let it = el.value();
+        v.visit_captured_param(it);
+    }
+    skip!(node.gt_token);
+}
 #[cfg(any(feature = "derive", feature = "full"))]
 pub fn visit_predicate_lifetime<'ast, V>(v: &mut V, node: &'ast PredicateLifetime)
 where
@@ -3000,6 +3068,7 @@ where
     V: Visit<'ast> + ?Sized,
 {
     skip!(node.paren_token);
+    skip!(node.asyncness);
     v.visit_trait_bound_modifier(&node.modifier);
     if let Some(it) = &node.lifetimes {
         v.visit_bound_lifetimes(it);
@@ -3264,6 +3333,9 @@ where
         TypeParamBound::Lifetime(_binding_0) => {
             v.visit_lifetime(_binding_0);
         }
+        TypeParamBound::PreciseCapture(_binding_0) => {
+            full!(v.visit_precise_capture(_binding_0));
+        }
         TypeParamBound::Verbatim(_binding_0) => {
             skip!(_binding_0);
         }
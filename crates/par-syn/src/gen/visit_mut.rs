@@ -80,6 +80,10 @@ pub trait VisitMut {
     fn visit_bound_lifetimes_mut(&mut self, i: &mut BoundLifetimes) {
         visit_bound_lifetimes_mut(self, i);
     }
+    #[cfg(feature = "full")]
+    fn visit_captured_param_mut(&mut self, i: &mut CapturedParam) {
+        visit_captured_param_mut(self, i);
+    }
     #[cfg(any(feature = "derive", feature = "full"))]
     fn visit_const_param_mut(&mut self, i: &mut ConstParam) {
         visit_const_param_mut(self, i);
@@ -168,6 +172,10 @@ pub trait VisitMut {
     fn visit_expr_for_loop_mut(&mut self, i: &mut ExprForLoop) {
         visit_expr_for_loop_mut(self, i);
     }
+    #[cfg(feature = "full")]
+    fn visit_expr_gen_mut(&mut self, i: &mut ExprGen) {
+        visit_expr_gen_mut(self, i);
+    }
     #[cfg(any(feature = "derive", feature = "full"))]
     fn visit_expr_group_mut(&mut self, i: &mut ExprGroup) {
         visit_expr_group_mut(self, i);
@@ -450,6 +458,9 @@ pub trait VisitMut {
     fn visit_lit_byte_str_mut(&mut self, i: &mut LitByteStr) {
         visit_lit_byte_str_mut(self, i);
     }
+    fn visit_lit_cstr_mut(&mut self, i: &mut LitCStr) {
+        visit_lit_cstr_mut(self, i);
+    }
     fn visit_lit_char_mut(&mut self, i: &mut LitChar) {
         visit_lit_char_mut(self, i);
     }
@@ -561,6 +572,10 @@ pub trait VisitMut {
     fn visit_path_segment_mut(&mut self, i: &mut PathSegment) {
         visit_path_segment_mut(self, i);
     }
+    #[cfg(feature = "full")]
+    fn visit_precise_capture_mut(&mut self, i: &mut PreciseCapture) {
+        visit_precise_capture_mut(self, i);
+    }
     #[cfg(any(feature = "derive", feature = "full"))]
     fn visit_predicate_lifetime_mut(&mut self, i: &mut PredicateLifetime) {
         visit_predicate_lifetime_mut(self, i);
@@ -986,6 +1001,20 @@ where
     }
     skip!(node.gt_token);
 }
+#[cfg(feature = "full")]
+pub fn visit_captured_param_mut<V>(v: &mut V, node: &mut CapturedParam)
+where
+    V: VisitMut + ?Sized,
+{
+    match node {
+        CapturedParam::Lifetime(_binding_0) => {
+            v.visit_lifetime_mut(_binding_0);
+        }
+        CapturedParam::Ident(_binding_0) => {
+            v.visit_ident_mut(_binding_0);
+        }
+    }
+}
 #[cfg(any(feature = "derive", feature = "full"))]
 pub fn visit_const_param_mut<V>(v: &mut V, node: &mut ConstParam)
 where
@@ -1125,6 +1154,9 @@ where
         Expr::ForLoop(_binding_0) => {
             full!(v.visit_expr_for_loop_mut(_binding_0));
         }
+        Expr::Gen(_binding_0) => {
+            full!(v.visit_expr_gen_mut(_binding_0));
+        }
         Expr::Group(_binding_0) => {
             v.visit_expr_group_mut(_binding_0);
         }
@@ -1397,6 +1429,19 @@ where
     v.visit_expr_mut(&mut *node.expr);
     v.visit_block_mut(&mut node.body);
 }
+#[cfg(feature = "full")]
+pub fn visit_expr_gen_mut<V>(v: &mut V, node: &mut ExprGen)
+where
+    V: VisitMut + ?Sized,
+{
+    for it in &mut node.attrs {
+        v.visit_attribute_mut(it);
+    }
+    skip!(node.async_token);
+    skip!(node.gen_token);
+    skip!(node.capture);
+    v.visit_block_mut(&mut node.block);
+}
 #[cfg(any(feature = "derive", feature = "full"))]
 pub fn visit_expr_group_mut<V>(v: &mut V, node: &mut ExprGroup)
 where
@@ -1859,6 +1904,7 @@ where
         v.visit_attribute_mut(it);
     }
     v.visit_visibility_mut(&mut node.vis);
+    skip!(node.safety);
     v.visit_signature_mut(&mut node.sig);
     skip!(node.semi_token);
 }
@@ -1882,6 +1928,7 @@ where
         v.visit_attribute_mut(it);
     }
     v.visit_visibility_mut(&mut node.vis);
+    skip!(node.safety);
     skip!(node.static_token);
     v.visit_static_mutability_mut(&mut node.mutability);
     v.visit_ident_mut(&mut node.ident);
@@ -2425,6 +2472,9 @@ where
         Lit::ByteStr(_binding_0) => {
             v.visit_lit_byte_str_mut(_binding_0);
         }
+        Lit::CStr(_binding_0) => {
+            v.visit_lit_cstr_mut(_binding_0);
+        }
         Lit::Byte(_binding_0) => {
             v.visit_lit_byte_mut(_binding_0);
         }
@@ -2460,6 +2510,10 @@ pub fn visit_lit_byte_str_mut<V>(v: &mut V, node: &mut LitByteStr)
 where
     V: VisitMut + ?Sized,
 {}
+pub fn visit_lit_cstr_mut<V>(v: &mut V, node: &mut LitCStr)
+where
+    V: VisitMut + ?Sized,
+{}
 pub fn visit_lit_char_mut<V>(v: &mut V, node: &mut LitChar)
 where
     V: VisitMut + ?Sized,
@@ -2839,6 +2893,19 @@ where
     v.visit_ident_mut(&mut node.ident);
     v.visit_path_arguments_mut(&mut node.arguments);
 }
+#[cfg(feature = "full")]
+pub fn visit_precise_capture_mut<V>(v: &mut V, node: &mut PreciseCapture)
+where
+    V: VisitMut + ?Sized,
+{
+    skip!(node.use_token);
+    skip!(node.lt_token);
+    for mut el in Punctuated::pairs_mut(&mut node.params) {
+        let it = el.value_mut();
+        v.visit_captured_param_mut(it);
+    }
+    skip!(node.gt_token);
+}
 #[cfg(any(feature = "derive", feature = "full"))]
 pub fn visit_predicate_lifetime_mut<V>(v: &mut V, node: &mut PredicateLifetime)
 where
@@ -3001,6 +3068,7 @@ where
     V: VisitMut + ?Sized,
 {
     skip!(node.paren_token);
+    skip!(node.asyncness);
     v.visit_trait_bound_modifier_mut(&mut node.modifier);
     if let Some(it) = &mut node.lifetimes {
         v.visit_bound_lifetimes_mut(it);
@@ -3265,6 +3333,9 @@ where
         TypeParamBound::Lifetime(_binding_0) => {
             v.visit_lifetime_mut(_binding_0);
         }
+        TypeParamBound::PreciseCapture(_binding_0) => {
+            full!(v.visit_precise_capture_mut(_binding_0));
+        }
         TypeParamBound::Verbatim(_binding_0) => {
             skip!(_binding_0);
         }
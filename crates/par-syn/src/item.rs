@@ -532,6 +532,8 @@ ast_struct! {
     pub struct ForeignItemFn {
         pub attrs: Vec<Attribute>,
         pub vis: Visibility,
+        /// The `safe` in `safe fn`, in an `unsafe extern` block
+        pub safety: Option<Token![safe]>,
         pub sig: Signature,
         pub semi_token: Token![;],
     }
@@ -543,6 +545,8 @@ ast_struct! {
     pub struct ForeignItemStatic {
         pub attrs: Vec<Attribute>,
         pub vis: Visibility,
+        /// The `safe` in `safe static`, in an `unsafe extern` block
+        pub safety: Option<Token![safe]>,
         pub static_token: Token![static],
         pub mutability: StaticMutability,
         pub ident: Ident,
@@ -1777,10 +1781,17 @@ pub(crate) mod parsing {
             let mut attrs = input.call(Attribute::parse_outer)?;
             let ahead = input.fork();
             let vis: Visibility = ahead.parse()?;
+            // `safe` is not a keyword, so `safe!()` is still a macro
+            let safe = ahead.peek(Token![safe])
+                && (ahead.peek2(Token![fn]) || ahead.peek2(Token![static]));
+            if safe {
+                ahead.parse::<Token![safe]>()?;
+            }
 
             let lookahead = ahead.lookahead1();
             let mut item = if lookahead.peek(Token![fn]) || peek_signature(&ahead) {
                 let vis: Visibility = input.parse()?;
+                let safety: Option<Token![safe]> = if safe { input.parse()? } else { None };
                 let sig: Signature = input.parse()?;
                 if input.peek(token::Brace) {
                     let content;
@@ -1793,12 +1804,14 @@ pub(crate) mod parsing {
                     Ok(ForeignItem::Fn(ForeignItemFn {
                         attrs: Vec::new(),
                         vis,
+                        safety,
                         sig,
                         semi_token: input.parse()?,
                     }))
                 }
             } else if lookahead.peek(Token![static]) {
                 let vis = input.parse()?;
+                let safety: Option<Token![safe]> = if safe { input.parse()? } else { None };
                 let static_token = input.parse()?;
                 let mutability = input.parse()?;
                 let ident = input.parse()?;
@@ -1813,6 +1826,7 @@ pub(crate) mod parsing {
                     Ok(ForeignItem::Static(ForeignItemStatic {
                         attrs: Vec::new(),
                         vis,
+                        safety,
                         static_token,
                         mutability,
                         ident,
@@ -1854,11 +1868,13 @@ pub(crate) mod parsing {
         fn parse(input: ParseStream) -> Result<Self> {
             let attrs = input.call(Attribute::parse_outer)?;
             let vis: Visibility = input.parse()?;
+            let safety: Option<Token![safe]> = input.parse()?;
             let sig: Signature = input.parse()?;
             let semi_token: Token![;] = input.parse()?;
             Ok(ForeignItemFn {
                 attrs,
                 vis,
+                safety,
                 sig,
                 semi_token,
             })
@@ -1871,6 +1887,7 @@ pub(crate) mod parsing {
             Ok(ForeignItemStatic {
                 attrs: input.call(Attribute::parse_outer)?,
                 vis: input.parse()?,
+                safety: input.parse()?,
                 static_token: input.parse()?,
                 mutability: input.parse()?,
                 ident: input.parse()?,
@@ -3277,6 +3294,7 @@ mod printing {
         fn to_tokens(&self, tokens: &mut TokenStream) {
             tokens.append_all(self.attrs.outer());
             self.vis.to_tokens(tokens);
+            self.safety.to_tokens(tokens);
             self.sig.to_tokens(tokens);
             self.semi_token.to_tokens(tokens);
         }
@@ -3287,6 +3305,7 @@ mod printing {
         fn to_tokens(&self, tokens: &mut TokenStream) {
             tokens.append_all(self.attrs.outer());
             self.vis.to_tokens(tokens);
+            self.safety.to_tokens(tokens);
             self.static_token.to_tokens(tokens);
             self.mutability.to_tokens(tokens);
             self.ident.to_tokens(tokens);
@@ -346,8 +346,8 @@ pub use crate::expr::{Arm, FieldValue, Label, RangeLimits};
 #[cfg(any(feature = "full", feature = "derive"))]
 pub use crate::expr::{
     Expr, ExprArray, ExprAssign, ExprAsync, ExprAwait, ExprBinary, ExprBlock, ExprBreak, ExprCall,
-    ExprCast, ExprClosure, ExprConst, ExprContinue, ExprField, ExprForLoop, ExprGroup, ExprIf,
-    ExprIndex, ExprInfer, ExprLet, ExprLit, ExprLoop, ExprMacro, ExprMatch, ExprMethodCall,
+    ExprCast, ExprClosure, ExprConst, ExprContinue, ExprField, ExprForLoop, ExprGen, ExprGroup,
+    ExprIf, ExprIndex, ExprInfer, ExprLet, ExprLit, ExprLoop, ExprMacro, ExprMatch, ExprMethodCall,
     ExprParen, ExprPath, ExprRange, ExprReference, ExprRepeat, ExprReturn, ExprStruct, ExprTry,
     ExprTryBlock, ExprTuple, ExprUnary, ExprUnsafe, ExprWhile, ExprYield, Index, Member,
 };
@@ -363,11 +363,13 @@ pub use crate::file::File;
 
 #[cfg(any(feature = "full", feature = "derive"))]
 mod generics;
+#[cfg(feature = "full")]
+pub use crate::generics::CapturedParam;
 #[cfg(any(feature = "full", feature = "derive"))]
 pub use crate::generics::{
-    BoundLifetimes, ConstParam, GenericParam, Generics, LifetimeParam, PredicateLifetime,
-    PredicateType, TraitBound, TraitBoundModifier, TypeParam, TypeParamBound, WhereClause,
-    WherePredicate,
+    BoundLifetimes, ConstParam, GenericParam, Generics, LifetimeParam, PreciseCapture,
+    PredicateLifetime, PredicateType, TraitBound, TraitBoundModifier, TypeParam, TypeParamBound,
+    WhereClause, WherePredicate,
 };
 #[cfg(all(any(feature = "full", feature = "derive"), feature = "printing"))]
 pub use crate::generics::{ImplGenerics, Turbofish, TypeGenerics};
@@ -395,7 +397,7 @@ pub use crate::lifetime::Lifetime;
 mod lit;
 #[doc(inline)]
 pub use crate::lit::{
-    Lit, LitBool, LitByte, LitByteStr, LitChar, LitFloat, LitInt, LitStr, StrStyle,
+    Lit, LitBool, LitByte, LitByteStr, LitCStr, LitChar, LitFloat, LitInt, LitStr, StrStyle,
 };
 
 #[cfg(feature = "parsing")]
@@ -944,7 +946,70 @@ pub fn parse_str<T: parse::Parse>(s: &str) -> Result<T> {
 /// ```
 #[cfg(all(feature = "parsing", feature = "full"))]
 #[cfg_attr(doc_cfg, doc(cfg(all(feature = "parsing", feature = "full"))))]
-pub fn parse_file(mut content: &str) -> Result<File> {
+pub fn parse_file(content: &str) -> Result<File> {
+    let (content, shebang) = split_file_prelude(content);
+
+    let mut file: File = parse_str(content)?;
+    file.shebang = shebang;
+    Ok(file)
+}
+
+/// Parse the content of a file of Rust code, attributing its spans to `path`.
+///
+/// Works exactly like [`parse_file`], but
+/// [`Span::source_file`](proc_macro2::Span::source_file) of the parsed tokens
+/// will return `path`, which allows mapping a syntax node back to the file it
+/// came from.
+#[cfg(all(feature = "parsing", feature = "full", feature = "span-locations"))]
+#[cfg_attr(
+    doc_cfg,
+    doc(cfg(all(feature = "parsing", feature = "full", feature = "span-locations")))
+)]
+pub fn parse_file_with_path(content: &str, path: impl Into<std::path::PathBuf>) -> Result<File> {
+    let (content, shebang) = split_file_prelude(content);
+
+    let tokens = proc_macro2::TokenStream::from_str_with_path(content, path.into())?;
+    let mut file: File = parse2(tokens)?;
+    file.shebang = shebang;
+    Ok(file)
+}
+
+/// Parse the content of a file of Rust code, keeping the items that fail to
+/// parse as [`Item::Verbatim`].
+///
+/// Works like [`parse_file_with_path`], but a single item with a syntax error
+/// (or a syntax this version of syn doesn't know yet) does not make the whole
+/// file fail. The errors of such items are returned along with the file, in
+/// the order of the items. The file can still fail to parse as a whole, e.g.
+/// if its delimiters are unbalanced.
+///
+/// The source of the file is kept for the locations of its spans until the
+/// returned guard is dropped, so the guard should live as long as the file
+/// and the errors.
+#[cfg(all(feature = "parsing", feature = "full", feature = "span-locations"))]
+#[cfg_attr(
+    doc_cfg,
+    doc(cfg(all(feature = "parsing", feature = "full", feature = "span-locations")))
+)]
+pub fn parse_file_recovering(
+    content: &str,
+    path: impl Into<std::path::PathBuf>,
+) -> (Result<(File, Vec<Error>)>, proc_macro2::SourceFileGuard) {
+    let (content, shebang) = split_file_prelude(content);
+
+    let (tokens, guard) =
+        proc_macro2::TokenStream::from_str_with_path_guarded(content, path.into());
+    let parsed = tokens.map_err(Error::from).and_then(|tokens| {
+        let (mut file, errors) = parse::Parser::parse2(file::parsing::parse_recovering, tokens)?;
+        file.shebang = shebang;
+        Ok((file, errors))
+    });
+    (parsed, guard)
+}
+
+/// Strips the BOM and the shebang line from the file content
+#[cfg(all(feature = "parsing", feature = "full"))]
+fn split_file_prelude(mut content: &str) -> (&str, Option<String>) {
     // Strip the BOM if it is present
     const BOM: &str = "\u{feff}";
     if content.starts_with(BOM) {
@@ -965,7 +1030,5 @@ pub fn parse_file(mut content: &str) -> Result<File> {
         }
     }
 
-    let mut file: File = parse_str(content)?;
-    file.shebang = shebang;
-    Ok(file)
+    (content, shebang)
 }
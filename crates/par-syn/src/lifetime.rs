@@ -48,13 +48,24 @@ impl Lifetime {
             panic!("lifetime name must not be empty");
         }
 
-        if !crate::ident::xid_ok(&symbol[1..]) {
+        // raw lifetimes, `'r#a`
+        let (name, raw) = match symbol[1..].strip_prefix("r#") {
+            Some(name) => (name, true),
+            None => (&symbol[1..], false),
+        };
+
+        if !crate::ident::xid_ok(name) {
             panic!("{:?} is not a valid lifetime name", symbol);
         }
 
+        let ident = if raw {
+            Ident::new_raw(name, span)
+        } else {
+            Ident::new(name, span)
+        };
         Lifetime {
             apostrophe: span,
-            ident: Ident::new(&symbol[1..], span),
+            ident,
         }
     }
 
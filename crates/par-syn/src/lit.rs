@@ -6,6 +6,7 @@ use crate::{Error, Result};
 use proc_macro2::{Ident, Literal, Span};
 #[cfg(feature = "parsing")]
 use proc_macro2::{TokenStream, TokenTree};
+use std::ffi::{CStr, CString};
 use std::fmt::{self, Display};
 #[cfg(feature = "extra-traits")]
 use std::hash::{Hash, Hasher};
@@ -27,6 +28,9 @@ ast_enum_of_structs! {
         /// A byte string literal: `b"foo"`.
         ByteStr(LitByteStr),
 
+        /// A nul-terminated C-string literal: `c"foo"`.
+        CStr(LitCStr),
+
         /// A byte literal: `b'f'`.
         Byte(LitByte),
 
@@ -63,6 +67,13 @@ ast_struct! {
     }
 }
 
+ast_struct! {
+    /// A nul-terminated C-string literal: `c"foo"`.
+    pub struct LitCStr {
+        repr: Box<LitRepr>,
+    }
+}
+
 ast_struct! {
     /// A byte literal: `b'f'`.
     pub struct LitByte {
@@ -293,6 +304,51 @@ impl LitByteStr {
     }
 }
 
+impl LitCStr {
+    pub fn new(value: &CStr, span: Span) -> Self {
+        let mut repr = String::from("c\"");
+        for &b in value.to_bytes() {
+            match b {
+                b'"' => repr.push_str("\\\""),
+                b'\\' => repr.push_str("\\\\"),
+                b' '..=b'~' => repr.push(char::from(b)),
+                _ => repr.push_str(&format!("\\x{:02x}", b)),
+            }
+        }
+        repr.push('"');
+        let mut token = Literal::from_str(&repr).unwrap();
+        token.set_span(span);
+        LitCStr {
+            repr: Box::new(LitRepr {
+                token,
+                suffix: Box::<str>::default(),
+            }),
+        }
+    }
+
+    pub fn value(&self) -> CString {
+        let repr = self.repr.token.to_string();
+        let (value, _suffix) = value::parse_lit_c_str(&repr);
+        value
+    }
+
+    pub fn span(&self) -> Span {
+        self.repr.token.span()
+    }
+
+    pub fn set_span(&mut self, span: Span) {
+        self.repr.token.set_span(span);
+    }
+
+    pub fn suffix(&self) -> &str {
+        &self.repr.suffix
+    }
+
+    pub fn token(&self) -> Literal {
+        self.repr.token.clone()
+    }
+}
+
 impl LitByte {
     pub fn new(value: u8, span: Span) -> Self {
         let mut token = Literal::u8_suffixed(value);
@@ -595,6 +651,25 @@ mod debug_impls {
         }
     }
 
+    #[cfg_attr(doc_cfg, doc(cfg(feature = "extra-traits")))]
+    impl Debug for LitCStr {
+        fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            impl LitCStr {
+                pub(crate) fn debug(
+                    &self,
+                    formatter: &mut fmt::Formatter,
+                    name: &str,
+                ) -> fmt::Result {
+                    formatter
+                        .debug_struct(name)
+                        .field("token", &format_args!("{}", self.repr.token))
+                        .finish()
+                }
+            }
+            self.debug(formatter, "LitCStr")
+        }
+    }
+
     #[cfg_attr(doc_cfg, doc(cfg(feature = "extra-traits")))]
     impl Debug for LitByte {
         fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
@@ -770,6 +845,7 @@ macro_rules! lit_extra_traits {
 
 lit_extra_traits!(LitStr);
 lit_extra_traits!(LitByteStr);
+lit_extra_traits!(LitCStr);
 lit_extra_traits!(LitByte);
 lit_extra_traits!(LitChar);
 lit_extra_traits!(LitInt);
@@ -906,6 +982,17 @@ pub(crate) mod parsing {
         }
     }
 
+    #[cfg_attr(doc_cfg, doc(cfg(feature = "parsing")))]
+    impl Parse for LitCStr {
+        fn parse(input: ParseStream) -> Result<Self> {
+            let head = input.fork();
+            match input.parse() {
+                Ok(Lit::CStr(lit)) => Ok(lit),
+                _ => Err(head.error("expected C string literal")),
+            }
+        }
+    }
+
     #[cfg_attr(doc_cfg, doc(cfg(feature = "parsing")))]
     impl Parse for LitByte {
         fn parse(input: ParseStream) -> Result<Self> {
@@ -982,6 +1069,13 @@ mod printing {
         }
     }
 
+    #[cfg_attr(doc_cfg, doc(cfg(feature = "printing")))]
+    impl ToTokens for LitCStr {
+        fn to_tokens(&self, tokens: &mut TokenStream) {
+            self.repr.token.to_tokens(tokens);
+        }
+    }
+
     #[cfg_attr(doc_cfg, doc(cfg(feature = "printing")))]
     impl ToTokens for LitByte {
         fn to_tokens(&self, tokens: &mut TokenStream) {
@@ -1093,8 +1187,12 @@ mod value {
                     }
                 }
                 // c"...", cr"...", cr#"..."#
-                // TODO: add a Lit::CStr variant?
-                b'c' => return Lit::Verbatim(token),
+                b'c' => {
+                    let (_, suffix) = parse_lit_c_str(&repr);
+                    return Lit::CStr(LitCStr {
+                        repr: Box::new(LitRepr { token, suffix }),
+                    });
+                }
                 b'(' if repr == "(/*ERROR*/)" => return Lit::Verbatim(token),
                 _ => {}
             }
@@ -1106,6 +1204,7 @@ mod value {
             match self {
                 Lit::Str(lit) => lit.suffix(),
                 Lit::ByteStr(lit) => lit.suffix(),
+                Lit::CStr(lit) => lit.suffix(),
                 Lit::Byte(lit) => lit.suffix(),
                 Lit::Char(lit) => lit.suffix(),
                 Lit::Int(lit) => lit.suffix(),
@@ -1118,6 +1217,7 @@ mod value {
             match self {
                 Lit::Str(lit) => lit.span(),
                 Lit::ByteStr(lit) => lit.span(),
+                Lit::CStr(lit) => lit.span(),
                 Lit::Byte(lit) => lit.span(),
                 Lit::Char(lit) => lit.span(),
                 Lit::Int(lit) => lit.span(),
@@ -1131,6 +1231,7 @@ mod value {
             match self {
                 Lit::Str(lit) => lit.set_span(span),
                 Lit::ByteStr(lit) => lit.set_span(span),
+                Lit::CStr(lit) => lit.set_span(span),
                 Lit::Byte(lit) => lit.set_span(span),
                 Lit::Char(lit) => lit.set_span(span),
                 Lit::Int(lit) => lit.set_span(span),
@@ -1323,6 +1424,94 @@ mod value {
         (String::from(value).into_bytes(), suffix)
     }
 
+    // Returns (content, suffix).
+    pub(crate) fn parse_lit_c_str(s: &str) -> (CString, Box<str>) {
+        assert_eq!(byte(s, 0), b'c');
+        match byte(s, 1) {
+            b'"' => parse_lit_c_str_cooked(s),
+            b'r' => parse_lit_c_str_raw(s),
+            _ => unreachable!(),
+        }
+    }
+
+    // Clippy false positive
+    // https://github.com/rust-lang-nursery/rust-clippy/issues/2329
+    #[allow(clippy::needless_continue)]
+    fn parse_lit_c_str_cooked(mut s: &str) -> (CString, Box<str>) {
+        assert_eq!(byte(s, 0), b'c');
+        assert_eq!(byte(s, 1), b'"');
+        s = &s[2..];
+
+        // We're going to want to have slices which don't respect codepoint boundaries.
+        let mut v = s.as_bytes();
+
+        let mut out = Vec::new();
+        'outer: loop {
+            let byte = match byte(v, 0) {
+                b'"' => break,
+                b'\\' => {
+                    let b = byte(v, 1);
+                    v = &v[2..];
+                    match b {
+                        b'x' => {
+                            let (b, rest) = backslash_x(v);
+                            assert!(b != 0, "\\x00 is not allowed in C string literal");
+                            v = rest;
+                            b
+                        }
+                        b'u' => {
+                            // the escapes start at a char boundary
+                            let (ch, rest) = backslash_u(str::from_utf8(v).unwrap());
+                            assert!(ch != '\0', "\\u{{0}} is not allowed in C string literal");
+                            let mut buf = [0; 4];
+                            out.extend_from_slice(ch.encode_utf8(&mut buf).as_bytes());
+                            v = rest.as_bytes();
+                            continue 'outer;
+                        }
+                        b'n' => b'\n',
+                        b'r' => b'\r',
+                        b't' => b'\t',
+                        b'\\' => b'\\',
+                        b'\'' => b'\'',
+                        b'"' => b'"',
+                        b'\r' | b'\n' => loop {
+                            let byte = byte(v, 0);
+                            if matches!(byte, b' ' | b'\t' | b'\n' | b'\r') {
+                                v = &v[1..];
+                            } else {
+                                continue 'outer;
+                            }
+                        },
+                        b => panic!(
+                            "unexpected byte {:?} after \\ character in C string literal",
+                            b,
+                        ),
+                    }
+                }
+                b'\r' => {
+                    assert_eq!(byte(v, 1), b'\n', "Bare CR not allowed in string");
+                    v = &v[2..];
+                    b'\n'
+                }
+                b => {
+                    v = &v[1..];
+                    b
+                }
+            };
+            out.push(byte);
+        }
+
+        assert_eq!(byte(v, 0), b'"');
+        let suffix = s[s.len() - v.len() + 1..].to_owned().into_boxed_str();
+        (CString::new(out).unwrap(), suffix)
+    }
+
+    fn parse_lit_c_str_raw(s: &str) -> (CString, Box<str>) {
+        assert_eq!(byte(s, 0), b'c');
+        let (value, suffix) = parse_lit_str_raw(&s[1..]);
+        (CString::new(String::from(value)).unwrap(), suffix)
+    }
+
     // Returns (value, suffix).
     pub(crate) fn parse_lit_byte(s: &str) -> (u8, Box<str>) {
         assert_eq!(byte(s, 0), b'b');
@@ -338,6 +338,7 @@ pub(crate) mod parsing {
                 | Expr::Continue(_)
                 | Expr::Field(_)
                 | Expr::ForLoop(_)
+                | Expr::Gen(_)
                 | Expr::Group(_)
                 | Expr::If(_)
                 | Expr::Index(_)
@@ -96,7 +96,7 @@ use crate::error::Result;
 #[cfg(feature = "parsing")]
 use crate::lifetime::Lifetime;
 #[cfg(feature = "parsing")]
-use crate::lit::{Lit, LitBool, LitByte, LitByteStr, LitChar, LitFloat, LitInt, LitStr};
+use crate::lit::{Lit, LitBool, LitByte, LitByteStr, LitCStr, LitChar, LitFloat, LitInt, LitStr};
 #[cfg(feature = "parsing")]
 use crate::lookahead;
 #[cfg(feature = "parsing")]
@@ -189,6 +189,7 @@ impl_token!("lifetime" Lifetime);
 impl_token!("literal" Lit);
 impl_token!("string literal" LitStr);
 impl_token!("byte string literal" LitByteStr);
+impl_token!("C string literal" LitCStr);
 impl_token!("byte literal" LitByte);
 impl_token!("character literal" LitChar);
 impl_token!("integer literal" LitInt);
@@ -750,6 +751,7 @@ define_keywords! {
     "final"       pub struct Final
     "fn"          pub struct Fn
     "for"         pub struct For
+    "gen"         pub struct Gen
     "if"          pub struct If
     "impl"        pub struct Impl
     "in"          pub struct In
@@ -765,6 +767,7 @@ define_keywords! {
     "pub"         pub struct Pub
     "ref"         pub struct Ref
     "return"      pub struct Return
+    "safe"        pub struct Safe
     "Self"        pub struct SelfType
     "self"        pub struct SelfValue
     "static"      pub struct Static
@@ -928,6 +931,7 @@ macro_rules! Token {
     [final]       => { $crate::token::Final };
     [fn]          => { $crate::token::Fn };
     [for]         => { $crate::token::For };
+    [gen]         => { $crate::token::Gen };
     [if]          => { $crate::token::If };
     [impl]        => { $crate::token::Impl };
     [in]          => { $crate::token::In };
@@ -943,6 +947,7 @@ macro_rules! Token {
     [pub]         => { $crate::token::Pub };
     [ref]         => { $crate::token::Ref };
     [return]      => { $crate::token::Return };
+    [safe]        => { $crate::token::Safe };
     [Self]        => { $crate::token::SelfType };
     [self]        => { $crate::token::SelfValue };
     [static]      => { $crate::token::Static };
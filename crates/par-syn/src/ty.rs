@@ -410,6 +410,7 @@ pub(crate) mod parsing {
                         Type::Path(TypePath { qself: None, path }) => {
                             TypeParamBound::Trait(TraitBound {
                                 paren_token: Some(paren_token),
+                                asyncness: None,
                                 modifier: TraitBoundModifier::None,
                                 lifetimes: None,
                                 path,
@@ -434,6 +435,7 @@ pub(crate) mod parsing {
                                     })
                                 }
                                 other @ (TypeParamBound::Lifetime(_)
+                                | TypeParamBound::PreciseCapture(_)
                                 | TypeParamBound::Verbatim(_)) => other,
                             }
                         }
@@ -494,6 +496,7 @@ pub(crate) mod parsing {
                 let mut bounds = Punctuated::new();
                 bounds.push_value(TypeParamBound::Trait(TraitBound {
                     paren_token: None,
+                    asyncness: None,
                     modifier: TraitBoundModifier::None,
                     lifetimes,
                     path: ty.path,
@@ -801,7 +804,7 @@ pub(crate) mod parsing {
             allow_plus: bool,
         ) -> Result<Punctuated<TypeParamBound, Token![+]>> {
             let bounds = TypeParamBound::parse_multiple(input, allow_plus)?;
-            let mut last_lifetime_span = None;
+            let mut last_nontrait_span = None;
             let mut at_least_one_trait = false;
             for bound in &bounds {
                 match bound {
@@ -810,14 +813,20 @@ pub(crate) mod parsing {
                         break;
                     }
                     TypeParamBound::Lifetime(lifetime) => {
-                        last_lifetime_span = Some(lifetime.ident.span());
+                        last_nontrait_span = Some(lifetime.ident.span());
                     }
+                    #[cfg(feature = "full")]
+                    TypeParamBound::PreciseCapture(precise_capture) => {
+                        last_nontrait_span = Some(precise_capture.gt_token.span);
+                    }
+                    #[cfg(not(feature = "full"))]
+                    TypeParamBound::PreciseCapture(_) => unreachable!(),
                 }
             }
             // Just lifetimes like `'a + 'b` is not a TraitObject.
             if !at_least_one_trait {
                 let msg = "at least one trait is required for an object type";
-                return Err(error::new2(dyn_span, last_lifetime_span.unwrap(), msg));
+                return Err(error::new2(dyn_span, last_nontrait_span.unwrap(), msg));
             }
             Ok(bounds)
         }
@@ -841,7 +850,7 @@ pub(crate) mod parsing {
         pub(crate) fn parse(input: ParseStream, allow_plus: bool) -> Result<Self> {
             let impl_token: Token![impl] = input.parse()?;
             let bounds = TypeParamBound::parse_multiple(input, allow_plus)?;
-            let mut last_lifetime_span = None;
+            let mut last_nontrait_span = None;
             let mut at_least_one_trait = false;
             for bound in &bounds {
                 match bound {
@@ -850,15 +859,21 @@ pub(crate) mod parsing {
                         break;
                     }
                     TypeParamBound::Lifetime(lifetime) => {
-                        last_lifetime_span = Some(lifetime.ident.span());
+                        last_nontrait_span = Some(lifetime.ident.span());
+                    }
+                    #[cfg(feature = "full")]
+                    TypeParamBound::PreciseCapture(precise_capture) => {
+                        last_nontrait_span = Some(precise_capture.gt_token.span);
                     }
+                    #[cfg(not(feature = "full"))]
+                    TypeParamBound::PreciseCapture(_) => unreachable!(),
                 }
             }
             if !at_least_one_trait {
                 let msg = "at least one trait must be specified";
                 return Err(error::new2(
                     impl_token.span,
-                    last_lifetime_span.unwrap(),
+                    last_nontrait_span.unwrap(),
                     msg,
                 ));
             }
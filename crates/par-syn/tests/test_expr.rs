@@ -310,3 +310,55 @@ fn test_ranges() {
     syn::parse_str::<Expr>("lo...").unwrap_err();
     syn::parse_str::<Expr>("lo...hi").unwrap_err();
 }
+
+#[test]
+fn test_gen_block_vs_struct() {
+    let tokens = quote!(gen move { yield 1; });
+    snapshot!(tokens as Expr, @r###"
+    Expr::Gen {
+        capture: Some,
+        block: Block {
+            stmts: [
+                Stmt::Expr(
+                    Expr::Yield {
+                        expr: Some(Expr::Lit {
+                            lit: 1,
+                        }),
+                    },
+                    Some,
+                ),
+            ],
+        },
+    }
+    "###);
+
+    // `gen` is an identifier before edition 2024
+    let tokens = quote!(gen { a: 1 });
+    snapshot!(tokens as Expr, @r###"
+    Expr::Struct {
+        path: Path {
+            segments: [
+                PathSegment {
+                    ident: "gen",
+                },
+            ],
+        },
+        fields: [
+            FieldValue {
+                member: Member::Named("a"),
+                colon_token: Some,
+                expr: Expr::Lit {
+                    lit: 1,
+                },
+            },
+        ],
+    }
+    "###);
+
+    syn::parse_str::<syn::Stmt>("let x = gen { a: 1 };").unwrap();
+    syn::parse_str::<syn::Stmt>("let x = gen { yield 1; };").unwrap();
+    let expr = syn::parse_str::<Expr>("gen { ..Default::default() }").unwrap();
+    assert!(matches!(expr, Expr::Struct(_)), "{:?}", expr);
+    let expr = syn::parse_str::<Expr>("async gen {}").unwrap();
+    assert!(matches!(expr, Expr::Gen(_)), "{:?}", expr);
+}
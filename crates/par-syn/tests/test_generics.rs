@@ -8,7 +8,7 @@
 mod macros;
 
 use quote::quote;
-use syn::{DeriveInput, ItemFn, TypeParamBound, WhereClause, WherePredicate};
+use syn::{DeriveInput, ItemFn, Type, TypeParamBound, WhereClause, WherePredicate};
 
 #[test]
 fn test_split_for_impl() {
@@ -274,3 +274,69 @@ fn test_where_clause_at_end_of_input() {
 
     assert_eq!(input.predicates.len(), 0);
 }
+
+#[test]
+fn test_async_fn_bound() {
+    let tokens = quote!(async Fn() -> u8);
+    snapshot!(tokens as TypeParamBound, @r###"
+    TypeParamBound::Trait(TraitBound {
+        asyncness: Some,
+        path: Path {
+            segments: [
+                PathSegment {
+                    ident: "Fn",
+                    arguments: PathArguments::Parenthesized {
+                        output: ReturnType::Type(
+                            Type::Path {
+                                path: Path {
+                                    segments: [
+                                        PathSegment {
+                                            ident: "u8",
+                                        },
+                                    ],
+                                },
+                            },
+                        ),
+                    },
+                },
+            ],
+        },
+    })
+    "###);
+}
+
+#[test]
+fn test_precise_capture() {
+    let tokens = quote!(use<'a, T, Self>);
+    snapshot!(tokens as TypeParamBound, @r###"
+    TypeParamBound::PreciseCapture(PreciseCapture {
+        params: [
+            CapturedParam::Lifetime(Lifetime {
+                ident: "a",
+            }),
+            CapturedParam::Ident("T"),
+            CapturedParam::Ident("Self"),
+        ],
+    })
+    "###);
+
+    let tokens = quote!(impl Sized + use<>);
+    snapshot!(tokens as Type, @r###"
+    Type::ImplTrait {
+        bounds: [
+            TypeParamBound::Trait(TraitBound {
+                path: Path {
+                    segments: [
+                        PathSegment {
+                            ident: "Sized",
+                        },
+                    ],
+                },
+            }),
+            TypeParamBound::PreciseCapture(PreciseCapture),
+        ],
+    }
+    "###);
+
+    syn::parse_str::<Type>("impl use<'a>").unwrap_err();
+}
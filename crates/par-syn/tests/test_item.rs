@@ -324,3 +324,61 @@ fn test_impl_trait_trailing_plus() {
     }
     "###);
 }
+
+#[test]
+fn test_safe_foreign_items() {
+    let tokens = quote! {
+        unsafe extern "C" {
+            pub safe fn f();
+            safe static X: u8;
+            safe!();
+        }
+    };
+    snapshot!(tokens as Item, @r###"
+    Item::ForeignMod {
+        unsafety: Some,
+        abi: Abi {
+            name: Some("C"),
+        },
+        items: [
+            ForeignItem::Fn {
+                vis: Visibility::Public,
+                safety: Some,
+                sig: Signature {
+                    ident: "f",
+                    generics: Generics,
+                    output: ReturnType::Default,
+                },
+            },
+            ForeignItem::Static {
+                vis: Visibility::Inherited,
+                safety: Some,
+                ident: "X",
+                ty: Type::Path {
+                    path: Path {
+                        segments: [
+                            PathSegment {
+                                ident: "u8",
+                            },
+                        ],
+                    },
+                },
+            },
+            ForeignItem::Macro {
+                mac: Macro {
+                    path: Path {
+                        segments: [
+                            PathSegment {
+                                ident: "safe",
+                            },
+                        ],
+                    },
+                    delimiter: MacroDelimiter::Paren,
+                    tokens: TokenStream(``),
+                },
+                semi_token: Some,
+            },
+        ],
+    }
+    "###);
+}
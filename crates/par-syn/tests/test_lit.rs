@@ -10,8 +10,9 @@ mod macros;
 
 use proc_macro2::{Delimiter, Group, Literal, Span, TokenStream, TokenTree};
 use quote::ToTokens;
+use std::ffi::CStr;
 use std::str::FromStr;
-use syn::{Lit, LitFloat, LitInt, LitStr};
+use syn::{Lit, LitCStr, LitFloat, LitInt, LitStr};
 
 fn lit(s: &str) -> Lit {
     let mut tokens = TokenStream::from_str(s).unwrap().into_iter();
@@ -95,6 +96,45 @@ fn byte_strings() {
     test_byte_string("br##\"...\"##q", b"...");
 }
 
+#[test]
+fn c_strings() {
+    fn test_c_string(s: &str, value: &[u8]) {
+        match lit(s) {
+            Lit::CStr(lit) => {
+                assert_eq!(lit.value().as_bytes(), value);
+                let again = lit.into_token_stream().to_string();
+                if again != s {
+                    test_c_string(&again, value);
+                }
+            }
+            wrong => panic!("{:?}", wrong),
+        }
+    }
+
+    test_c_string("c\"a\"", b"a");
+    test_c_string("c\"\"", b"");
+    test_c_string("c\"\\n\\\"\"", b"\n\"");
+    test_c_string("c\"\\x41\\u{1F600}\"", "A\u{1F600}".as_bytes());
+    test_c_string("c\"\\xff\"", b"\xff");
+    test_c_string("c\"escaped\\\n    newline\"", b"escapednewline");
+    test_c_string("cr\"raw\\n\"", b"raw\\n");
+    test_c_string("cr#\"\"raw\"\"#", b"\"raw\"");
+    test_c_string("c\"...\"q", b"...");
+    test_c_string("cr##\"...\"##q", b"...");
+
+    let lit = LitCStr::new(
+        CStr::from_bytes_with_nul(b"a\"\\\x01\0").unwrap(),
+        Span::call_site(),
+    );
+    assert_eq!(lit.value().as_bytes(), b"a\"\\\x01");
+    assert_eq!(lit.token().to_string(), "c\"a\\\"\\\\\\x01\"");
+
+    // a C string can't have a NUL before its end
+    for s in ["c\"a\\0b\"", "c\"a\\x00b\"", "c\"a\\u{0}b\"", "cr\"a\0b\""] {
+        syn::parse_str::<Lit>(s).unwrap_err();
+    }
+}
+
 #[test]
 fn bytes() {
     fn test_byte(s: &str, value: u8) {
@@ -229,6 +269,7 @@ fn suffix() {
         match lit {
             Lit::Str(lit) => lit.suffix().to_owned(),
             Lit::ByteStr(lit) => lit.suffix().to_owned(),
+            Lit::CStr(lit) => lit.suffix().to_owned(),
             Lit::Byte(lit) => lit.suffix().to_owned(),
             Lit::Char(lit) => lit.suffix().to_owned(),
             Lit::Int(lit) => lit.suffix().to_owned(),
@@ -241,6 +282,9 @@ fn suffix() {
     assert_eq!(get_suffix("r\"\"r"), "r");
     assert_eq!(get_suffix("b\"\"b"), "b");
     assert_eq!(get_suffix("br\"\"br"), "br");
+    assert_eq!(get_suffix("c\"\"c"), "c");
+    assert_eq!(get_suffix("cr\"\"cr"), "cr");
+    assert_eq!(get_suffix("cr#\"\"#c"), "c");
     assert_eq!(get_suffix("r#\"\"#r"), "r");
     assert_eq!(get_suffix("'c'c"), "c");
     assert_eq!(get_suffix("b'b'b"), "b");
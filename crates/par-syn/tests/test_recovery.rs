@@ -0,0 +1,81 @@
+#![cfg(all(feature = "full", feature = "span-locations"))]
+
+use quote::ToTokens;
+use syn::{ForeignItem, ImplItem, Item, TraitItem};
+
+fn parse(content: &str) -> (syn::File, Vec<syn::Error>, proc_macro2::SourceFileGuard) {
+    let (parsed, source) = syn::parse_file_recovering(content, "recovery.rs");
+    let (file, errors) = parsed.unwrap();
+    (file, errors, source)
+}
+
+#[test]
+fn test_brace_in_generics() {
+    let (file, errors, _source) = parse(
+        "fn f() where T: Tr<{ N }> { let }\n\
+         const A: S = S { a: };\n\
+         fn g() {}",
+    );
+    assert_eq!(errors.len(), 2);
+    assert_eq!(file.items.len(), 3);
+    match &file.items[0] {
+        Item::Verbatim(tokens) => {
+            assert_eq!(tokens.to_string(), "fn f () where T : Tr < { N } > { let }");
+        }
+        item => panic!("expected a verbatim item, got {}", item.to_token_stream()),
+    }
+    match &file.items[1] {
+        Item::Verbatim(tokens) => assert_eq!(tokens.to_string(), "const A : S = S { a : } ;"),
+        item => panic!("expected a verbatim item, got {}", item.to_token_stream()),
+    }
+    assert!(matches!(&file.items[2], Item::Fn(item) if item.sig.ident == "g"));
+}
+
+#[test]
+fn test_nested_items() {
+    let (file, errors, _source) = parse(
+        "mod m {\n\
+         #![allow(dead_code)]\n\
+         fn ok() {}\n\
+         mod inner { fn bad() -> {} }\n\
+         }\n\
+         impl S { fn a() {} fn b() -> {} }\n\
+         trait T { fn c() -> ; fn d(); }\n\
+         extern \"C\" { fn e() -> ; }",
+    );
+    assert_eq!(errors.len(), 4);
+    assert_eq!(errors[0].span().start().line, 4);
+
+    let Item::Mod(module) = &file.items[0] else {
+        panic!("expected a module");
+    };
+    assert_eq!(module.attrs.len(), 1);
+    let items = &module.content.as_ref().unwrap().1;
+    assert!(matches!(items[0], Item::Fn(_)));
+    let Item::Mod(inner) = &items[1] else {
+        panic!("expected a module");
+    };
+    let inner_items = &inner.content.as_ref().unwrap().1;
+    assert!(matches!(inner_items[..], [Item::Verbatim(_)]));
+
+    let Item::Impl(item) = &file.items[1] else {
+        panic!("expected an impl");
+    };
+    assert!(matches!(
+        item.items[..],
+        [ImplItem::Fn(_), ImplItem::Verbatim(_)]
+    ));
+
+    let Item::Trait(item) = &file.items[2] else {
+        panic!("expected a trait");
+    };
+    assert!(matches!(
+        item.items[..],
+        [TraitItem::Verbatim(_), TraitItem::Fn(_)]
+    ));
+
+    let Item::ForeignMod(item) = &file.items[3] else {
+        panic!("expected an extern block");
+    };
+    assert!(matches!(item.items[..], [ForeignItem::Verbatim(_)]));
+}
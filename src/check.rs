@@ -0,0 +1,325 @@
+use crate::collector::{metric_locations, metric_values, Location};
+use anyhow::{anyhow, Context, Result};
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fmt::{self, Display, Write};
+use std::str::FromStr;
+
+/// The metric used to compute `per_kloc` statistics
+const LINE_COUNT_METRIC: &str = "per_file.line_count";
+/// How many offending observations to list for a single violation
+const MAX_OFFENDERS: usize = 10;
+
+/// A statistic computed over the observations of a metric
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Deserialize)]
+#[serde(try_from = "String")]
+pub enum Statistic {
+    /// the largest observation, i.e. a limit on every single observation
+    Max,
+    Avg,
+    Sum,
+    Count,
+    /// nearest-rank percentile
+    Percentile(u8),
+    /// sum of observations per 1000 lines of code
+    PerKloc,
+}
+
+impl FromStr for Statistic {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Ok(match s {
+            "max" => Self::Max,
+            "avg" => Self::Avg,
+            "sum" => Self::Sum,
+            "count" => Self::Count,
+            "per_kloc" => Self::PerKloc,
+            _ => {
+                let percentile = s
+                    .strip_prefix('p')
+                    .and_then(|p| p.parse::<u8>().ok())
+                    .filter(|&p| p <= 100)
+                    .ok_or_else(|| {
+                        anyhow!(
+                            "Unknown statistic `{}`, expected one of max, avg, sum, count, per_kloc or p0..p100",
+                            s
+                        )
+                    })?;
+                Self::Percentile(percentile)
+            }
+        })
+    }
+}
+
+impl TryFrom<String> for Statistic {
+    type Error = anyhow::Error;
+
+    fn try_from(value: String) -> Result<Self> {
+        value.parse()
+    }
+}
+
+impl Display for Statistic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Statistic::Max => write!(f, "max"),
+            Statistic::Avg => write!(f, "avg"),
+            Statistic::Sum => write!(f, "sum"),
+            Statistic::Count => write!(f, "count"),
+            Statistic::Percentile(p) => write!(f, "p{}", p),
+            Statistic::PerKloc => write!(f, "per_kloc"),
+        }
+    }
+}
+
+impl Statistic {
+    /// Computes the statistic, returns `None` if it's not defined (e.g. `max` of no observations)
+    pub fn compute(
+        self,
+        values: &[f64],
+        kloc: impl FnOnce() -> Result<f64>,
+    ) -> Result<Option<f64>> {
+        let sum = || values.iter().sum::<f64>();
+        Ok(match self {
+            Statistic::Max => values.iter().copied().reduce(f64::max),
+            Statistic::Avg => (!values.is_empty()).then(|| sum() / values.len() as f64),
+            Statistic::Sum => Some(sum()),
+            Statistic::Count => Some(values.len() as f64),
+            Statistic::Percentile(p) => {
+                let mut values = values.to_vec();
+                values.sort_by(f64::total_cmp);
+                let rank = (p as f64 / 100.0 * values.len() as f64).ceil() as usize;
+                values.get(rank.saturating_sub(1)).copied()
+            }
+            Statistic::PerKloc => Some(sum() / kloc()?),
+        })
+    }
+}
+
+/// Upper limits on the metrics of a repository
+///
+/// Loaded from the `[budgets]` table of `ifcount.toml`:
+///
+/// ```toml
+/// [budgets]
+/// "complexity.all_fn" = { max = 25 }
+/// "fn_depth" = { p95 = 4 }
+/// "if_count" = { per_kloc = 10 }
+/// ```
+#[derive(Debug, Default, Deserialize)]
+pub struct BudgetConfig {
+    #[serde(default)]
+    pub budgets: BTreeMap<String, BTreeMap<Statistic, f64>>,
+}
+
+#[derive(Debug)]
+pub struct Violation {
+    pub metric: String,
+    pub statistic: Statistic,
+    pub actual: f64,
+    pub limit: f64,
+    /// The observations exceeding the limit, largest first (only for `max`)
+    pub offenders: Vec<(f64, Option<Location>)>,
+}
+
+/// Formats a metric value without the noise of long fractions
+pub fn format_value(value: f64) -> String {
+    let value = format!("{:.2}", value);
+    match value.trim_end_matches('0').trim_end_matches('.') {
+        "-0" => "0".to_string(),
+        value => value.to_string(),
+    }
+}
+
+impl Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{}: {} is {}, the budget is {}",
+            self.metric,
+            self.statistic,
+            format_value(self.actual),
+            format_value(self.limit)
+        )?;
+        for (value, location) in self.offenders.iter().take(MAX_OFFENDERS) {
+            write!(f, "    {:>8}", format_value(*value))?;
+            if let Some(location) = location {
+                write!(
+                    f,
+                    "  {}:{}:{}",
+                    location.path, location.start.line, location.start.column
+                )?;
+                if let Some(item) = &location.item {
+                    write!(f, " ({})", item)?;
+                }
+            }
+            writeln!(f)?;
+        }
+        if self.offenders.len() > MAX_OFFENDERS {
+            writeln!(
+                f,
+                "    ... and {} more",
+                self.offenders.len() - MAX_OFFENDERS
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Evaluates the budgets against flattened metrics (as in `RepoResult`)
+pub fn check_budgets(
+    config: &BudgetConfig,
+    metrics: &BTreeMap<String, Value>,
+    locations: &BTreeMap<String, Value>,
+) -> Result<Vec<Violation>> {
+    let kloc = || {
+        let lines = metrics
+            .get(LINE_COUNT_METRIC)
+            .and_then(metric_values)
+            .map(|v| v.iter().sum::<f64>())
+            .filter(|&lines| lines > 0.0)
+            .ok_or_else(|| {
+                anyhow!(
+                    "`per_kloc` budgets need the `{}` metric to be non-empty",
+                    LINE_COUNT_METRIC
+                )
+            })?;
+        Ok(lines / 1000.0)
+    };
+
+    let mut violations = Vec::new();
+    for (metric, limits) in &config.budgets {
+        let value = metrics
+            .get(metric)
+            .with_context(|| format!("Unknown metric `{}` in budget config", metric))?;
+        let values =
+            metric_values(value).with_context(|| format!("Metric `{}` is not numeric", metric))?;
+
+        for (&statistic, &limit) in limits {
+            let Some(actual) = statistic
+                .compute(&values, kloc)
+                .with_context(|| format!("Computing {} of `{}`", statistic, metric))?
+            else {
+                continue;
+            };
+            if actual <= limit {
+                continue;
+            }
+
+            let mut offenders = Vec::new();
+            if statistic == Statistic::Max {
+                let mut locations = metric_locations(locations, metric)?.into_iter();
+                offenders = values
+                    .iter()
+                    .map(|&value| (value, locations.next().flatten()))
+                    .filter(|&(value, _)| value > limit)
+                    .collect::<Vec<_>>();
+                offenders.sort_by(|(a, _), (b, _)| b.total_cmp(a));
+            }
+
+            violations.push(Violation {
+                metric: metric.clone(),
+                statistic,
+                actual,
+                limit,
+                offenders,
+            });
+        }
+    }
+
+    Ok(violations)
+}
+
+/// Renders a human-readable report of the violations
+pub fn report(violations: &[Violation]) -> String {
+    let mut report = String::new();
+    for violation in violations {
+        write!(report, "{}", violation).unwrap();
+    }
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{check_budgets, report, BudgetConfig};
+    use expect_test::expect;
+    use serde_json::json;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn parse_config() {
+        let config: BudgetConfig = toml::from_str(
+            r#"
+            [budgets]
+            "complexity.all_fn" = { max = 25 }
+            "fn_depth" = { p95 = 4, avg = 1.5 }
+            "if_count" = { per_kloc = 10 }
+            "#,
+        )
+        .unwrap();
+
+        expect![[r#"
+            {
+                "complexity.all_fn": {
+                    Max: 25.0,
+                },
+                "fn_depth": {
+                    Avg: 1.5,
+                    Percentile(
+                        95,
+                    ): 4.0,
+                },
+                "if_count": {
+                    PerKloc: 10.0,
+                },
+            }
+        "#]]
+        .assert_debug_eq(&config.budgets);
+
+        let err = toml::from_str::<BudgetConfig>("[budgets]\nfn_depth = { p101 = 4 }").unwrap_err();
+        assert!(err.to_string().contains("Unknown statistic `p101`"));
+    }
+
+    #[test]
+    fn violations() {
+        let config: BudgetConfig = toml::from_str(
+            r#"
+            [budgets]
+            "complexity.all_fn" = { max = 3, sum = 100 }
+            "fn_depth" = { p50 = 1, p100 = 2 }
+            "if_count" = { per_kloc = 10 }
+            "#,
+        )
+        .unwrap();
+        let metrics = BTreeMap::from([
+            ("complexity.all_fn".to_string(), json!([1, 5, 2, 4])),
+            ("fn_depth".to_string(), json!([0, 1, 1, 3])),
+            ("if_count".to_string(), json!(3)),
+            ("per_file.line_count".to_string(), json!([100, 150])),
+        ]);
+        let location = |line, item| {
+            json!({
+                "path": "src/lib.rs",
+                "item": item,
+                "start": {"line": line, "column": 0},
+                "end": {"line": line + 1, "column": 1},
+            })
+        };
+        let locations = BTreeMap::from([(
+            "complexity.all_fn".to_string(),
+            json!([location(1, "a"), location(10, "b"), null, location(30, "d")]),
+        )]);
+
+        let violations = check_budgets(&config, &metrics, &locations).unwrap();
+        expect![[r#"
+            complexity.all_fn: max is 5, the budget is 3
+                       5  src/lib.rs:10:0 (b)
+                       4  src/lib.rs:30:0 (d)
+            fn_depth: p100 is 3, the budget is 2
+            if_count: per_kloc is 12, the budget is 10
+        "#]]
+        .assert_eq(&report(&violations));
+    }
+}
@@ -0,0 +1,210 @@
+//! Caches on disk: the data downloaded from GitHub and the metrics of the files
+
+use super::metrics::util::{MacroCoverage, WALK_VERSION};
+use super::metrics::MetricCollectorBox;
+use super::rust_code_analysis::RcaCollector;
+use super::{FileText, ObserverKind};
+use anyhow::{Context, Result};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use tracing::warn;
+
+/// A key-value store of zstd-compressed bincode values
+pub struct Cache {
+    directory: PathBuf,
+}
+
+fn decode<T: DeserializeOwned>(content: &[u8]) -> Result<T> {
+    let content = zstd::decode_all(content).context("Decompressing cache entry")?;
+    bincode::deserialize(&content).context("Deserializing cache entry")
+}
+
+fn encode<T: Serialize>(value: &T) -> Result<Vec<u8>> {
+    let content = bincode::serialize(value).context("Serializing cache entry")?;
+    zstd::encode_all(&content[..], 5).context("Compressing cache entry")
+}
+
+impl Cache {
+    pub fn new(directory: PathBuf) -> Self {
+        Self { directory }
+    }
+
+    pub async fn get<T: DeserializeOwned>(&self, key: &str) -> Result<Option<T>> {
+        let Some(meta) = cacache::index::find_async(&self.directory, key)
+            .await
+            .context("Finding cache entry")?
+        else {
+            return Ok(None);
+        };
+        let content = cacache::read_hash(&self.directory, &meta.integrity)
+            .await
+            .context("Reading cache entry")?;
+        decode(&content).map(Some)
+    }
+
+    pub async fn set<T: Serialize>(&self, key: &str, value: &T) -> Result<()> {
+        cacache::write(&self.directory, key, encode(value)?)
+            .await
+            .context("Writing cache entry")?;
+        Ok(())
+    }
+
+    /// Same as `get`, for the code running outside of the async runtime
+    pub fn get_sync<T: DeserializeOwned>(&self, key: &str) -> Result<Option<T>> {
+        let Some(meta) =
+            cacache::index::find(&self.directory, key).context("Finding cache entry")?
+        else {
+            return Ok(None);
+        };
+        let content = cacache::read_hash_sync(&self.directory, &meta.integrity)
+            .context("Reading cache entry")?;
+        decode(&content).map(Some)
+    }
+
+    pub fn set_sync<T: Serialize>(&self, key: &str, value: &T) -> Result<()> {
+        cacache::write_sync(&self.directory, key, encode(value)?).context("Writing cache entry")?;
+        Ok(())
+    }
+
+    /// Opens an entry written with `writer`, to be read as it is rather than decoded
+    pub async fn reader(&self, key: &str) -> Result<Option<cacache::SyncReader>> {
+        let Some(meta) = cacache::index::find_async(&self.directory, key)
+            .await
+            .context("Finding cache entry")?
+        else {
+            return Ok(None);
+        };
+        cacache::SyncReader::open_hash(&self.directory, meta.integrity)
+            .context("Reading cache entry")
+            .map(Some)
+    }
+
+    /// Starts an entry written as it is, for the values too big to be encoded at once
+    ///
+    /// The entry is only there once the writer is committed.
+    pub fn writer(&self, key: &str) -> Result<cacache::SyncWriter> {
+        cacache::SyncWriter::create(&self.directory, key).context("Writing cache entry")
+    }
+}
+
+/// Has to be bumped whenever the fields of `CachedFile` change, as bincode would misread the entries of another layout
+const CACHE_FORMAT: u32 = 2;
+
+/// What is remembered about a file with a given content
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct CachedFile {
+    /// whether the whole file is `#![cfg(test)]`
+    pub test_file: bool,
+    /// whether the `#[cfg(test)]` items are split out, so that there are three parts instead of one
+    pub split: bool,
+    /// the number of items syn could not parse
+    pub recovered_items: usize,
+    pub macros: MacroCoverage,
+    /// the encoded metrics of every part of the file (see `FileParts`), keyed by `collector_key`
+    pub metrics: BTreeMap<String, Vec<Vec<u8>>>,
+}
+
+pub fn collector_key(collector: &MetricCollectorBox) -> String {
+    format!("{}@{}", collector.name(), collector.version())
+}
+
+pub fn rca_key(rca: &RcaCollector) -> String {
+    format!("rca@{}", rca.version())
+}
+
+/// The metrics of the files, keyed by the hash of their content
+///
+/// The same files are shared by forks, commits and even unrelated repositories, so they only have to be parsed once.
+pub struct MetricCache {
+    cache: Cache,
+    observer: ObserverKind,
+}
+
+impl MetricCache {
+    pub fn new(directory: PathBuf, observer: ObserverKind) -> Self {
+        Self {
+            cache: Cache::new(directory),
+            observer,
+        }
+    }
+
+    fn key(&self, file: &FileText) -> String {
+        // the locations include the path, so they can't be reused for a file somewhere else
+        let path = match self.observer {
+            ObserverKind::Located => file.path.as_str(),
+            ObserverKind::Unaggregated | ObserverKind::Histogram => "",
+        };
+        format!(
+            "metrics/{}/{}/{}/{:?}/{}/{}",
+            env!("CARGO_PKG_VERSION"),
+            CACHE_FORMAT,
+            WALK_VERSION,
+            self.observer,
+            path,
+            cacache::Integrity::from(&file.content)
+        )
+    }
+
+    /// A broken cache is not worth failing the collection over, so the errors are only logged
+    pub fn get(&self, file: &FileText) -> Option<CachedFile> {
+        self.cache.get_sync(&self.key(file)).unwrap_or_else(|e| {
+            warn!("Cannot read cached metrics of {}: {:#}", file.path, e);
+            None
+        })
+    }
+
+    pub fn set(&self, file: &FileText, cached: &CachedFile) {
+        if let Err(e) = self.cache.set_sync(&self.key(file), cached) {
+            warn!("Cannot cache metrics of {}: {:#}", file.path, e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::collector::workspace::Workspace;
+    use crate::collector::{aggregate_results, Analyzer, CollectConfig, FileText};
+
+    #[test]
+    fn cached_metrics_are_the_same() {
+        let directory = std::env::temp_dir().join(format!("ifcount-cache-{}", std::process::id()));
+        let config = CollectConfig {
+            cache_dir: Some(directory.clone()),
+            ..CollectConfig::default()
+        };
+        let file = FileText {
+            path: "src/lib.rs".into(),
+            content: r#"
+pub struct Foo { pub a: u32 }
+impl Foo { fn foo(&mut self) { if self.a > 1 { println!("{}", |x: u32| x); } } }
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn foo() { assert!(true) }
+}
+"#
+            .to_string(),
+        };
+
+        let collect = |config: &CollectConfig| {
+            let analyzer = Analyzer::new(config);
+            let results = analyzer.analyze_files(vec![file.clone()]);
+            aggregate_results(&analyzer, &Workspace::default(), &results).unwrap()
+        };
+
+        let uncached = collect(&CollectConfig::default());
+        let first = collect(&config);
+        let analyzer = Analyzer::new(&config);
+        let cached = analyzer.cache.as_ref().unwrap().get(&file).unwrap();
+        assert!(cached.split);
+        assert!(analyzer.decode_parts(&cached).unwrap().is_some());
+        let second = collect(&config);
+
+        std::fs::remove_dir_all(&directory).unwrap();
+        assert_eq!(first, uncached);
+        assert_eq!(second, uncached);
+    }
+}
@@ -0,0 +1,286 @@
+//! Telling library code apart from tests, benchmarks, examples and the like
+
+use super::{FileAst, FileText};
+use ::rust_code_analysis::FuncSpace;
+use std::ops::RangeInclusive;
+use syn::punctuated::Punctuated;
+use syn::spanned::Spanned;
+use syn::{Attribute, Item, Meta, Token};
+
+/// How many lines from the top of a file are searched for the `@generated` marker
+const GENERATED_MARKER_LINES: usize = 5;
+
+/// What a file (or a part of it) is for, the metrics are reported for every class separately
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum FileClass {
+    Lib,
+    /// `src/main.rs` and `src/bin/`
+    Bin,
+    /// integration tests and `#[cfg(test)]` items
+    Test,
+    Bench,
+    Example,
+    /// build scripts
+    Build,
+    /// files marked with `@generated`
+    Generated,
+}
+
+impl FileClass {
+    pub const ALL: [FileClass; 7] = [
+        FileClass::Lib,
+        FileClass::Bin,
+        FileClass::Test,
+        FileClass::Bench,
+        FileClass::Example,
+        FileClass::Build,
+        FileClass::Generated,
+    ];
+
+    /// The prefix of the metrics of the class
+    pub fn name(self) -> &'static str {
+        match self {
+            FileClass::Lib => "lib",
+            FileClass::Bin => "bin",
+            FileClass::Test => "test",
+            FileClass::Bench => "bench",
+            FileClass::Example => "example",
+            FileClass::Build => "build",
+            FileClass::Generated => "generated",
+        }
+    }
+
+    /// Whether the `#[cfg(test)]` items should be split out of files of this class
+    pub fn has_inline_tests(self) -> bool {
+        matches!(self, FileClass::Lib | FileClass::Bin)
+    }
+}
+
+/// Classifies a file by its path, following the cargo conventions for target locations
+///
+/// The path can point into a package in a subdirectory, e.g. `crates/foo/tests/bar.rs` is a test.
+pub fn classify_path(path: &str) -> FileClass {
+    let components = path.split('/').collect::<Vec<_>>();
+    let (dirs, file_name) = components.split_at(components.len() - 1);
+
+    // the outermost target directory decides, so that `tests/fixtures/src/lib.rs` is a test
+    for (i, dir) in dirs.iter().enumerate() {
+        match *dir {
+            "tests" => return FileClass::Test,
+            "benches" => return FileClass::Bench,
+            "examples" => return FileClass::Example,
+            "src" => {
+                let rest = &components[i + 1..];
+                return if rest == ["main.rs"] || rest.first() == Some(&"bin") {
+                    FileClass::Bin
+                } else {
+                    FileClass::Lib
+                };
+            }
+            _ => {}
+        }
+    }
+
+    if file_name == ["build.rs"] {
+        FileClass::Build
+    } else {
+        FileClass::Lib
+    }
+}
+
+/// Classifies a file by its path and its contents
+pub fn classify(file: &FileText) -> FileClass {
+    let generated = file
+        .content
+        .lines()
+        .take(GENERATED_MARKER_LINES)
+        .any(|line| line.contains("@generated"));
+    if generated {
+        return FileClass::Generated;
+    }
+
+    classify_path(file.path.as_str())
+}
+
+fn is_test_predicate(meta: &Meta) -> bool {
+    match meta {
+        Meta::Path(path) => path.is_ident("test"),
+        // `all(test, feature = "foo")` is test-only too, `any(..)` and `not(..)` are not
+        Meta::List(list) if list.path.is_ident("all") => list
+            .parse_args_with(Punctuated::<Meta, Token![,]>::parse_terminated)
+            .is_ok_and(|metas| metas.iter().any(is_test_predicate)),
+        _ => false,
+    }
+}
+
+/// Whether the attributes include a `#[cfg(test)]`
+fn is_test_only(attrs: &[Attribute]) -> bool {
+    attrs.iter().any(|attr| {
+        attr.path().is_ident("cfg")
+            && attr
+                .parse_args::<Meta>()
+                .is_ok_and(|meta| is_test_predicate(&meta))
+    })
+}
+
+fn item_attrs(item: &Item) -> &[Attribute] {
+    match item {
+        Item::Const(i) => &i.attrs,
+        Item::Enum(i) => &i.attrs,
+        Item::ExternCrate(i) => &i.attrs,
+        Item::Fn(i) => &i.attrs,
+        Item::ForeignMod(i) => &i.attrs,
+        Item::Impl(i) => &i.attrs,
+        Item::Macro(i) => &i.attrs,
+        Item::Mod(i) => &i.attrs,
+        Item::Static(i) => &i.attrs,
+        Item::Struct(i) => &i.attrs,
+        Item::Trait(i) => &i.attrs,
+        Item::TraitAlias(i) => &i.attrs,
+        Item::Type(i) => &i.attrs,
+        Item::Union(i) => &i.attrs,
+        Item::Use(i) => &i.attrs,
+        _ => &[],
+    }
+}
+
+/// A file with the `#[cfg(test)]` items split out of it
+pub struct SplitFile {
+    pub main: FileAst,
+    pub tests: FileAst,
+    /// lines spanned by the test items
+    pub test_lines: Vec<RangeInclusive<usize>>,
+}
+
+/// Whether the whole file is `#![cfg(test)]`
+pub fn is_test_file(file: &FileAst) -> bool {
+    is_test_only(&file.content.attrs)
+}
+
+/// Splits the `#[cfg(test)]` items (usually `mod tests`) out of a file, `None` if there are none
+pub fn split_tests(file: &FileAst) -> Option<SplitFile> {
+    let (tests, main) = file
+        .content
+        .items
+        .iter()
+        .cloned()
+        .partition::<Vec<_>, _>(|item| is_test_only(item_attrs(item)));
+    if tests.is_empty() {
+        return None;
+    }
+
+    let test_lines = tests
+        .iter()
+        .map(|item| item.span().start().line..=item.span().end().line)
+        .collect();
+
+    Some(SplitFile {
+        main: FileAst {
+            path: file.path.clone(),
+            content: syn::File {
+                items: main,
+                ..file.content.clone()
+            },
+        },
+        tests: FileAst {
+            path: file.path.clone(),
+            content: syn::File {
+                shebang: None,
+                attrs: Vec::new(),
+                items: tests,
+            },
+        },
+        test_lines,
+    })
+}
+
+fn with_spaces(space: &FuncSpace, spaces: Vec<FuncSpace>) -> FuncSpace {
+    FuncSpace {
+        name: space.name.clone(),
+        start_line: space.start_line,
+        end_line: space.end_line,
+        kind: space.kind,
+        spaces,
+        metrics: space.metrics.clone(),
+    }
+}
+
+/// Splits the spaces of a file found by Rust Code Analysis the same way as `split_tests` splits the items
+///
+/// Returns the main and the test parts.
+pub fn split_funcspace(
+    space: &FuncSpace,
+    test_lines: &[RangeInclusive<usize>],
+) -> (FuncSpace, FuncSpace) {
+    let (tests, main) = space
+        .spaces
+        .iter()
+        .cloned()
+        .partition::<Vec<_>, _>(|s| test_lines.iter().any(|r| r.contains(&s.start_line)));
+
+    (with_spaces(space, main), with_spaces(space, tests))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{classify, classify_path, split_tests, FileClass};
+    use crate::collector::FileText;
+
+    #[test]
+    fn paths() {
+        for (path, class) in [
+            ("src/lib.rs", FileClass::Lib),
+            ("src/collector/mod.rs", FileClass::Lib),
+            ("src/main.rs", FileClass::Bin),
+            ("src/bin/tool.rs", FileClass::Bin),
+            ("src/bin/tool/main.rs", FileClass::Bin),
+            ("crates/foo/src/tests.rs", FileClass::Lib),
+            ("crates/foo/tests/it.rs", FileClass::Test),
+            ("tests/fixtures/src/lib.rs", FileClass::Test),
+            ("src/tests/helpers.rs", FileClass::Lib),
+            ("benches/parse.rs", FileClass::Bench),
+            ("examples/demo/main.rs", FileClass::Example),
+            ("build.rs", FileClass::Build),
+            ("crates/foo/build.rs", FileClass::Build),
+            ("lib.rs", FileClass::Lib),
+        ] {
+            assert_eq!(classify_path(path), class, "{}", path);
+        }
+    }
+
+    #[test]
+    fn generated() {
+        let file = FileText {
+            path: "src/parser.rs".into(),
+            content: "// @generated by lalrpop\nfn main() {}\n".to_string(),
+        };
+        assert_eq!(classify(&file), FileClass::Generated);
+    }
+
+    #[test]
+    fn inline_tests() {
+        let parsed = FileText {
+            path: "src/lib.rs".into(),
+            content: r#"
+fn lib() {}
+
+#[cfg(all(test, feature = "foo"))]
+mod tests {
+    #[test]
+    fn test() {}
+}
+
+#[cfg(not(test))]
+fn not_test() {}
+"#
+            .to_string(),
+        }
+        .parse()
+        .unwrap();
+
+        let split = split_tests(&parsed.ast).unwrap();
+        assert_eq!(split.main.content.items.len(), 2);
+        assert_eq!(split.tests.content.items.len(), 1);
+        assert_eq!(split.test_lines, [4..=8]);
+    }
+}
@@ -0,0 +1,233 @@
+use super::git::FetchStrategy;
+use super::metrics;
+use super::metrics::query::{self, QueryMetric, QUERIES_COLLECTOR};
+use super::rust_code_analysis::RcaCollector;
+use super::workspace::is_manifest;
+use super::ObserverKind;
+use anyhow::{bail, Context, Result};
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+/// Pseudo-collector names for the metrics which are not collected by `MetricCollector`s
+const RCA_COLLECTOR: &str = "rca";
+const REPO_COLLECTOR: &str = "repo";
+
+/// The part of `ifcount.toml` that controls what gets collected
+///
+/// ```toml
+/// collectors = ["fn_depth", "complexity", "rca"]
+/// skip_collectors = ["rca"]
+/// include = ["src/**"]
+/// exclude = ["vendor/**", "**/generated/**"]
+/// observer = "histogram"
+/// fetch = "blobs"
+///
+/// [queries.unwrap_calls]
+/// query = '(ExprMethodCall method: "unwrap")'
+/// ```
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct CollectConfig {
+    /// The collectors to run (by the name of their metrics), all of them if not set
+    pub collectors: Option<Vec<String>>,
+    /// The collectors not to run
+    pub skip_collectors: Vec<String>,
+    /// Globs for the files to collect metrics from (relative to the repository root), all `.rs` files if empty
+    pub include: Vec<String>,
+    /// Globs for the files to ignore, takes precedence over `include`
+    pub exclude: Vec<String>,
+    pub observer: ObserverKind,
+    /// How the files of GitHub repositories are downloaded
+    pub fetch: FetchStrategy,
+    /// Metrics counting the matches of queries, by their names
+    pub queries: BTreeMap<String, QueryMetric>,
+    /// Where to cache the metrics of the files, not cached if `None`
+    ///
+    /// Set from the command line rather than from the config.
+    #[serde(skip)]
+    pub cache_dir: Option<PathBuf>,
+}
+
+impl Default for CollectConfig {
+    fn default() -> Self {
+        Self {
+            collectors: None,
+            skip_collectors: Vec::new(),
+            include: Vec::new(),
+            // we don't want vendored dependencies
+            exclude: vec!["vendor/**".to_string()],
+            observer: ObserverKind::default(),
+            fetch: FetchStrategy::default(),
+            queries: BTreeMap::new(),
+            cache_dir: None,
+        }
+    }
+}
+
+/// Names of all the collectors that can be enabled or disabled
+pub fn collector_names() -> Vec<&'static str> {
+    let mut names = metrics::get_metric_collectors(ObserverKind::default())
+        .iter()
+        .map(|c| c.name())
+        .collect::<Vec<_>>();
+    names.push(QUERIES_COLLECTOR);
+    names.push(RCA_COLLECTOR);
+    names.push(REPO_COLLECTOR);
+    names
+}
+
+fn build_globset(globs: &[String]) -> Result<GlobSet> {
+    let mut builder = GlobSetBuilder::new();
+    for glob in globs {
+        builder.add(Glob::new(glob).with_context(|| format!("Invalid glob `{}`", glob))?);
+    }
+    Ok(builder.build()?)
+}
+
+impl CollectConfig {
+    /// Checks that the collector names and globs make sense
+    pub fn validate(&self) -> Result<()> {
+        let known = collector_names();
+        let names = self
+            .collectors
+            .iter()
+            .flatten()
+            .chain(&self.skip_collectors);
+        for name in names {
+            if !known.contains(&name.as_str()) {
+                bail!(
+                    "Unknown collector `{}`, expected one of: {}",
+                    name,
+                    known.join(", ")
+                );
+            }
+        }
+        for name in self.queries.keys() {
+            if name.is_empty() || !name.chars().all(|c| c.is_alphanumeric() || c == '_') {
+                bail!(
+                    "Invalid query name `{}`, only letters, digits and `_` are allowed",
+                    name
+                );
+            }
+        }
+        self.file_filter()?;
+        Ok(())
+    }
+
+    pub fn is_enabled(&self, collector: &str) -> bool {
+        self.collectors
+            .as_ref()
+            .is_none_or(|c| c.iter().any(|c| c == collector))
+            && !self.skip_collectors.iter().any(|c| c == collector)
+    }
+
+    pub fn rca_enabled(&self) -> bool {
+        self.is_enabled(RCA_COLLECTOR)
+    }
+
+    pub fn repo_enabled(&self) -> bool {
+        self.is_enabled(REPO_COLLECTOR)
+    }
+
+    pub fn rca_collector(&self) -> Option<RcaCollector> {
+        self.rca_enabled().then(|| RcaCollector::new(self.observer))
+    }
+
+    pub fn metric_collectors(&self) -> Vec<metrics::MetricCollectorBox> {
+        let mut collectors = metrics::get_metric_collectors(self.observer)
+            .into_iter()
+            .filter(|c| self.is_enabled(c.name()))
+            .collect::<Vec<_>>();
+        if !self.queries.is_empty() && self.is_enabled(QUERIES_COLLECTOR) {
+            collectors.push(query::make_collector(&self.queries, self.observer));
+        }
+        collectors
+    }
+
+    pub fn file_filter(&self) -> Result<FileFilter> {
+        Ok(FileFilter {
+            include: (!self.include.is_empty())
+                .then(|| build_globset(&self.include))
+                .transpose()
+                .context("Building `include` globs")?,
+            exclude: build_globset(&self.exclude).context("Building `exclude` globs")?,
+        })
+    }
+}
+
+/// Decides which files to collect the metrics from
+#[derive(Clone)]
+pub struct FileFilter {
+    include: Option<GlobSet>,
+    exclude: GlobSet,
+}
+
+impl FileFilter {
+    /// `path` is relative to the repository root, with `/` as a separator
+    pub fn matches(&self, path: &str) -> bool {
+        path.ends_with(".rs")
+            && self.include.as_ref().is_none_or(|i| i.is_match(path))
+            && !self.exclude.is_match(path)
+    }
+
+    /// Whether the file is needed at all: either to collect the metrics from or to find the packages
+    pub fn wants(&self, path: &str) -> bool {
+        self.matches(path) || (is_manifest(path) && !self.exclude.is_match(path))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CollectConfig;
+
+    #[test]
+    fn file_filter() {
+        let filter = CollectConfig::default().file_filter().unwrap();
+        assert!(filter.matches("src/main.rs"));
+        assert!(!filter.matches("vendor/foo/src/lib.rs"));
+        assert!(!filter.matches("README.md"));
+        assert!(filter.wants("crates/foo/Cargo.toml"));
+        assert!(!filter.wants("vendor/foo/Cargo.toml"));
+
+        let config: CollectConfig = toml::from_str(
+            r#"
+            include = ["src/**", "crates/*/src/**"]
+            exclude = ["**/generated/**"]
+            "#,
+        )
+        .unwrap();
+        let filter = config.file_filter().unwrap();
+        assert!(filter.matches("src/main.rs"));
+        assert!(filter.matches("crates/foo/src/lib.rs"));
+        assert!(!filter.matches("src/generated/parser.rs"));
+        assert!(!filter.matches("tests/test.rs"));
+        // the default exclusion is replaced
+        assert!(filter.matches("src/vendor/lib.rs"));
+    }
+
+    #[test]
+    fn collectors() {
+        let config: CollectConfig = toml::from_str(
+            r#"
+            collectors = ["fn_depth", "if_count", "rca"]
+            skip_collectors = ["if_count"]
+            "#,
+        )
+        .unwrap();
+        config.validate().unwrap();
+        let names = config
+            .metric_collectors()
+            .iter()
+            .map(|c| c.name())
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["fn_depth"]);
+        assert!(config.rca_enabled());
+        assert!(!config.repo_enabled());
+
+        let config: CollectConfig = toml::from_str(r#"collectors = ["if_cuont"]"#).unwrap();
+        let err = config.validate().unwrap_err();
+        assert!(err.to_string().contains("Unknown collector `if_cuont`"));
+    }
+}
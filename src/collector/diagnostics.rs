@@ -0,0 +1,219 @@
+//! Reporting the files that are missing from the metrics, so that the datasets with a bad coverage can be spotted
+
+use super::metrics::util::Position;
+use super::FileResult;
+use serde::{Deserialize, Serialize};
+use std::borrow::Borrow;
+
+/// The step of the analysis a file failed at
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Stage {
+    /// the file could not be read (or is not UTF-8), it's missing from all the metrics
+    Read,
+    /// the file could not be parsed by syn, it's missing from all the metrics except `rca`
+    Syn,
+    /// the file was rejected by Rust Code Analysis, it's missing from the `rca` metrics
+    Rca,
+}
+
+/// A file that is missing from some of the metrics
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SkippedFile {
+    /// path of the file, relative to repo root
+    pub path: String,
+    pub stage: Stage,
+    pub message: String,
+    /// where in the file the error is, if it's known
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub position: Option<Position>,
+}
+
+impl SkippedFile {
+    pub fn new(path: impl Into<String>, stage: Stage, message: impl Into<String>) -> Self {
+        Self {
+            path: path.into(),
+            stage,
+            message: message.into(),
+            position: None,
+        }
+    }
+
+    pub fn at(self, position: Position) -> Self {
+        Self {
+            position: Some(position),
+            ..self
+        }
+    }
+}
+
+/// How much of the repository made it into the metrics
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Coverage {
+    /// rust files wanted by the filter
+    pub files_seen: usize,
+    /// files parsed by syn, they are counted by all the metrics except `rca`
+    pub files_parsed: usize,
+    /// files that are counted by all the metrics
+    pub files_analyzed: usize,
+    pub bytes_seen: usize,
+    /// size of the files that are missing from some of the metrics
+    pub bytes_skipped: usize,
+    /// items syn could not parse, the rest of their files is still counted
+    pub items_recovered: usize,
+    /// macro invocations whose arguments are parsed as code and counted by the metrics
+    pub macros_parsed: usize,
+    /// macro invocations whose arguments are not code syn understands, like the ones of `html!`
+    pub macros_unparsed: usize,
+    /// lines taken by the arguments of `macros_unparsed`
+    pub macro_lines_unparsed: usize,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Diagnostics {
+    pub coverage: Coverage,
+    /// in the order of the files, a file can fail at several stages
+    pub skipped: Vec<SkippedFile>,
+    /// whether GitHub could not list all the files of the repository, so some of them are not even `files_seen`
+    #[serde(default)]
+    pub listing_truncated: bool,
+}
+
+impl Diagnostics {
+    pub(super) fn of(results: &[impl Borrow<FileResult>]) -> Self {
+        let mut diagnostics = Self::default();
+        for result in results {
+            let result = result.borrow();
+            let coverage = &mut diagnostics.coverage;
+            coverage.files_seen += 1;
+            coverage.bytes_seen += result.bytes;
+            coverage.items_recovered += result.recovered_items;
+            coverage.macros_parsed += result.macros.parsed;
+            coverage.macros_unparsed += result.macros.unparsed;
+            coverage.macro_lines_unparsed += result.macros.unparsed_lines;
+            if result.whole.collectors.is_some() {
+                coverage.files_parsed += 1;
+            }
+            if result.skipped.is_empty() {
+                coverage.files_analyzed += 1;
+            } else {
+                coverage.bytes_skipped += result.bytes;
+                diagnostics.skipped.extend(result.skipped.iter().cloned());
+            }
+        }
+        diagnostics
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Coverage, Stage};
+    use crate::collector::metrics::util::Position;
+    use crate::collector::{Analyzer, CollectConfig, FileText};
+
+    #[test]
+    fn skipped_files() {
+        let analyzer = Analyzer::new(&CollectConfig::default());
+        let file = |path: &str, content: &str| FileText {
+            path: path.into(),
+            content: content.to_string(),
+        };
+        let results = analyzer.analyze_files(vec![
+            file("src/lib.rs", "fn foo() { if true {} }\n"),
+            file("src/broken.rs", "fn foo() {}\nfn bar( {}\n"),
+        ]);
+
+        // RCA is fine with the broken file, so it's still counted by the `rca` metrics
+        assert!(results[1].whole.collectors.is_none());
+        assert!(results[1].whole.rca.is_some());
+
+        let diagnostics = super::Diagnostics::of(&results);
+        assert_eq!(
+            diagnostics.coverage,
+            Coverage {
+                files_seen: 2,
+                files_parsed: 1,
+                files_analyzed: 1,
+                bytes_seen: 47,
+                bytes_skipped: 23,
+                items_recovered: 0,
+                macros_parsed: 0,
+                macros_unparsed: 0,
+                macro_lines_unparsed: 0,
+            }
+        );
+        let stages = diagnostics
+            .skipped
+            .iter()
+            .map(|s| (s.path.as_str(), s.stage))
+            .collect::<Vec<_>>();
+        assert!(
+            stages.contains(&("src/broken.rs", Stage::Syn)),
+            "{:?}",
+            stages
+        );
+        let syn = diagnostics
+            .skipped
+            .iter()
+            .find(|s| s.stage == Stage::Syn)
+            .unwrap();
+        assert_eq!(syn.position, Some(Position { line: 2, column: 6 }));
+    }
+
+    #[test]
+    fn recovered_items() {
+        let file = FileText {
+            path: "src/lib.rs".into(),
+            content: r#"
+use a::{b, c};
+fn bad() { let x = 1 +; }
+static S: u8 = 1;
+fn good() { if true {} }
+"#
+            .to_string(),
+        };
+
+        let parsed = file.parse().unwrap();
+        assert_eq!(parsed.recovered_items, 1);
+        let verbatim = parsed
+            .ast
+            .content
+            .items
+            .iter()
+            .map(|item| matches!(item, syn::Item::Verbatim(_)))
+            .collect::<Vec<_>>();
+        assert_eq!(verbatim, [false, true, false, false]);
+
+        let analyzer = Analyzer::new(&CollectConfig::default());
+        let diagnostics = super::Diagnostics::of(&analyzer.analyze_files(vec![file]));
+        assert_eq!(diagnostics.coverage.files_analyzed, 1);
+        assert_eq!(diagnostics.coverage.items_recovered, 1);
+    }
+
+    #[test]
+    fn newer_syntax() {
+        let file = FileText {
+            path: "src/lib.rs".into(),
+            content: r#"
+unsafe extern "C" {
+    pub safe fn abs(i: i32) -> i32;
+}
+fn name() -> &'static CStr {
+    c"name"
+}
+fn counter<'r#a>(start: &'r#a u32) -> impl Iterator<Item = u32> + use<'r#a> {
+    gen move { yield *start; }
+}
+async fn call<F: async Fn()>(f: F) {
+    let g = async move || f().await;
+    g().await
+}
+"#
+            .to_string(),
+        };
+
+        let parsed = file.parse().unwrap();
+        assert_eq!(parsed.recovered_items, 0);
+        assert_eq!(parsed.ast.content.items.len(), 4);
+    }
+}
@@ -0,0 +1,332 @@
+//! Access to local git repositories, through the `git` command line tool
+
+use anyhow::{bail, Context, Result};
+use relative_path::RelativePathBuf;
+use std::collections::HashSet;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use tracing::{error, instrument};
+
+use super::RepoMetadata;
+use crate::collector::config::FileFilter;
+use crate::collector::diagnostics::{SkippedFile, Stage};
+use crate::collector::File;
+
+pub struct LocalRepo {
+    path: PathBuf,
+}
+
+#[derive(Clone)]
+pub struct TreeEntry {
+    /// hash of the blob
+    pub object: String,
+    pub path: String,
+}
+
+impl LocalRepo {
+    pub fn open(path: &Path) -> Result<Self> {
+        let repo = Self {
+            path: path.to_path_buf(),
+        };
+        repo.git(&["rev-parse", "--git-dir"])
+            .with_context(|| format!("{} is not a git repository", path.display()))?;
+        Ok(repo)
+    }
+
+    fn command(&self) -> Command {
+        let mut command = Command::new("git");
+        command.arg("-C").arg(&self.path);
+        command
+    }
+
+    fn git(&self, args: &[&str]) -> Result<Vec<u8>> {
+        let output = self.command().args(args).output().context("Running git")?;
+        if !output.status.success() {
+            bail!(
+                "`git {}` failed: {}",
+                args.join(" "),
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+        Ok(output.stdout)
+    }
+
+    /// Resolves a revision (branch, tag, `HEAD~3`, ...) to a commit hash
+    pub fn resolve_commit(&self, rev: &str) -> Result<String> {
+        let output = self
+            .git(&[
+                "rev-parse",
+                "--verify",
+                // don't let it be taken for an option
+                "--end-of-options",
+                &format!("{}^{{commit}}", rev),
+            ])
+            .with_context(|| format!("Cannot resolve `{}`", rev))?;
+        Ok(String::from_utf8(output)?.trim().to_string())
+    }
+
+    /// Runs a git command which fails when the thing it asks about does not exist
+    fn git_optional(&self, args: &[&str]) -> Result<Option<String>> {
+        let output = self.command().args(args).output().context("Running git")?;
+        if !output.status.success() {
+            return Ok(None);
+        }
+        let output = String::from_utf8(output.stdout)?.trim().to_string();
+        Ok((!output.is_empty()).then_some(output))
+    }
+
+    /// Metadata of the commit `rev` points to
+    ///
+    /// The branch is filled in if `rev` is a branch (or `HEAD` pointing to one)
+    pub fn metadata(&self, rev: &str) -> Result<RepoMetadata> {
+        let commit = self.resolve_commit(rev)?;
+        let branch = self
+            .git_optional(&["rev-parse", "--symbolic-full-name", rev])?
+            .and_then(|r| r.strip_prefix("refs/heads/").map(str::to_string));
+        let url = self
+            .git_optional(&["config", "--get", "remote.origin.url"])?
+            .map(|url| normalize_remote_url(&url))
+            .unwrap_or_else(|| "<LOCAL>".to_string());
+        let timestamp = self.commit_timestamp(&commit)?;
+
+        Ok(RepoMetadata {
+            url,
+            commit,
+            branch,
+            dirty: false,
+            timestamp: Some(timestamp),
+            reference: None,
+        })
+    }
+
+    /// Metadata of the checked out commit, taking the uncommitted changes into account
+    pub fn working_tree_metadata(&self) -> Result<RepoMetadata> {
+        let status = self.git(&["status", "--porcelain"])?;
+        Ok(RepoMetadata {
+            dirty: !status.is_empty(),
+            ..self.metadata("HEAD")?
+        })
+    }
+
+    /// Commit time in RFC 3339 format, in UTC (same as GitHub API reports it)
+    fn commit_timestamp(&self, commit: &str) -> Result<String> {
+        let output = self
+            .command()
+            .env("TZ", "UTC")
+            .args([
+                "log",
+                "-1",
+                "--no-show-signature",
+                "--format=%cd",
+                "--date=format-local:%Y-%m-%dT%H:%M:%SZ",
+                commit,
+            ])
+            .output()
+            .context("Running git")?;
+        if !output.status.success() {
+            bail!(
+                "Cannot get the timestamp of {}: {}",
+                commit,
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+        Ok(String::from_utf8(output.stdout)?.trim().to_string())
+    }
+
+    fn list_tree(&self, commit: &str) -> Result<Vec<TreeEntry>> {
+        let output = self.git(&["ls-tree", "-r", "-z", "--full-tree", commit])?;
+
+        let mut entries = Vec::new();
+        for line in output.split(|&b| b == 0).filter(|l| !l.is_empty()) {
+            let line = String::from_utf8_lossy(line);
+            // <mode> SP <type> SP <object> TAB <path>
+            let Some((info, path)) = line.split_once('\t') else {
+                bail!("Unexpected `git ls-tree` output: {}", line);
+            };
+            let mut info = info.split(' ');
+            let (Some(_mode), Some(type_), Some(object)) = (info.next(), info.next(), info.next())
+            else {
+                bail!("Unexpected `git ls-tree` output: {}", line);
+            };
+            if type_ == "blob" {
+                entries.push(TreeEntry {
+                    object: object.to_string(),
+                    path: path.to_string(),
+                });
+            }
+        }
+        Ok(entries)
+    }
+
+    /// Reads the contents of blobs, in the same order
+    fn read_blobs(&self, objects: Vec<String>) -> Result<Vec<Vec<u8>>> {
+        let mut child = self
+            .command()
+            .args(["cat-file", "--batch"])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .context("Running git")?;
+
+        let mut stdin = child.stdin.take().unwrap();
+        let count = objects.len();
+        // feed the requests from another thread, so that neither of the pipes can fill up
+        let writer = std::thread::spawn(move || {
+            for object in objects {
+                writeln!(stdin, "{}", object)?;
+            }
+            Ok::<_, std::io::Error>(())
+        });
+
+        let mut stdout = BufReader::new(child.stdout.take().unwrap());
+        let mut blobs = Vec::with_capacity(count);
+        let mut header = String::new();
+        for _ in 0..count {
+            header.clear();
+            stdout.read_line(&mut header)?;
+            // <object> SP <type> SP <size> LF <contents> LF
+            let size = match header.trim_end().split(' ').collect::<Vec<_>>()[..] {
+                [_, "blob", size] => size.parse::<usize>()?,
+                _ => bail!("Unexpected `git cat-file` output: {}", header.trim_end()),
+            };
+            let mut content = vec![0; size + 1];
+            stdout.read_exact(&mut content)?;
+            content.pop();
+            blobs.push(content);
+        }
+
+        writer
+            .join()
+            .expect("BUG: git stdin writer panicked")
+            .context("Writing to git")?;
+        child.wait()?;
+
+        Ok(blobs)
+    }
+
+    /// Lists the files wanted by the filter as they are at the given commit
+    pub fn list_files(&self, commit: &str, filter: &FileFilter) -> Result<Vec<TreeEntry>> {
+        Ok(self
+            .list_tree(commit)?
+            .into_iter()
+            .filter(|e| filter.wants(&e.path))
+            .collect())
+    }
+
+    /// Reads the files, in the same order (the files that are not UTF-8 are reported as skipped)
+    pub fn read_files(
+        &self,
+        entries: &[TreeEntry],
+    ) -> Result<Vec<Result<File<String>, SkippedFile>>> {
+        let blobs = self.read_blobs(entries.iter().map(|e| e.object.clone()).collect())?;
+
+        Ok(entries
+            .iter()
+            .zip(blobs)
+            .map(|(entry, content)| match String::from_utf8(content) {
+                Ok(content) => Ok(File {
+                    path: RelativePathBuf::from(entry.path.clone()),
+                    content,
+                }),
+                Err(_) => {
+                    error!("File is not UTF-8: `{}`", entry.path);
+                    Err(SkippedFile::new(
+                        entry.path.as_str(),
+                        Stage::Read,
+                        "File is not UTF-8",
+                    ))
+                }
+            })
+            .collect())
+    }
+
+    /// Reads the files wanted by the filter as they are at the given commit
+    #[instrument(skip(self, filter))]
+    pub fn read_files_at(
+        &self,
+        commit: &str,
+        filter: &FileFilter,
+    ) -> Result<Vec<Result<File<String>, SkippedFile>>> {
+        let entries = self.list_files(commit, filter)?;
+        self.read_files(&entries)
+            .with_context(|| format!("Reading files at {}", commit))
+    }
+
+    /// Commits in the first-parent history of `rev`, newest first
+    pub fn first_parent_history(&self, rev: &str) -> Result<Vec<String>> {
+        let commit = self.resolve_commit(rev)?;
+        let output = self.git(&["rev-list", "--first-parent", &commit])?;
+        Ok(String::from_utf8(output)?
+            .lines()
+            .map(str::to_string)
+            .collect())
+    }
+
+    /// Commits pointed to by tags
+    pub fn tagged_commits(&self) -> Result<HashSet<String>> {
+        // `*objectname` is the commit an annotated tag points to, `objectname` is the commit for lightweight tags
+        let output = self.git(&[
+            "for-each-ref",
+            "--format=%(objectname) %(*objectname)",
+            "refs/tags",
+        ])?;
+        Ok(String::from_utf8(output)?
+            .lines()
+            .filter_map(|line| line.split(' ').rfind(|s| !s.is_empty()))
+            .map(str::to_string)
+            .collect())
+    }
+}
+
+/// Brings the different ways to spell a GitHub remote to the form used for GitHub repos
+fn normalize_remote_url(url: &str) -> String {
+    let repo = [
+        "https://github.com/",
+        "ssh://git@github.com/",
+        "git@github.com:",
+    ]
+    .iter()
+    .find_map(|prefix| url.strip_prefix(prefix));
+    match repo {
+        Some(repo) => {
+            let repo = repo.trim_end_matches('/');
+            let repo = repo.strip_suffix(".git").unwrap_or(repo);
+            format!("git@github.com:{}.git", repo)
+        }
+        None => url.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{normalize_remote_url, LocalRepo};
+    use std::path::Path;
+
+    #[test]
+    fn revisions_are_not_options() {
+        let repo = LocalRepo::open(Path::new(env!("CARGO_MANIFEST_DIR"))).unwrap();
+        assert_eq!(repo.resolve_commit("HEAD").unwrap().len(), 40);
+        assert!(repo.resolve_commit("--all").is_err());
+    }
+
+    #[test]
+    fn remote_urls() {
+        for url in [
+            "https://github.com/DCNick3/ifcount",
+            "https://github.com/DCNick3/ifcount.git",
+            "https://github.com/DCNick3/ifcount/",
+            "ssh://git@github.com/DCNick3/ifcount.git",
+            "git@github.com:DCNick3/ifcount.git",
+        ] {
+            assert_eq!(
+                normalize_remote_url(url),
+                "git@github.com:DCNick3/ifcount.git"
+            );
+        }
+        assert_eq!(
+            normalize_remote_url("https://gitlab.com/foo/bar.git"),
+            "https://gitlab.com/foo/bar.git"
+        );
+    }
+}
@@ -1,24 +1,110 @@
+mod local;
 mod octocrab_ext;
 
-use anyhow::{Context, Result};
+use anyhow::{bail, Context, Result};
 use futures::{pin_mut, stream, StreamExt};
 use indicatif::ProgressStyle;
+use octocrab::models::Repository;
 use relative_path::RelativePathBuf;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
-use std::collections::BTreeMap;
-use tracing::{instrument, Span};
+use std::collections::{BTreeMap, HashSet, VecDeque};
+use std::fmt;
+use std::io::Read;
+use std::str::FromStr;
+use tokio::sync::mpsc::Sender;
+use tracing::{error, instrument, warn, Span};
 use tracing_indicatif::span_ext::IndicatifSpanExt;
 
-use crate::collector::git::octocrab_ext::TreeItemType;
+use crate::collector::config::FileFilter;
+use crate::collector::diagnostics::{SkippedFile, Stage};
+use crate::collector::git::octocrab_ext::{TreeItem, TreeItemType};
+use crate::collector::metrics::schema::{MetricSchema, Scope, Unit};
 use crate::collector::File;
-pub use octocrab_ext::LimitedCrab;
+pub use local::{LocalRepo, TreeEntry};
+pub use octocrab_ext::{GithubError, GithubUrls, LimitedCrab};
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct RepoMetadata {
     // name: String,
     pub url: String,
     pub commit: String,
+    /// `None` if the commit wasn't taken from a branch (e.g. detached HEAD)
+    #[serde(default)]
+    pub branch: Option<String>,
+    /// Whether the working tree had uncommitted changes (always `false` for GitHub repos)
+    #[serde(default)]
+    pub dirty: bool,
+    /// Commit time in RFC 3339 format, in UTC
+    #[serde(default)]
+    pub timestamp: Option<String>,
+    /// The ref (branch, tag or commit) requested with `owner/repo@ref`, `None` for the default branch and local repos
+    #[serde(default, rename = "ref")]
+    pub reference: Option<String>,
+}
+
+impl RepoMetadata {
+    /// Metadata of a directory which is not a git repository
+    pub fn unknown() -> Self {
+        Self {
+            url: "<LOCAL>".to_string(),
+            commit: "<LOCAL>".to_string(),
+            branch: None,
+            dirty: false,
+            timestamp: None,
+            reference: None,
+        }
+    }
+}
+
+/// A GitHub repository to collect: `owner/repo`, or `owner/repo@ref` for a branch, a tag or a commit
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GithubRepo {
+    /// `owner/repo`
+    pub name: String,
+    /// `None` for the head of the default branch
+    pub reference: Option<String>,
+}
+
+impl FromStr for GithubRepo {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (name, reference) = match s.split_once('@') {
+            Some((name, reference)) => (name, Some(reference)),
+            None => (s, None),
+        };
+        let is_valid_part = |part: &str| {
+            !part.is_empty()
+                && part
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+        };
+        match name.split_once('/') {
+            Some((owner, repo)) if is_valid_part(owner) && is_valid_part(repo) => {}
+            _ => bail!(
+                "Invalid repository `{}`, expected `owner/repo` or `owner/repo@ref`",
+                s
+            ),
+        }
+        if reference.is_some_and(|r| r.is_empty() || r.contains(char::is_whitespace)) {
+            bail!("Invalid ref in `{}`", s);
+        }
+
+        Ok(Self {
+            name: name.to_string(),
+            reference: reference.map(str::to_string),
+        })
+    }
+}
+
+impl fmt::Display for GithubRepo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.reference {
+            Some(reference) => write!(f, "{}@{}", self.name, reference),
+            None => write!(f, "{}", self.name),
+        }
+    }
 }
 
 fn progressbar_style() -> ProgressStyle {
@@ -28,35 +114,214 @@ fn progressbar_style() -> ProgressStyle {
         .progress_chars("#>-")
 }
 
-#[instrument(skip(crab))]
+/// How the files of a GitHub repository are downloaded, `fetch` in `ifcount.toml`
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FetchStrategy {
+    /// A single tarball of the commit, falling back to `Blobs` if it can't be downloaded or read
+    ///
+    /// The files left out of the tarball with `export-ignore` are still downloaded one by one.
+    #[default]
+    Archive,
+    /// Every file separately from `raw.githubusercontent.com`, only downloading the files that changed since the
+    /// previous runs
+    Blobs,
+}
+
+/// A downloaded file, or the reason it can't be analyzed
+pub type FetchedFile = Result<File<String>, SkippedFile>;
+
+/// Downloads the files of a GitHub repository, sending each of them as soon as it's there
+///
+/// Returns whether GitHub listed all the files, the ones in the directories too big to list are missing otherwise.
 pub async fn fetch_repo(
     crab: &LimitedCrab,
     repo_name: &str,
     commit: &str,
-) -> Result<Vec<File<String>>> {
-    let tree = crab
-        .get_repo_tree(repo_name, &commit)
-        .await
-        .context("Cannot get repo tree")?;
-
-    let wanted_files = tree
-        .tree
+    filter: &FileFilter,
+    strategy: FetchStrategy,
+    files: &Sender<FetchedFile>,
+) -> Result<bool> {
+    // the files already sent from the archive, all of them or the ones before it failed
+    let mut sent = HashSet::new();
+    if strategy == FetchStrategy::Archive {
+        let fetched;
+        (sent, fetched) = fetch_archive(crab, repo_name, commit, filter, files).await;
+        if let Err(e) = fetched {
+            warn!(
+                "Cannot fetch the archive of {}, fetching the files one by one: {:#}",
+                repo_name, e
+            );
+        }
+    }
+    // even after the whole archive, as it leaves out the files marked `export-ignore` in `.gitattributes`
+    fetch_blobs(crab, repo_name, commit, filter, files, &sent).await
+}
+
+/// Sends the files of the archive, returning the paths of the ones sent even when it fails halfway
+#[instrument(skip(crab, filter, files))]
+async fn fetch_archive(
+    crab: &LimitedCrab,
+    repo_name: &str,
+    commit: &str,
+    filter: &FileFilter,
+    files: &Sender<FetchedFile>,
+) -> (HashSet<String>, Result<()>) {
+    let mut archive = match crab.get_archive(repo_name, commit).await {
+        Ok(archive) => archive,
+        Err(e) => return (HashSet::new(), Err(e.into())),
+    };
+    let filter = filter.clone();
+    let files = files.clone();
+    // decompressed as it's downloaded
+    let extraction = tokio::task::spawn_blocking(move || {
+        let mut sent = HashSet::new();
+        let extracted = extract_archive(&mut archive, &filter, |file| {
+            let path = match &file {
+                Ok(file) => file.path.to_string(),
+                Err(skipped) => skipped.path.clone(),
+            };
+            sent.insert(path);
+            files
+                .blocking_send(file)
+                .context("The analysis of the files stopped")
+        });
+        (sent, extracted.and_then(|()| archive.finish()))
+    });
+    match extraction.await {
+        Ok(extracted) => extracted,
+        // the files sent before the panic are unknown, so they can't be left out of the ones fetched after it
+        Err(e) => std::panic::resume_unwind(e.into_panic()),
+    }
+}
+
+/// Reads the wanted files out of a `.tar.gz` while decompressing it, skipping the rest
+///
+/// The files are passed to `found` one by one, as they are decompressed, the ones that are not UTF-8 as skipped.
+fn extract_archive(
+    archive: impl Read,
+    filter: &FileFilter,
+    mut found: impl FnMut(FetchedFile) -> Result<()>,
+) -> Result<()> {
+    let mut archive = tar::Archive::new(flate2::read::GzDecoder::new(archive));
+    for entry in archive.entries().context("Reading the archive")? {
+        let mut entry = entry.context("Reading the archive")?;
+        // also skips the directories, the symlinks and the `pax_global_header` with the commit
+        if entry.header().entry_type() != tar::EntryType::Regular {
+            continue;
+        }
+        let path = entry.path().context("Reading the archive")?;
+        // everything is in a `<owner>-<repo>-<commit>/` directory
+        let Some((_, path)) = path.to_str().and_then(|p| p.split_once('/')) else {
+            continue;
+        };
+        if !filter.wants(path) {
+            continue;
+        }
+
+        let path = RelativePathBuf::from(path);
+        let mut content = Vec::new();
+        entry
+            .read_to_end(&mut content)
+            .with_context(|| format!("Reading {} from the archive", path))?;
+        match String::from_utf8(content) {
+            Ok(content) => found(Ok(File { path, content }))?,
+            Err(_) => {
+                error!("File is not UTF-8: `{}`", path);
+                found(Err(SkippedFile::new(
+                    path.as_str(),
+                    Stage::Read,
+                    "File is not UTF-8",
+                )))?
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Lists the files of the commit, with the second element telling whether the listing is complete
+///
+/// GitHub truncates the recursive listing of big trees, so the truncated ones are listed one level at a time, with a
+/// recursive listing for each of their subtrees.
+async fn list_files(
+    crab: &LimitedCrab,
+    repo_name: &str,
+    commit: &str,
+) -> Result<(Vec<TreeItem>, bool)> {
+    let mut files = Vec::new();
+    let mut complete = true;
+    // the trees to list, with the paths they are at
+    let mut pending = VecDeque::from([(commit.to_string(), String::new())]);
+    while let Some((sha, prefix)) = pending.pop_front() {
+        let tree = crab
+            .get_repo_tree(repo_name, &sha, true)
+            .await
+            .context("Cannot get repo tree")?;
+        let (tree, recursive) = if tree.truncated {
+            warn!(
+                "The listing of `{}/` is truncated, listing its subdirectories separately",
+                prefix
+            );
+            let tree = crab
+                .get_repo_tree(repo_name, &sha, false)
+                .await
+                .context("Cannot get repo tree")?;
+            if tree.truncated {
+                warn!(
+                    "`{}/` has too many files for GitHub to list, some of them are missing",
+                    prefix
+                );
+                complete = false;
+            }
+            (tree, false)
+        } else {
+            (tree, true)
+        };
+
+        for mut item in tree.tree {
+            if !prefix.is_empty() {
+                item.path = format!("{}/{}", prefix, item.path);
+            }
+            match item.type_ {
+                TreeItemType::Tree if recursive => {}
+                TreeItemType::Tree => pending.push_back((item.sha, item.path)),
+                _ => files.push(item),
+            }
+        }
+    }
+    Ok((files, complete))
+}
+
+/// Downloads the files one by one, except for the ones in `sent`
+#[instrument(skip(crab, filter, files, sent))]
+async fn fetch_blobs(
+    crab: &LimitedCrab,
+    repo_name: &str,
+    commit: &str,
+    filter: &FileFilter,
+    files: &Sender<FetchedFile>,
+    sent: &HashSet<String>,
+) -> Result<bool> {
+    let (listed_files, complete) = list_files(crab, repo_name, commit).await?;
+
+    let wanted_files = listed_files
         .into_iter()
         .filter(|i| i.type_ == TreeItemType::Blob)
-        .filter(|i| i.path.ends_with(".rs"))
-        // we don't want vendored dependencies
-        .filter(|i| !i.path.starts_with("vendor/"))
+        .filter(|i| filter.wants(&i.path))
+        .filter(|i| !sent.contains(&i.path))
         .collect::<Vec<_>>();
 
-    let total_size = wanted_files.iter().map(|i| i.size.unwrap()).sum::<u64>();
+    // only for the progress bar, so a size GitHub leaves out is not worth failing over
+    let total_size = wanted_files
+        .iter()
+        .map(|i| i.size.unwrap_or(0))
+        .sum::<u64>();
 
     let cur_span = Span::current();
 
     cur_span.pb_set_style(&progressbar_style());
     cur_span.pb_set_length(total_size);
 
-    let mut downloaded_files = Vec::with_capacity(wanted_files.len());
-
     let futures_stream = stream::iter(wanted_files.iter().map(|item| {
         let commit_hash = commit.to_owned();
 
@@ -71,7 +336,7 @@ pub async fn fetch_repo(
                 content,
             };
 
-            Ok::<_, anyhow::Error>((item.size.unwrap(), file))
+            Ok::<_, anyhow::Error>((item.size.unwrap_or(0), file))
         }
     }))
     .buffer_unordered(16);
@@ -80,34 +345,137 @@ pub async fn fetch_repo(
     while let Some(result) = futures_stream.next().await {
         let (size, file) = result?;
         cur_span.pb_inc(size);
-        downloaded_files.push(file);
+        files
+            .send(Ok(file))
+            .await
+            .context("The analysis of the files stopped")?;
     }
 
-    Ok(downloaded_files)
+    Ok(complete)
 }
 
-#[instrument(skip(crab))]
+/// The `repo.*` metrics, only collected for GitHub repositories
+pub const REPO_SCHEMA: &[MetricSchema] = &[
+    MetricSchema::new(
+        "commit_count",
+        Scope::Repo,
+        Unit::Count,
+        "Number of commits in the history of the collected commit",
+    ),
+    MetricSchema::new("forks", Scope::Repo, Unit::Count, "Number of forks"),
+    MetricSchema::new(
+        "open_issues",
+        Scope::Repo,
+        Unit::Count,
+        "Number of open issues and pull requests",
+    ),
+    MetricSchema::new(
+        "size",
+        Scope::Repo,
+        Unit::Kilobytes,
+        "Size of the repository as reported by GitHub",
+    ),
+    MetricSchema::new("stars", Scope::Repo, Unit::Count, "Number of stars"),
+    MetricSchema::new("watchers", Scope::Repo, Unit::Count, "Number of watchers"),
+];
+
+#[instrument(skip(crab, info))]
 pub async fn get_repo_metrics(
     crab: &LimitedCrab,
     repo_name: &str,
+    commit: &str,
+    info: &Repository,
 ) -> Result<BTreeMap<String, serde_json::Value>> {
-    let info = crab
-        .get_repo_info(repo_name)
-        .await
-        .context("Cannot get repo info")?;
-
-    let commit_count = crab.get_commit_count(repo_name).await?;
+    let commit_count = crab.get_commit_count(repo_name, commit).await?;
 
+    // the counts GitHub leaves out are `null`, read as missing values
     let repo_metrics = json!(
         {
-            "stars": info.stargazers_count.unwrap(),
-            "watchers": info.watchers_count.unwrap(),
-            "forks": info.forks_count.unwrap(),
-            "open_issues": info.open_issues_count.unwrap(),
-            "size": info.size.unwrap(),
+            "stars": info.stargazers_count,
+            "watchers": info.watchers_count,
+            "forks": info.forks_count,
+            "open_issues": info.open_issues_count,
+            "size": info.size,
             "commit_count": commit_count,
         }
     );
 
     Ok(BTreeMap::from([("repo".to_string(), repo_metrics)]))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{extract_archive, GithubRepo};
+    use crate::collector::CollectConfig;
+    use crate::collector::Stage;
+    use flate2::write::GzEncoder;
+
+    #[test]
+    fn parse_github_repo() {
+        let repo: GithubRepo = "DCNick3/shin".parse().unwrap();
+        assert_eq!(repo.name, "DCNick3/shin");
+        assert_eq!(repo.reference, None);
+
+        let repo: GithubRepo = "rust-lang/rust-analyzer@release/2023-09-04"
+            .parse()
+            .unwrap();
+        assert_eq!(repo.name, "rust-lang/rust-analyzer");
+        assert_eq!(repo.reference.as_deref(), Some("release/2023-09-04"));
+        assert_eq!(
+            repo.to_string(),
+            "rust-lang/rust-analyzer@release/2023-09-04"
+        );
+
+        for invalid in [
+            "shin",
+            "DCNick3/",
+            "a/b/c",
+            "DCNick3/shin@",
+            "DCNick3/shin@a b",
+        ] {
+            assert!(invalid.parse::<GithubRepo>().is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn archive() {
+        let mut builder = tar::Builder::new(GzEncoder::new(Vec::new(), Default::default()));
+        let mut add = |path: &str, content: &[u8]| {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(content.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            let path = format!("DCNick3-shin-796ae0c/{}", path);
+            builder.append_data(&mut header, path, content).unwrap();
+        };
+        add("Cargo.toml", b"[package]");
+        add("README.md", b"# shin");
+        add("src/main.rs", b"fn main() {}");
+        // the files after it are still read
+        add("src/latin1.rs", b"// caf\xe9");
+        add("src/util.rs", b"fn util() {}");
+        add("vendor/foo/src/lib.rs", b"fn foo() {}");
+        let archive = builder.into_inner().unwrap().finish().unwrap();
+
+        let filter = CollectConfig::default().file_filter().unwrap();
+        let mut files = Vec::new();
+        let mut skipped = Vec::new();
+        extract_archive(&archive[..], &filter, |f| {
+            match f {
+                Ok(f) => files.push((f.path.to_string(), f.content)),
+                Err(s) => skipped.push((s.path, s.stage)),
+            }
+            Ok(())
+        })
+        .unwrap();
+        assert_eq!(
+            files,
+            [
+                ("Cargo.toml".to_string(), "[package]".to_string()),
+                ("src/main.rs".to_string(), "fn main() {}".to_string()),
+                ("src/util.rs".to_string(), "fn util() {}".to_string()),
+            ]
+        );
+        assert_eq!(skipped, [("src/latin1.rs".to_string(), Stage::Read)]);
+    }
+}
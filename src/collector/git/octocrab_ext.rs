@@ -1,212 +1,463 @@
-use anyhow::{Context, Result};
-use async_trait::async_trait;
-use governor::clock::DefaultClock;
-use governor::state::{InMemoryState, NotKeyed};
-use governor::{Quota, RateLimiter};
-use http::Response;
-use hyper::Body;
+use anyhow::Context;
+use futures::StreamExt;
+use http::{HeaderMap, Response, StatusCode};
+use hyper::body::{Body, Bytes};
 use octocrab::models::commits::Commit;
-use octocrab::models::{Rate, Repository};
-use octocrab::{FromResponse, Octocrab, Page};
+use octocrab::models::Repository;
+use octocrab::service::middleware::retry::RetryConfig;
+use octocrab::Octocrab;
 use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
-use snafu::GenerateImplicitData;
-use std::num::NonZeroU32;
+use std::fmt;
+use std::future::Future;
+use std::io::{self, Read, Write};
 use std::path::PathBuf;
-use tracing::{instrument, Instrument};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio_util::io::{StreamReader, SyncIoBridge};
+use tracing::{info, instrument, warn, Instrument};
 use url::Url;
 
-type DefaultRateLimiter = RateLimiter<NotKeyed, InMemoryState, DefaultClock>;
+use crate::collector::cache::Cache;
 
-async fn make_rate_limiter(rate: Rate) -> Result<DefaultRateLimiter> {
-    // assuming all rates are reset every minute
-    let quota = Quota::per_minute(NonZeroU32::new(rate.limit as u32).unwrap());
-    let rate_limiter = RateLimiter::direct(quota);
-    // preload
-    if let Some(used) = NonZeroU32::new(rate.remaining as u32) {
-        rate_limiter.until_n_ready(used).await.unwrap();
-    }
-    Ok(rate_limiter)
+/// How many times a request is retried after a failure that is likely to go away
+const MAX_RETRIES: u32 = 5;
+/// The delay before the first retry of a failed request, doubled for every next one
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+
+/// The errors of the requests to GitHub
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum GithubError {
+    /// The repository name is not `owner/repo`
+    InvalidRepoName(String),
+    /// Nothing at the URL, or it's private
+    NotFound(String),
+    /// The repository has no commits
+    EmptyRepository,
+    /// GitHub responded with an error that is not worth retrying, or kept responding with it after all the retries
+    Status {
+        status: StatusCode,
+        message: String,
+    },
+    /// The request could not be sent or the response could not be read, even after all the retries
+    Request(octocrab::Error),
+    /// The response is not what was expected
+    InvalidResponse(String),
+    Cache(anyhow::Error),
 }
 
-struct Cache {
-    directory: PathBuf,
+impl fmt::Display for GithubError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GithubError::InvalidRepoName(name) => {
+                write!(
+                    f,
+                    "Invalid repository name `{}`, expected `owner/repo`",
+                    name
+                )
+            }
+            GithubError::NotFound(url) => write!(f, "`{}` is not found (or is private)", url),
+            GithubError::EmptyRepository => write!(f, "The repository has no commits"),
+            GithubError::Status { status, message } => {
+                write!(f, "GitHub responded with {}: {}", status, message)
+            }
+            GithubError::Request(e) => write!(f, "The request to GitHub failed: {}", root_cause(e)),
+            GithubError::InvalidResponse(message) => {
+                write!(f, "Unexpected response from GitHub: {}", message)
+            }
+            GithubError::Cache(_) => write!(f, "Cannot access the cache"),
+        }
+    }
 }
 
-impl Cache {
-    pub fn new(directory: PathBuf) -> Self {
-        Self { directory }
+impl std::error::Error for GithubError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        // not the octocrab errors, as they include the backtraces in their messages
+        match self {
+            GithubError::Cache(e) => Some(e.as_ref()),
+            _ => None,
+        }
     }
+}
 
-    pub async fn get<T: DeserializeOwned>(&self, key: &str) -> Result<Option<T>> {
-        let Some(meta) = cacache::index::find_async(&self.directory, key)
-            .await
-            .context("Finding cache entry")?
-        else {
-            return Ok(None);
+impl GithubError {
+    fn from_response(url: &str, response: &Response<Bytes>) -> Self {
+        #[derive(Deserialize)]
+        struct ErrorBody {
+            message: String,
+        }
+
+        let message = match serde_json::from_slice::<ErrorBody>(response.body()) {
+            Ok(body) => body.message,
+            Err(_) => String::from_utf8_lossy(response.body()).into_owned(),
         };
-        let content = cacache::read_hash(&self.directory, &meta.integrity)
-            .await
-            .context("Reading cache entry")?;
-        let content = zstd::decode_all(&content[..]).context("Decompressing cache entry")?;
-        let content = bincode::deserialize(&content).context("Deserializing cache entry")?;
-        Ok(Some(content))
+        match response.status() {
+            StatusCode::NOT_FOUND => GithubError::NotFound(url.to_string()),
+            // the commits and trees of an empty repository are a conflict
+            StatusCode::CONFLICT if message.contains("empty") => GithubError::EmptyRepository,
+            status => GithubError::Status { status, message },
+        }
     }
+}
 
-    pub async fn set<T: Serialize>(&self, key: &str, value: &T) -> Result<()> {
-        let content = bincode::serialize(value).context("Serializing cache entry")?;
-        let content = zstd::encode_all(&content[..], 5).context("Compressing cache entry")?;
-        cacache::write(&self.directory, key, &content)
-            .await
-            .context("Writing cache entry")?;
-        Ok(())
+type Result<T, E = GithubError> = std::result::Result<T, E>;
+
+/// The message of the innermost error, like "dns error" for a failed connection
+fn root_cause(error: &dyn std::error::Error) -> String {
+    match error.source() {
+        Some(source) => root_cause(source),
+        None => error.to_string(),
     }
 }
 
-struct RawBody(String);
+fn header<T: std::str::FromStr>(headers: &HeaderMap, name: &str) -> Option<T> {
+    headers.get(name)?.to_str().ok()?.parse().ok()
+}
 
-#[async_trait]
-impl FromResponse for RawBody {
-    async fn from_response(response: Response<Body>) -> octocrab::Result<Self> {
-        let (_, body) = response.into_parts();
-        let body = hyper::body::to_bytes(body)
-            .await
-            .map_err(|source| octocrab::Error::Hyper {
-                source,
-                backtrace: snafu::Backtrace::generate(),
-            })?;
+/// The time left until a unix timestamp, zero if it's in the past
+fn until(timestamp: u64) -> Duration {
+    (UNIX_EPOCH + Duration::from_secs(timestamp))
+        .duration_since(SystemTime::now())
+        .unwrap_or_default()
+}
 
-        let body =
-            String::from_utf8(body.to_vec()).map_err(|source| octocrab::Error::InvalidUtf8 {
-                source,
-                backtrace: snafu::Backtrace::generate(),
-            })?;
+/// `INITIAL_BACKOFF`, doubled for every previous attempt
+fn backoff(attempt: u32) -> Duration {
+    INITIAL_BACKOFF * 2u32.pow(attempt)
+}
 
-        Ok(Self(body))
+/// How long to wait before retrying a failed request, `None` if retrying won't help
+fn retry_delay(response: &Response<Bytes>, attempt: u32) -> Option<Duration> {
+    let headers = response.headers();
+    let status = response.status();
+    if status == StatusCode::FORBIDDEN || status == StatusCode::TOO_MANY_REQUESTS {
+        // the secondary rate limits tell how long to wait
+        if let Some(seconds) = header::<u64>(headers, "retry-after") {
+            return Some(Duration::from_secs(seconds));
+        }
+        // the primary one is exhausted until the reset
+        if header::<u32>(headers, "x-ratelimit-remaining") == Some(0) {
+            return header(headers, "x-ratelimit-reset").map(until);
+        }
+    }
+    (status.is_server_error() || status == StatusCode::TOO_MANY_REQUESTS).then(|| backoff(attempt))
+}
+
+/// The page number of the `rel="last"` link of a paginated response
+fn last_page(headers: &HeaderMap) -> Option<u32> {
+    let links = headers.get("link")?.to_str().ok()?;
+    let last = links.split(',').find(|l| l.contains("rel=\"last\""))?;
+    let url = last.split_once('<')?.1.split_once('>')?.0;
+    let url = Url::parse(url).ok()?;
+    let (_, page) = url.query_pairs().find(|(k, _)| k == "page")?;
+    page.parse().ok()
+}
+
+/// A rate limit, as the `x-ratelimit-*` headers of the last response report it
+///
+/// Before the first response (and for the hosts that don't report the limits) nothing is waited for.
+#[derive(Default)]
+struct RateLimit(Mutex<RateLimitState>);
+
+#[derive(Default)]
+struct RateLimitState {
+    remaining: Option<u32>,
+    /// unix timestamp
+    reset: Option<u64>,
+}
+
+impl RateLimit {
+    /// Waits until there are requests left
+    async fn wait(&self) {
+        let delay = {
+            let mut state = self.0.lock().unwrap();
+            match state.remaining {
+                // the requests in flight will tell the new limit once it's reset
+                Some(0) => state.reset.map(until),
+                Some(remaining) => {
+                    state.remaining = Some(remaining - 1);
+                    None
+                }
+                None => None,
+            }
+        };
+        if let Some(delay) = delay.filter(|d| !d.is_zero()) {
+            info!(
+                "Rate limit exceeded, waiting {}s for the reset",
+                delay.as_secs()
+            );
+            tokio::time::sleep(delay).await;
+        }
+    }
+
+    fn update(&self, headers: &HeaderMap) {
+        let (Some(remaining), Some(reset)) = (
+            header(headers, "x-ratelimit-remaining"),
+            header(headers, "x-ratelimit-reset"),
+        ) else {
+            return;
+        };
+        let mut state = self.0.lock().unwrap();
+        state.remaining = Some(remaining);
+        state.reset = Some(reset);
+    }
+}
+
+/// Where GitHub is, to use GitHub Enterprise or a local stand-in instead
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GithubUrls {
+    /// The REST API, `https://<host>/api/v3` for GitHub Enterprise
+    pub api: String,
+    /// The contents of the files, as `<raw>/<owner>/<repo>/<commit>/<path>`
+    pub raw: String,
+}
+
+impl GithubUrls {
+    pub const DEFAULT_API: &'static str = "https://api.github.com";
+    pub const DEFAULT_RAW: &'static str = "https://raw.githubusercontent.com";
+}
+
+impl Default for GithubUrls {
+    fn default() -> Self {
+        Self {
+            api: Self::DEFAULT_API.to_string(),
+            raw: Self::DEFAULT_RAW.to_string(),
+        }
     }
 }
 
 pub struct LimitedCrab {
     crab: Octocrab,
     cache: Cache,
-    api_rate_limiter: DefaultRateLimiter,
-    user_content_rate_limiter: DefaultRateLimiter,
+    /// without the trailing `/`
+    urls: GithubUrls,
+    api_rate_limit: RateLimit,
+    /// `raw.githubusercontent.com` doesn't report its limits, but does say when to retry
+    user_content_rate_limit: RateLimit,
 }
 
 impl LimitedCrab {
-    pub async fn new(token: Option<String>, cache_dir: PathBuf) -> Result<Self> {
+    pub fn new(token: Option<String>, cache_dir: PathBuf, urls: GithubUrls) -> Result<Self> {
         let mut crab_builder = Octocrab::builder();
         if let Some(token) = token {
             crab_builder = crab_builder.personal_token(token);
         }
-        let crab = crab_builder.build().context("Building octocrab")?;
-
-        let rate_limit = crab
-            .ratelimit()
-            .get()
-            .await
-            .context("Getting the rate limit")?;
-
-        let api_rate_limiter = make_rate_limiter(rate_limit.resources.core).await?;
-        let user_content_rate_limiter =
-            RateLimiter::direct(Quota::per_hour(NonZeroU32::new(5000).unwrap()));
-
-        let cache = Cache::new(cache_dir.clone());
+        // retried with a backoff by `send` instead
+        crab_builder.add_retry_config(RetryConfig::None);
+        let crab = crab_builder.build().map_err(GithubError::Request)?;
+
+        // the URLs are made absolute here, as octocrab would drop the path of the base URL (like `/api/v3`)
+        let urls = GithubUrls {
+            api: urls.api.trim_end_matches('/').to_string(),
+            raw: urls.raw.trim_end_matches('/').to_string(),
+        };
 
         Ok(Self {
             crab,
-            cache,
-            api_rate_limiter,
-            user_content_rate_limiter,
+            cache: Cache::new(cache_dir),
+            urls,
+            api_rate_limit: RateLimit::default(),
+            user_content_rate_limit: RateLimit::default(),
         })
     }
 
-    #[instrument(skip(self))]
-    pub async fn get_commit_count(&self, repo_name: &str) -> Result<u32> {
-        self.api_rate_limiter
-            .until_ready()
+    /// Sends a GET request (following a redirect) and reads the response
+    async fn try_send(&self, url: &str) -> octocrab::Result<Response<Bytes>> {
+        let response = self.crab._get(url).await?;
+        let response = self.crab.follow_location_to_data(response).await?;
+        let (parts, body) = response.into_parts();
+        let body = hyper::body::to_bytes(body)
+            .await
+            .map_err(|source| octocrab::Error::Hyper {
+                source,
+                backtrace: snafu::GenerateImplicitData::generate(),
+            })?;
+        Ok(Response::from_parts(parts, body))
+    }
+
+    /// Sends a GET request within the rate limit, retrying the failures that are likely to go away
+    async fn send(&self, rate_limit: &RateLimit, url: &str) -> Result<Response<Bytes>> {
+        let mut attempt = 0;
+        loop {
+            rate_limit
+                .wait()
+                .instrument(tracing::info_span!("wait_rate_limit"))
+                .await;
+            let (delay, reason) = match self.try_send(url).await {
+                Ok(response) => {
+                    rate_limit.update(response.headers());
+                    if response.status().is_success() {
+                        return Ok(response);
+                    }
+                    match retry_delay(&response, attempt) {
+                        Some(delay) if attempt < MAX_RETRIES => {
+                            (delay, response.status().to_string())
+                        }
+                        _ => return Err(GithubError::from_response(url, &response)),
+                    }
+                }
+                Err(e) if attempt < MAX_RETRIES => (backoff(attempt), root_cause(&e)),
+                Err(e) => return Err(GithubError::Request(e)),
+            };
+            warn!(
+                "Request to {} failed ({}), retrying in {}s",
+                url,
+                reason,
+                delay.as_secs()
+            );
+            tokio::time::sleep(delay).await;
+            attempt += 1;
+        }
+    }
+
+    /// Like `send`, but without reading the body of a successful response
+    ///
+    /// Only the first attempt is streamed, the failed ones are retried by `send`.
+    async fn send_streamed(&self, rate_limit: &RateLimit, url: &str) -> Result<Response<Body>> {
+        rate_limit
+            .wait()
             .instrument(tracing::info_span!("wait_rate_limit"))
             .await;
+        if let Ok(response) = self.crab._get(url).await {
+            if let Ok(response) = self.crab.follow_location_to_data(response).await {
+                rate_limit.update(response.headers());
+                if response.status().is_success() {
+                    return Ok(response);
+                }
+            }
+        }
+        let response = self.send(rate_limit, url).await?;
+        Ok(response.map(Body::from))
+    }
+
+    /// Gets `route` of the API, like `/repos/{repo_name}`
+    async fn get_json<T: DeserializeOwned>(&self, route: &str) -> Result<(T, HeaderMap)> {
+        let url = format!("{}{}", self.urls.api, route);
+        let response = self.send(&self.api_rate_limit, &url).await?;
+        let value = serde_json::from_slice(response.body())
+            .map_err(|e| GithubError::InvalidResponse(format!("{}: {}", url, e)))?;
+        Ok((value, response.into_parts().0.headers))
+    }
 
-        let page: Page<Commit> = self
-            .crab
-            .get(
-                format!("/repos/{repo_name}/commits?per_page=1",),
-                None::<&()>,
-            )
+    /// Returns the cached value, or caches the one `fetch` gets
+    async fn cached<T: Serialize + DeserializeOwned>(
+        &self,
+        key: &str,
+        fetch: impl Future<Output = Result<T>>,
+    ) -> Result<T> {
+        if let Some(cached) = self.cache.get(key).await.map_err(GithubError::Cache)? {
+            return Ok(cached);
+        }
+        let value = fetch.await?;
+        self.cache
+            .set(key, &value)
             .await
-            .context("Getting commits")?;
+            .map_err(GithubError::Cache)?;
+        Ok(value)
+    }
 
-        let last_page_url = Url::parse(&page.last.unwrap().to_string()).unwrap();
-        let (_, commits) = last_page_url
-            .query_pairs()
-            .find(|(k, _)| k == "page")
-            .unwrap();
-        let commits = commits.parse::<u32>().unwrap();
+    /// The number of commits in the history of `commit`
+    #[instrument(skip(self))]
+    pub async fn get_commit_count(&self, repo_name: &str, commit: &str) -> Result<u32> {
+        let route = format!("/repos/{repo_name}/commits?sha={commit}&per_page=1");
+        let (commits, headers) = self.get_json::<Vec<Commit>>(&route).await?;
 
-        Ok(commits)
+        // with a single commit there are no other pages
+        if headers.get("link").is_none() {
+            return Ok(commits.len() as u32);
+        }
+        last_page(&headers).ok_or_else(|| {
+            GithubError::InvalidResponse(format!("no last page in the links of {}", route))
+        })
     }
 
     #[instrument(skip(self))]
-    pub async fn get_latest_commit(&self, repo_name: &str) -> Result<String> {
-        self.api_rate_limiter
-            .until_ready()
-            .instrument(tracing::info_span!("wait_rate_limit"))
-            .await;
+    pub async fn get_latest_commit(&self, repo_name: &str) -> Result<Commit> {
+        let (commits, _) = self
+            .get_json::<Vec<Commit>>(&format!("/repos/{repo_name}/commits?per_page=1"))
+            .await?;
+
+        commits
+            .into_iter()
+            .next()
+            .ok_or(GithubError::EmptyRepository)
+    }
 
-        let page: Page<Commit> = self
-            .crab
-            .get(
-                format!("/repos/{repo_name}/commits?per_page=1",),
-                None::<&()>,
-            )
-            .await
-            .context("Getting commits")?;
+    /// The commit a branch, a tag or a commit SHA points to
+    #[instrument(skip(self))]
+    pub async fn get_commit(&self, repo_name: &str, reference: &str) -> Result<Commit> {
+        let reference = encode_path(reference);
+        let (commit, _) = self
+            .get_json(&format!("/repos/{repo_name}/commits/{reference}"))
+            .await?;
+        Ok(commit)
+    }
 
-        Ok(page.items[0].sha.clone())
+    #[instrument(skip(self))]
+    pub async fn is_branch(&self, repo_name: &str, reference: &str) -> Result<bool> {
+        // unlike `/branches/{branch}`, doesn't fail when there is no such branch
+        let (refs, _) = self
+            .get_json::<Vec<GitRef>>(&format!(
+                "/repos/{repo_name}/git/matching-refs/heads/{}",
+                encode_path(reference)
+            ))
+            .await?;
+
+        let full_name = format!("refs/heads/{reference}");
+        Ok(refs.iter().any(|r| r.ref_ == full_name))
     }
 
+    /// The items of a tree (or of the tree of a commit), with the items of all its subtrees if `recursive`
     #[instrument(skip(self))]
-    pub async fn get_repo_tree(&self, repo_name: &str, commit: &str) -> Result<Tree> {
-        let cache_key = format!("tree/{}", commit);
+    pub async fn get_repo_tree(&self, repo_name: &str, sha: &str, recursive: bool) -> Result<Tree> {
+        let (cache_key, route) = match recursive {
+            true => (
+                format!("tree/{}", sha),
+                format!("/repos/{repo_name}/git/trees/{sha}?recursive=true"),
+            ),
+            false => (
+                format!("tree/{}/flat", sha),
+                format!("/repos/{repo_name}/git/trees/{sha}"),
+            ),
+        };
 
-        if let Some(cached) = self
-            .cache
-            .get(&cache_key)
-            .await
-            .context("Reading from cache")?
-        {
-            return Ok(cached);
-        }
+        self.cached(&cache_key, async {
+            let (tree, _) = self
+                .get_json(&route)
+                .instrument(tracing::info_span!("get_tree"))
+                .await?;
+            Ok(tree)
+        })
+        .await
+    }
 
-        self.api_rate_limiter
-            .until_ready()
-            .instrument(tracing::info_span!("wait_rate_limit"))
-            .await;
-        let tree: RateLimitInfo<Tree> = self
-            .crab
-            .get(
-                format!("/repos/{repo_name}/git/trees/{commit}?recursive=true"),
-                None::<&()>,
-            )
-            .instrument(tracing::info_span!("get_tree"))
-            .await
-            .context("Getting the tree")?;
-        if let Some(used) = NonZeroU32::new(tree.used - 1) {
-            self.api_rate_limiter
-                .until_n_ready(used)
-                .instrument(tracing::info_span!("wait_rate_limit"))
-                .await
-                .context("Waiting for rate limit")?;
+    /// The `.tar.gz` of the repository at the commit, from the cache or as it's downloaded
+    ///
+    /// A downloaded archive is written to the cache while it's read, and kept once `Archive::finish` reads all of it.
+    #[instrument(skip(self))]
+    pub async fn get_archive(&self, repo_name: &str, commit: &str) -> Result<Archive> {
+        let key = format!("archive/{commit}");
+        if let Some(cached) = self.cache.reader(&key).await.map_err(GithubError::Cache)? {
+            return Ok(Archive {
+                body: Box::new(cached),
+                cache: None,
+            });
         }
 
-        let tree = tree.content;
-        self.cache
-            .set(&cache_key, &tree)
-            .await
-            .context("Writing to cache")?;
-
-        Ok(tree)
+        // redirects to `codeload.github.com`
+        let url = format!("{}/repos/{repo_name}/tarball/{commit}", self.urls.api);
+        let response = self
+            .send_streamed(&self.api_rate_limit, &url)
+            .instrument(tracing::info_span!("download"))
+            .await?;
+        let body = response
+            .into_body()
+            .map(|chunk| chunk.map_err(io::Error::other));
+        Ok(Archive {
+            body: Box::new(SyncIoBridge::new(StreamReader::new(body))),
+            cache: Some(self.cache.writer(&key).map_err(GithubError::Cache)?),
+        })
     }
 
     #[instrument(skip(self, tree_item), fields(path = %tree_item.path))]
@@ -217,62 +468,74 @@ impl LimitedCrab {
         tree_item: &TreeItem,
     ) -> Result<String> {
         let hash = &tree_item.sha;
-        let cache_key = format!("file/{hash}");
-        if let Some(cached) = self
-            .cache
-            .get(&cache_key)
-            .await
-            .context("Reading from cache")?
-        {
-            return Ok(cached);
-        }
+        self.cached(&format!("file/{hash}"), async {
+            let url_path = encode_path(&tree_item.path);
+            let url = format!("{}/{repo_name}/{commit}/{url_path}", self.urls.raw);
+            let response = self.send(&self.user_content_rate_limit, &url).await?;
 
-        self.user_content_rate_limiter
-            .until_ready()
-            .instrument(tracing::info_span!("wait_rate_limit"))
-            .await;
-
-        let url_path = tree_item
-            .path
-            .split('/')
-            .map(urlencoding::encode)
-            .collect::<Vec<_>>()
-            .join("/");
-        let url = format!("https://raw.githubusercontent.com/{repo_name}/{commit}/{url_path}");
-
-        let RawBody(contents) = self
-            .crab
-            .get(url, None::<&()>)
-            .await
-            .context("Getting the file")?;
-
-        self.cache
-            .set(&cache_key, &contents)
-            .await
-            .context("Writing to cache")?;
-
-        Ok(contents)
+            String::from_utf8(response.into_body().to_vec())
+                .map_err(|_| GithubError::InvalidResponse(format!("{} is not UTF-8", url)))
+        })
+        .await
     }
 
     #[instrument(skip(self))]
     pub async fn get_repo_info(&self, repo_name: &str) -> Result<Repository> {
-        let (owner, repo) = repo_name.split_once('/').unwrap();
-
-        self.user_content_rate_limiter
-            .until_ready()
-            .instrument(tracing::info_span!("wait_rate_limit"))
-            .await;
-        let info = self
-            .crab
-            .repos(owner, repo)
-            .get()
-            .await
-            .context("Getting repo info")?;
+        match repo_name.split_once('/') {
+            Some((owner, repo)) if !owner.is_empty() && !repo.is_empty() => {}
+            _ => return Err(GithubError::InvalidRepoName(repo_name.to_string())),
+        }
 
+        let (info, _) = self.get_json(&format!("/repos/{repo_name}")).await?;
         Ok(info)
     }
 }
 
+/// Encodes the segments of a `/`-separated path for an URL
+fn encode_path(path: &str) -> String {
+    path.split('/')
+        .map(urlencoding::encode)
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// The `.tar.gz` of a repository, read outside of the async runtime
+///
+/// A downloaded one is also written to the cache as it's read.
+pub struct Archive {
+    body: Box<dyn Read + Send>,
+    cache: Option<cacache::SyncWriter>,
+}
+
+impl Read for Archive {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.body.read(buf)?;
+        if let Some(cache) = &mut self.cache {
+            cache.write_all(&buf[..read])?;
+        }
+        Ok(read)
+    }
+}
+
+impl Archive {
+    /// Reads what's left of the archive, and keeps it in the cache if it was downloaded
+    ///
+    /// The end of a `.tar.gz`, past the last file, is not read by the extraction.
+    pub fn finish(mut self) -> anyhow::Result<()> {
+        io::copy(&mut self, &mut io::sink()).context("Reading the archive")?;
+        if let Some(cache) = self.cache {
+            cache.commit().context("Caching the archive")?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct GitRef {
+    #[serde(rename = "ref")]
+    ref_: String,
+}
+
 #[derive(Debug, Copy, Clone, Hash, Eq, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 #[non_exhaustive]
@@ -301,70 +564,62 @@ pub struct Tree {
     pub truncated: bool,
 }
 
-#[allow(unused)]
-struct RateLimitInfo<T: DeserializeOwned> {
-    content: T,
-    limit: u32,
-    used: u32,
-    remaining: u32,
-    resource: String,
-}
+#[cfg(test)]
+mod tests {
+    use super::{last_page, retry_delay};
+    use http::{HeaderMap, HeaderValue, Response, StatusCode};
+    use hyper::body::Bytes;
+    use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+    fn response(status: StatusCode, headers: &[(&'static str, &str)]) -> Response<Bytes> {
+        let mut response = Response::new(Bytes::new());
+        *response.status_mut() = status;
+        for (name, value) in headers {
+            response
+                .headers_mut()
+                .insert(*name, HeaderValue::from_str(value).unwrap());
+        }
+        response
+    }
 
-#[async_trait::async_trait]
-impl<T: DeserializeOwned> FromResponse for RateLimitInfo<T> {
-    async fn from_response(response: Response<Body>) -> octocrab::Result<Self> {
-        let headers = response.headers();
-        let limit = headers
-            .get("x-ratelimit-limit")
-            .unwrap()
-            .to_str()
-            .unwrap()
-            .parse()
-            .unwrap();
-        let used = headers
-            .get("x-ratelimit-used")
-            .unwrap()
-            .to_str()
-            .unwrap()
-            .parse()
-            .unwrap();
-        let remaining = headers
-            .get("x-ratelimit-remaining")
-            .unwrap()
-            .to_str()
-            .unwrap()
-            .parse()
-            .unwrap();
-        let resource = headers
-            .get("x-ratelimit-resource")
-            .unwrap()
-            .to_str()
-            .unwrap()
-            .to_string();
-
-        let json: serde_json::Value = serde_json::from_slice(
-            hyper::body::to_bytes(response.into_body())
-                .await
-                .map_err(|source| octocrab::Error::Hyper {
-                    source,
-                    backtrace: snafu::Backtrace::generate(),
-                })?
-                .as_ref(),
+    #[test]
+    fn retries() {
+        let delay = |status, headers: &[_]| retry_delay(&response(status, headers), 2);
+        assert_eq!(
+            delay(StatusCode::BAD_GATEWAY, &[]),
+            Some(Duration::from_secs(4))
+        );
+        assert_eq!(
+            delay(StatusCode::FORBIDDEN, &[("retry-after", "60")]),
+            Some(Duration::from_secs(60))
+        );
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
+        let reset = (now.as_secs() + 100).to_string();
+        let until_reset = delay(
+            StatusCode::FORBIDDEN,
+            &[
+                ("x-ratelimit-remaining", "0"),
+                ("x-ratelimit-reset", &reset),
+            ],
         )
-        .map_err(|source| octocrab::Error::Serde {
-            source,
-            backtrace: snafu::Backtrace::generate(),
-        })?;
+        .unwrap();
+        assert!(until_reset > Duration::from_secs(90) && until_reset <= Duration::from_secs(100));
+        // no access, or no such thing: waiting won't help
+        assert_eq!(delay(StatusCode::FORBIDDEN, &[]), None);
+        assert_eq!(delay(StatusCode::NOT_FOUND, &[]), None);
+    }
 
-        Ok(Self {
-            content: serde_json::from_value(json).map_err(|source| octocrab::Error::Serde {
-                source,
-                backtrace: snafu::Backtrace::generate(),
-            })?,
-            limit,
-            used,
-            remaining,
-            resource,
-        })
+    #[test]
+    fn pagination() {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            "link",
+            HeaderValue::from_static(
+                "<https://api.github.com/repositories/1/commits?per_page=1&page=2>; rel=\"next\", \
+                 <https://api.github.com/repositories/1/commits?per_page=1&page=1234>; rel=\"last\"",
+            ),
+        );
+        assert_eq!(last_page(&headers), Some(1234));
+        assert_eq!(last_page(&HeaderMap::new()), None);
     }
 }
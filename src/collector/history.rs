@@ -0,0 +1,119 @@
+use super::config::{CollectConfig, FileFilter};
+use super::diagnostics::Diagnostics;
+use super::git::{LocalRepo, TreeEntry};
+use super::workspace::{is_manifest, Workspace};
+use super::{aggregate_results, repo_result, Analyzer, FileResult, RepoResult};
+use anyhow::{ensure, Context, Result};
+use rayon::prelude::*;
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use std::sync::Arc;
+use tracing::info;
+
+/// (path, blob hash)
+type FileKey = (String, String);
+
+fn file_key(entry: &TreeEntry) -> FileKey {
+    (entry.path.clone(), entry.object.clone())
+}
+
+/// Selects the commits to collect the history for, oldest first
+///
+/// Takes the first-parent history of `rev`, keeping only every `every`th commit (counting from `rev`)
+/// or only the tagged ones.
+pub fn history_commits(
+    repo_path: &Path,
+    rev: &str,
+    every: usize,
+    tags: bool,
+) -> Result<Vec<String>> {
+    ensure!(every > 0, "Commit step must be positive");
+
+    let repo = LocalRepo::open(repo_path)?;
+    let history = repo.first_parent_history(rev)?;
+
+    let mut commits = if tags {
+        let tagged = repo.tagged_commits()?;
+        history
+            .into_iter()
+            .filter(|c| tagged.contains(c))
+            .collect::<Vec<_>>()
+    } else {
+        history.into_iter().step_by(every).collect::<Vec<_>>()
+    };
+    commits.reverse();
+
+    Ok(commits)
+}
+
+/// Collects metrics for a sequence of commits of a local repository
+///
+/// The files which didn't change since the previous commit are not parsed again.
+pub struct HistoryCollector {
+    repo: LocalRepo,
+    filter: FileFilter,
+    analyzer: Analyzer,
+    /// results for the files of the last collected commit
+    cache: HashMap<FileKey, Arc<FileResult>>,
+}
+
+impl HistoryCollector {
+    pub fn new(repo_path: &Path, config: CollectConfig) -> Result<Self> {
+        Ok(Self {
+            repo: LocalRepo::open(repo_path)?,
+            filter: config.file_filter()?,
+            analyzer: Analyzer::new(&config),
+            cache: HashMap::new(),
+        })
+    }
+
+    pub fn collect(&mut self, rev: &str) -> Result<RepoResult> {
+        let meta = self.repo.metadata(rev)?;
+        let (manifests, entries) = self
+            .repo
+            .list_files(&meta.commit, &self.filter)?
+            .into_iter()
+            .partition::<Vec<_>, _>(|e| is_manifest(&e.path));
+        let manifests = self
+            .repo
+            .read_files(&manifests)
+            .with_context(|| format!("Reading manifests at {}", meta.commit))?;
+        let workspace =
+            Workspace::from_manifests(&manifests.into_iter().flatten().collect::<Vec<_>>());
+
+        let new_entries = entries
+            .iter()
+            .filter(|e| !self.cache.contains_key(&file_key(e)))
+            .cloned()
+            .collect::<Vec<_>>();
+        info!(
+            "{} of {} files have changed",
+            new_entries.len(),
+            entries.len()
+        );
+        let new_files = self
+            .repo
+            .read_files(&new_entries)
+            .with_context(|| format!("Reading files at {}", meta.commit))?;
+
+        let analyzer = &self.analyzer;
+        let new_results = new_entries
+            .par_iter()
+            .zip(new_files)
+            .map(|(entry, file)| (file_key(entry), Arc::new(analyzer.analyze_source(file))))
+            .collect::<Vec<_>>();
+        self.cache.extend(new_results);
+
+        // forget the files that are gone, the history goes forward so they are unlikely to come back
+        let current = entries.iter().map(file_key).collect::<HashSet<_>>();
+        self.cache.retain(|key, _| current.contains(key));
+
+        let results = entries
+            .iter()
+            .map(|e| self.cache[&file_key(e)].clone())
+            .collect::<Vec<_>>();
+        let metrics = aggregate_results(&self.analyzer, &workspace, &results)?;
+
+        Ok(repo_result(meta, metrics, Diagnostics::of(&results)))
+    }
+}
@@ -0,0 +1,192 @@
+//! The list of the metrics with their descriptions, for `list-metrics` and the README
+
+use super::metrics::schema::{MetricInfo, Scope};
+use super::{git, rust_code_analysis, CollectConfig};
+use anyhow::Result;
+use clap::ValueEnum;
+use std::fmt::Write;
+
+/// How `list-metrics` prints the list
+#[derive(Debug, Default, Clone, Copy, ValueEnum)]
+pub enum ListFormat {
+    /// A table with the name, scope, unit and description of every metric
+    #[default]
+    Text,
+    Json,
+    /// `\metric{name}{description}` lines
+    Latex,
+    /// Lists grouped by the scope, as in the README
+    Markdown,
+}
+
+/// The metrics collected with `config`, known without collecting anything
+///
+/// The names are the ones produced by the `unaggregated` observer, the class and package prefixes are not included.
+pub fn metric_list(config: &CollectConfig) -> Vec<MetricInfo> {
+    let mut metrics = config
+        .metric_collectors()
+        .iter()
+        .flat_map(|c| {
+            c.schema()
+                .iter()
+                .map(|s| MetricInfo::of(c.name(), s))
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+    if config.rca_enabled() {
+        metrics.extend(rust_code_analysis::schema());
+    }
+    if config.repo_enabled() {
+        metrics.extend(git::REPO_SCHEMA.iter().map(|s| MetricInfo::of("repo", s)));
+    }
+    metrics.sort_by(|a, b| a.name.cmp(&b.name));
+    metrics
+}
+
+fn scope_title(scope: Scope) -> &'static str {
+    match scope {
+        Scope::File => "File metrics",
+        Scope::Function => "Function metrics",
+        Scope::Type => "Type metrics",
+        Scope::Statement => "Statement metrics",
+        Scope::MacroCall => "Macro invocation metrics",
+        Scope::Repo => "Repository metrics",
+    }
+}
+
+fn render_text(metrics: &[MetricInfo]) -> String {
+    let width = metrics.iter().map(|m| m.name.len()).max().unwrap_or(0);
+    let mut result = String::new();
+    for metric in metrics {
+        writeln!(
+            result,
+            "{:width$}  {:10}  {:9}  {}",
+            metric.name,
+            metric.scope.name(),
+            metric.unit.name(),
+            metric.description,
+        )
+        .unwrap();
+    }
+    result
+}
+
+fn render_latex(metrics: &[MetricInfo]) -> String {
+    let mut result = String::new();
+    for metric in metrics {
+        writeln!(
+            result,
+            "\\metric{{{}}}{{{}}}",
+            crowbook_text_processing::escape::tex(metric.name.as_str()),
+            crowbook_text_processing::escape::tex(metric.description.as_str()),
+        )
+        .unwrap();
+    }
+    result
+}
+
+fn render_markdown(metrics: &[MetricInfo]) -> String {
+    let mut sections = Vec::new();
+    for scope in Scope::ALL {
+        let mut section = format!("### {}\n\n", scope_title(scope));
+        let mut empty = true;
+        for metric in metrics.iter().filter(|m| m.scope == scope) {
+            writeln!(
+                section,
+                "- `{}` ({}): {}",
+                metric.name,
+                metric.unit.name(),
+                metric.description
+            )
+            .unwrap();
+            empty = false;
+        }
+        if !empty {
+            sections.push(section);
+        }
+    }
+    sections.join("\n")
+}
+
+pub fn render(metrics: &[MetricInfo], format: ListFormat) -> Result<String> {
+    Ok(match format {
+        ListFormat::Text => render_text(metrics),
+        ListFormat::Json => serde_json::to_string_pretty(metrics)? + "\n",
+        ListFormat::Latex => render_latex(metrics),
+        ListFormat::Markdown => render_markdown(metrics),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{metric_list, render, ListFormat};
+    use crate::collector::{flatten_metrics, Analyzer, CollectConfig, FileText};
+    use std::collections::BTreeSet;
+
+    const README_START: &str = "<!-- metrics:start -->\n";
+    const README_END: &str = "<!-- metrics:end -->";
+
+    #[test]
+    fn matches_collected_metrics() {
+        let config = CollectConfig::default();
+        let file = FileText {
+            path: "src/lib.rs".into(),
+            content: r#"
+pub struct Foo { pub a: u32 }
+enum Bar { A, B }
+trait Baz { fn baz(&self) {} }
+impl Foo { fn foo(&mut self) { if true { println!("{}", |x: u32| x); } } }
+pub fn free() {}
+"#
+            .to_string(),
+        };
+
+        let analyzer = Analyzer::new(&config);
+        let results = analyzer.analyze_files(vec![file]);
+        let files = results.iter().map(|r| &*r.whole).collect::<Vec<_>>();
+        let metrics = analyzer.aggregate_file_metrics(&files).unwrap();
+        let collected = flatten_metrics(&metrics)
+            .into_keys()
+            .collect::<BTreeSet<_>>();
+
+        let listed = metric_list(&config)
+            .into_iter()
+            .map(|m| m.name)
+            // not collected from the files
+            .filter(|name| !name.starts_with("repo."))
+            .collect::<BTreeSet<_>>();
+
+        assert_eq!(
+            listed.difference(&collected).collect::<Vec<_>>(),
+            Vec::<&String>::new(),
+            "listed, but not collected"
+        );
+        assert_eq!(
+            collected.difference(&listed).collect::<Vec<_>>(),
+            Vec::<&String>::new(),
+            "collected, but not listed"
+        );
+    }
+
+    /// The README section is generated by `list-metrics --format markdown`, run with `UPDATE_EXPECT=1` to update it
+    #[test]
+    fn readme_is_up_to_date() {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/README.md");
+        let readme = std::fs::read_to_string(path).unwrap();
+        let start =
+            readme.find(README_START).expect("no metrics start marker") + README_START.len();
+        let end = readme.find(README_END).expect("no metrics end marker");
+
+        let metrics = metric_list(&CollectConfig::default());
+        let expected = format!("{}\n", render(&metrics, ListFormat::Markdown).unwrap());
+        if readme[start..end] == expected {
+            return;
+        }
+        if std::env::var_os("UPDATE_EXPECT").is_some() {
+            let updated = format!("{}{}{}", &readme[..start], expected, &readme[end..]);
+            std::fs::write(path, updated).unwrap();
+        } else {
+            panic!("The metrics in README.md are out of date, run the tests with UPDATE_EXPECT=1 to update them");
+        }
+    }
+}
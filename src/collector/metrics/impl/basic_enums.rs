@@ -1,7 +1,7 @@
 use super::prelude::{util::Observer, *};
-use util::{Monoid, Unaggregated};
+use util::{Location, Monoid, Unaggregated};
 
-#[derive(Default, Serialize, Clone)]
+#[derive(Default, Serialize, Deserialize, Clone)]
 struct Enums<Obs = Unaggregated> {
     variant_count: Obs,
     attr_count: Obs,
@@ -24,20 +24,44 @@ impl<T: Monoid + Default> Monoid for Enums<T> {
 
 impl<Obs: Observer> Visit<'_> for Enums<Obs> {
     fn visit_item_enum(&mut self, i: &'_ syn::ItemEnum) {
-        self.variant_count.observe(i.variants.len());
-        self.attr_count.observe(i.attrs.len());
-        i.variants
-            .iter()
-            .for_each(|x| self.variant_attr_count.observe(x.attrs.len()));
+        let location = || Location::of(Some(i.ident.to_string()), i);
+        self.variant_count.observe_at(i.variants.len(), location);
+        self.attr_count.observe_at(i.attrs.len(), location);
+        i.variants.iter().for_each(|x| {
+            self.variant_attr_count
+                .observe_at(x.attrs.len(), || Location::of(Some(i.ident.to_string()), x))
+        });
         syn::visit::visit_item_enum(self, i);
     }
 }
 
+const SCHEMA: &[MetricSchema] = &[
+    MetricSchema::new(
+        "attr_count",
+        Scope::Type,
+        Unit::Count,
+        "Number of attributes of an enum",
+    ),
+    MetricSchema::new(
+        "variant_attr_count",
+        Scope::Type,
+        Unit::Count,
+        "Number of attributes of an enum variant",
+    ),
+    MetricSchema::new(
+        "variant_count",
+        Scope::Type,
+        Unit::Count,
+        "Number of variants of an enum",
+    ),
+];
+
 pub fn make_collector<
-    Obs: Observer + Default + Serialize + Clone + Monoid + Send + Sync + 'static,
+    Obs: Observer + Default + Serialize + DeserializeOwned + Clone + Monoid + Send + Sync + 'static,
 >() -> MetricCollectorBox {
     util::VisitorCollector::new(
         "enums",
+        SCHEMA,
         Enums::<Obs>::default(),
         |v| v,
         |v| Monoid::reduce(v.iter().cloned()),
@@ -1,7 +1,8 @@
 use super::prelude::{
-    util::{Monoid, Observer, Unaggregated},
+    util::{Location, Monoid, Observer, Unaggregated},
     *,
 };
+use syn::spanned::Spanned;
 
 #[derive(Default, Debug)]
 struct FileStats {
@@ -36,8 +37,10 @@ impl Visit<'_> for FileStats {
     }
 }
 
-#[derive(Clone, Default, Serialize)]
+#[derive(Clone, Default, Serialize, Deserialize)]
 struct Files<Obs = Unaggregated> {
+    /// number of lines, up to the last token of the file
+    line_count: Obs,
     struct_count: Obs,
     enum_count: Obs,
     impl_block_count: Obs,
@@ -49,11 +52,16 @@ impl<Obs: Observer> Visit<'_> for Files<Obs> {
     fn visit_file(&mut self, i: &'_ syn::File) {
         let mut file_stats = FileStats::default();
         syn::visit::visit_file(&mut file_stats, i);
-        self.struct_count.observe(file_stats.structs_count);
-        self.enum_count.observe(file_stats.enums_count);
-        self.impl_block_count.observe(file_stats.impls_count);
+        let location = || Location::of(None, i);
+        self.line_count.observe_at(i.span().end().line, location);
+        self.struct_count
+            .observe_at(file_stats.structs_count, location);
+        self.enum_count.observe_at(file_stats.enums_count, location);
+        self.impl_block_count
+            .observe_at(file_stats.impls_count, location);
         // self.all_fn_count.observe(file_stats.all_fns_count);
-        self.pub_fn_count.observe(file_stats.pub_fns_count);
+        self.pub_fn_count
+            .observe_at(file_stats.pub_fns_count, location);
     }
 }
 
@@ -64,6 +72,7 @@ impl<T: Monoid + Default> Monoid for Files<T> {
 
     fn unite(self, rhs: Self) -> Self {
         Self {
+            line_count: self.line_count.unite(rhs.line_count),
             struct_count: self.struct_count.unite(rhs.struct_count),
             enum_count: self.enum_count.unite(rhs.enum_count),
             impl_block_count: self.impl_block_count.unite(rhs.impl_block_count),
@@ -73,11 +82,45 @@ impl<T: Monoid + Default> Monoid for Files<T> {
     }
 }
 
+const SCHEMA: &[MetricSchema] = &[
+    MetricSchema::new(
+        "enum_count",
+        Scope::File,
+        Unit::Count,
+        "Number of enums in a file",
+    ),
+    MetricSchema::new(
+        "impl_block_count",
+        Scope::File,
+        Unit::Count,
+        "Number of impl blocks in a file",
+    ),
+    MetricSchema::new(
+        "line_count",
+        Scope::File,
+        Unit::Lines,
+        "Number of lines in a file, up to its last token",
+    ),
+    MetricSchema::new(
+        "pub_fn_count",
+        Scope::File,
+        Unit::Count,
+        "Number of `pub` free-standing functions in a file",
+    ),
+    MetricSchema::new(
+        "struct_count",
+        Scope::File,
+        Unit::Count,
+        "Number of structs in a file",
+    ),
+];
+
 pub fn make_collector<
-    Obs: Observer + Default + Serialize + Clone + Monoid + Send + Sync + 'static,
+    Obs: Observer + Default + Serialize + DeserializeOwned + Clone + Monoid + Send + Sync + 'static,
 >() -> MetricCollectorBox {
     util::VisitorCollector::new(
         "per_file",
+        SCHEMA,
         Files::<Obs>::default(),
         |v| v,
         |v| Monoid::reduce(v.iter().cloned()),
@@ -119,6 +162,9 @@ mod tests {
             code,
             expect![[r#"
                 {
+                  "line_count": [
+                    1
+                  ],
                   "struct_count": [
                     2
                   ],
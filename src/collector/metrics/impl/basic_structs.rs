@@ -1,8 +1,8 @@
 use super::prelude::{util::Observer, *};
 use syn::Visibility;
-use util::{Monoid, Unaggregated};
+use util::{Location, Monoid, Unaggregated};
 
-#[derive(Default, Serialize, Clone)]
+#[derive(Default, Serialize, Deserialize, Clone)]
 struct Structs<Obs = Unaggregated> {
     fields_count: Obs,
     public_fields_count: Obs,
@@ -27,26 +27,57 @@ impl<T: Monoid + Default> Monoid for Structs<T> {
 
 impl<Obs: Observer> Visit<'_> for Structs<Obs> {
     fn visit_item_struct(&mut self, i: &'_ syn::ItemStruct) {
-        self.fields_count.observe(i.fields.len());
-        self.public_fields_count.observe(
+        let location = || Location::of(Some(i.ident.to_string()), i);
+        self.fields_count.observe_at(i.fields.len(), location);
+        self.public_fields_count.observe_at(
             i.fields
                 .iter()
                 .filter(|field| matches!(field.vis, Visibility::Public(_)))
                 .count(),
+            location,
         );
         i.fields.iter().for_each(|x| {
-            self.field_attr_count.observe(x.attrs.len());
+            self.field_attr_count
+                .observe_at(x.attrs.len(), || Location::of(Some(i.ident.to_string()), x));
         });
-        self.attrs_count.observe(i.attrs.len());
+        self.attrs_count.observe_at(i.attrs.len(), location);
         syn::visit::visit_item_struct(self, i);
     }
 }
 
+const SCHEMA: &[MetricSchema] = &[
+    MetricSchema::new(
+        "attrs_count",
+        Scope::Type,
+        Unit::Count,
+        "Number of attributes of a struct",
+    ),
+    MetricSchema::new(
+        "field_attr_count",
+        Scope::Type,
+        Unit::Count,
+        "Number of attributes of a struct field",
+    ),
+    MetricSchema::new(
+        "fields_count",
+        Scope::Type,
+        Unit::Count,
+        "Number of fields of a struct",
+    ),
+    MetricSchema::new(
+        "public_fields_count",
+        Scope::Type,
+        Unit::Count,
+        "Number of `pub` fields of a struct",
+    ),
+];
+
 pub fn make_collector<
-    Obs: Observer + Default + Serialize + Clone + Monoid + Send + Sync + 'static,
+    Obs: Observer + Default + Serialize + DeserializeOwned + Clone + Monoid + Send + Sync + 'static,
 >() -> MetricCollectorBox {
     util::VisitorCollector::new(
         "structs",
+        SCHEMA,
         Structs::<Obs>::default(),
         |v| v,
         |v| Monoid::reduce(v.iter().cloned()),
@@ -1,8 +1,8 @@
 use super::prelude::{util::Observer, *};
 use syn::TraitItem;
-use util::{Monoid, Unaggregated};
+use util::{Location, Monoid, Unaggregated};
 
-#[derive(Default, Clone, Serialize)]
+#[derive(Default, Clone, Serialize, Deserialize)]
 struct TraitDefinitions<Obs = Unaggregated> {
     generic_param_count: Obs,
     supertrait_count: Obs,
@@ -28,9 +28,12 @@ impl<T: Monoid + Default> Monoid for TraitDefinitions<T> {
 
 impl<Obs: Observer> Visit<'_> for TraitDefinitions<Obs> {
     fn visit_item_trait(&mut self, i: &'_ syn::ItemTrait) {
-        self.generic_param_count.observe(i.generics.params.len());
-        self.supertrait_count.observe(i.supertraits.len());
-        self.default_fn_count.observe(
+        let location = || Location::of(Some(i.ident.to_string()), i);
+        self.generic_param_count
+            .observe_at(i.generics.params.len(), location);
+        self.supertrait_count
+            .observe_at(i.supertraits.len(), location);
+        self.default_fn_count.observe_at(
             i.items
                 .iter()
                 .filter(|trait_item| match trait_item {
@@ -38,28 +41,65 @@ impl<Obs: Observer> Visit<'_> for TraitDefinitions<Obs> {
                     _ => false,
                 })
                 .count(),
+            location,
         );
-        self.all_fn_count.observe(
+        self.all_fn_count.observe_at(
             i.items
                 .iter()
                 .filter(|trait_item| matches!(trait_item, TraitItem::Fn(_)))
                 .count(),
+            location,
         );
-        self.assoc_type_count.observe(
+        self.assoc_type_count.observe_at(
             i.items
                 .iter()
                 .filter(|trait_item| matches!(trait_item, TraitItem::Type(_)))
                 .count(),
+            location,
         );
         syn::visit::visit_item_trait(self, i);
     }
 }
 
+const SCHEMA: &[MetricSchema] = &[
+    MetricSchema::new(
+        "all_fn_count",
+        Scope::Type,
+        Unit::Count,
+        "Number of functions declared in a trait",
+    ),
+    MetricSchema::new(
+        "assoc_type_count",
+        Scope::Type,
+        Unit::Count,
+        "Number of associated types of a trait",
+    ),
+    MetricSchema::new(
+        "default_fn_count",
+        Scope::Type,
+        Unit::Count,
+        "Number of functions with a default implementation in a trait",
+    ),
+    MetricSchema::new(
+        "generic_param_count",
+        Scope::Type,
+        Unit::Count,
+        "Number of generic parameters of a trait",
+    ),
+    MetricSchema::new(
+        "supertrait_count",
+        Scope::Type,
+        Unit::Count,
+        "Number of supertraits of a trait",
+    ),
+];
+
 pub fn make_collector<
-    Obs: Observer + Default + Serialize + Clone + Monoid + Send + Sync + 'static,
+    Obs: Observer + Default + Serialize + DeserializeOwned + Clone + Monoid + Send + Sync + 'static,
 >() -> MetricCollectorBox {
     util::VisitorCollector::new(
         "trait_def",
+        SCHEMA,
         TraitDefinitions::<Obs>::default(),
         |v| v,
         |v| Monoid::reduce(v.iter().cloned()),
@@ -1,13 +1,15 @@
 //! Code taken from https://github.com/rossmacarthur/complexity, ported to syn 2.0 and adapted to work with our codebase.
 
+use crate::collector::metrics::util::{MacroArgs, WalkContext};
 use crate::stack::ensure_sufficient_stack;
 use std::{iter, ops};
 use syn::{
     BinOp, Block, Expr, ExprArray, ExprAssign, ExprAsync, ExprAwait, ExprBinary, ExprBlock,
-    ExprBreak, ExprCall, ExprCast, ExprClosure, ExprField, ExprForLoop, ExprGroup, ExprIf,
-    ExprIndex, ExprLet, ExprLoop, ExprMatch, ExprMethodCall, ExprParen, ExprRange, ExprReference,
-    ExprRepeat, ExprReturn, ExprStruct, ExprTry, ExprTryBlock, ExprTuple, ExprUnary, ExprUnsafe,
-    ExprWhile, ExprYield, Item, ItemConst, ItemStatic, Local, LocalInit, Stmt, UnOp,
+    ExprBreak, ExprCall, ExprCast, ExprClosure, ExprField, ExprForLoop, ExprGen, ExprGroup, ExprIf,
+    ExprIndex, ExprLet, ExprLoop, ExprMacro, ExprMatch, ExprMethodCall, ExprParen, ExprRange,
+    ExprReference, ExprRepeat, ExprReturn, ExprStruct, ExprTry, ExprTryBlock, ExprTuple, ExprUnary,
+    ExprUnsafe, ExprWhile, ExprYield, Item, ItemConst, ItemStatic, Local, LocalInit, Macro, Stmt,
+    StmtMacro, UnOp,
 };
 
 /// Represents a complexity index.
@@ -101,20 +103,31 @@ impl LogBoolOp {
 
 /// Represents the current state during parsing. We use this type to track the
 /// nesting level and the previous logical boolean operator.
-#[derive(Debug, Default, Clone, Copy)]
-pub struct State {
+#[derive(Clone, Copy)]
+pub struct State<'a> {
     /// The nesting level.
     nesting: u32,
     /// The previous logical boolean operator.
     log_bool_op: Option<LogBoolOp>,
+    /// The walk the function is evaluated in, which parses the arguments of the macros.
+    cx: &'a WalkContext,
 }
 
-impl State {
+impl<'a> State<'a> {
+    /// Create the `State` at the start of a function.
+    pub fn new(cx: &'a WalkContext) -> Self {
+        Self {
+            nesting: 0,
+            log_bool_op: None,
+            cx,
+        }
+    }
+
     /// Create a new `State` with an extra level of nesting.
     fn increase_nesting(self) -> Self {
         Self {
             nesting: self.nesting + 1,
-            log_bool_op: self.log_bool_op,
+            ..self
         }
     }
 }
@@ -142,8 +155,16 @@ pub fn eval_stmt(stmt: &Stmt, state: State) -> Index {
         Stmt::Local(Local { init: None, .. }) => Index::zero(),
         Stmt::Item(item) => eval_item(item, state),
         Stmt::Expr(expr, _) => eval_expr(expr, state),
-        // ignore macros
-        Stmt::Macro(_) => Index::zero(),
+        Stmt::Macro(StmtMacro { mac, .. }) => eval_macro(mac, state),
+    }
+}
+
+/// Returns the complexity of the arguments of a `syn::Macro`, zero if they are not Rust code.
+fn eval_macro(mac: &Macro, state: State) -> Index {
+    match state.cx.macro_args(mac).as_deref() {
+        Some(MacroArgs::Exprs(exprs)) => exprs.iter().map(|e| eval_expr(e, state)).sum(),
+        Some(MacroArgs::Stmts(stmts)) => stmts.iter().map(|s| eval_stmt(s, state)).sum(),
+        None => Index::zero(),
     }
 }
 
@@ -312,6 +333,7 @@ pub fn eval_expr(expr: &Expr, state: State) -> Index {
         // -----------------------------------------------------------
         Expr::Async(ExprAsync { block, .. })
         | Expr::Block(ExprBlock { block, .. })
+        | Expr::Gen(ExprGen { block, .. })
         | Expr::Loop(ExprLoop { body: block, .. })
         | Expr::TryBlock(ExprTryBlock { block, .. })
         | Expr::Unsafe(ExprUnsafe { block, .. }) => eval_block(block, state.increase_nesting()),
@@ -354,9 +376,7 @@ pub fn eval_expr(expr: &Expr, state: State) -> Index {
         Expr::Struct(expr_struct) => eval_expr_struct(expr_struct, state),
         Expr::Call(expr_call) => eval_expr_call(expr_call, state),
         Expr::MethodCall(expr_method_call) => eval_expr_method_call(expr_method_call, state),
-        // FIXME: should we attempt to parse macro the tokens into something that we can calculate
-        // the complexity for?
-        Expr::Macro(_) => Index::zero(),
+        Expr::Macro(ExprMacro { mac, .. }) => eval_macro(mac, state),
 
         Expr::Const(_) => Index::zero(),
         Expr::Infer(_) => Index::zero(),
@@ -379,7 +399,10 @@ mod tests {
                 println!("test");
             }
         };
-        assert_eq!(eval_expr(&expr, State::default()), Index(1));
+        assert_eq!(
+            eval_expr(&expr, State::new(&WalkContext::default())),
+            Index(1)
+        );
     }
 
     #[test]
@@ -391,7 +414,10 @@ mod tests {
                 }
             }
         };
-        assert_eq!(eval_expr(&expr, State::default()), Index(3));
+        assert_eq!(
+            eval_expr(&expr, State::new(&WalkContext::default())),
+            Index(3)
+        );
     }
 
     #[test]
@@ -405,7 +431,10 @@ mod tests {
                 }
             }
         };
-        assert_eq!(eval_expr(&expr, State::default()), Index(4));
+        assert_eq!(
+            eval_expr(&expr, State::new(&WalkContext::default())),
+            Index(4)
+        );
     }
 
     #[test]
@@ -417,7 +446,10 @@ mod tests {
                 }
             }
         };
-        assert_eq!(eval_expr(&expr, State::default()), Index(3));
+        assert_eq!(
+            eval_expr(&expr, State::new(&WalkContext::default())),
+            Index(3)
+        );
     }
 
     #[test]
@@ -429,7 +461,10 @@ mod tests {
                 }
             }
         };
-        assert_eq!(eval_expr(&expr, State::default()), Index(3));
+        assert_eq!(
+            eval_expr(&expr, State::new(&WalkContext::default())),
+            Index(3)
+        );
     }
 
     #[test]
@@ -441,7 +476,10 @@ mod tests {
                 }
             }
         };
-        assert_eq!(eval_expr(&expr, State::default()), Index(3));
+        assert_eq!(
+            eval_expr(&expr, State::new(&WalkContext::default())),
+            Index(3)
+        );
     }
 
     #[test]
@@ -453,7 +491,10 @@ mod tests {
                 }
             }
         };
-        assert_eq!(eval_expr(&expr, State::default()), Index(3));
+        assert_eq!(
+            eval_expr(&expr, State::new(&WalkContext::default())),
+            Index(3)
+        );
     }
 
     #[test]
@@ -466,7 +507,10 @@ mod tests {
                 }
             }
         };
-        assert_eq!(eval_expr(&expr, State::default()), Index(4));
+        assert_eq!(
+            eval_expr(&expr, State::new(&WalkContext::default())),
+            Index(4)
+        );
     }
 
     #[test]
@@ -479,7 +523,10 @@ mod tests {
                 }
             }
         };
-        assert_eq!(eval_expr(&expr, State::default()), Index(3));
+        assert_eq!(
+            eval_expr(&expr, State::new(&WalkContext::default())),
+            Index(3)
+        );
     }
 
     #[test]
@@ -491,42 +538,109 @@ mod tests {
                 s                                           => println!("test"),
             }
         };
-        assert_eq!(eval_expr(&expr, State::default()), Index(4));
+        assert_eq!(
+            eval_expr(&expr, State::new(&WalkContext::default())),
+            Index(4)
+        );
     }
 
     #[test]
     fn logical_boolean_operators_same() {
         let expr: Expr = parse_quote! { x && y };
-        assert_eq!(eval_expr(&expr, State::default()), Index(1));
+        assert_eq!(
+            eval_expr(&expr, State::new(&WalkContext::default())),
+            Index(1)
+        );
         let expr: Expr = parse_quote! { x && y && z };
-        assert_eq!(eval_expr(&expr, State::default()), Index(1));
+        assert_eq!(
+            eval_expr(&expr, State::new(&WalkContext::default())),
+            Index(1)
+        );
         let expr: Expr = parse_quote! { w && x && y && z };
-        assert_eq!(eval_expr(&expr, State::default()), Index(1));
+        assert_eq!(
+            eval_expr(&expr, State::new(&WalkContext::default())),
+            Index(1)
+        );
         let expr: Expr = parse_quote! { x || y };
-        assert_eq!(eval_expr(&expr, State::default()), Index(1));
+        assert_eq!(
+            eval_expr(&expr, State::new(&WalkContext::default())),
+            Index(1)
+        );
         let expr: Expr = parse_quote! { x || y || z };
-        assert_eq!(eval_expr(&expr, State::default()), Index(1));
+        assert_eq!(
+            eval_expr(&expr, State::new(&WalkContext::default())),
+            Index(1)
+        );
         let expr: Expr = parse_quote! { w || x || y || z };
-        assert_eq!(eval_expr(&expr, State::default()), Index(1));
+        assert_eq!(
+            eval_expr(&expr, State::new(&WalkContext::default())),
+            Index(1)
+        );
     }
 
     #[test]
     fn logical_boolean_operators_changing() {
         let expr: Expr = parse_quote! { w && x || y || z };
-        assert_eq!(eval_expr(&expr, State::default()), Index(2));
+        assert_eq!(
+            eval_expr(&expr, State::new(&WalkContext::default())),
+            Index(2)
+        );
         let expr: Expr = parse_quote! { w && x && y || z };
-        assert_eq!(eval_expr(&expr, State::default()), Index(2));
+        assert_eq!(
+            eval_expr(&expr, State::new(&WalkContext::default())),
+            Index(2)
+        );
         let expr: Expr = parse_quote! { w && x || y && z };
-        assert_eq!(eval_expr(&expr, State::default()), Index(3));
+        assert_eq!(
+            eval_expr(&expr, State::new(&WalkContext::default())),
+            Index(3)
+        );
     }
 
     #[test]
     fn logical_boolean_operators_not_operator() {
         let expr: Expr = parse_quote! { !a && !b };
-        assert_eq!(eval_expr(&expr, State::default()), Index(1));
+        assert_eq!(
+            eval_expr(&expr, State::new(&WalkContext::default())),
+            Index(1)
+        );
         let expr: Expr = parse_quote! { a && !(b && c) };
-        assert_eq!(eval_expr(&expr, State::default()), Index(2));
+        assert_eq!(
+            eval_expr(&expr, State::new(&WalkContext::default())),
+            Index(2)
+        );
         let expr: Expr = parse_quote! { !(a || b) && !(c || d) };
-        assert_eq!(eval_expr(&expr, State::default()), Index(3));
+        assert_eq!(
+            eval_expr(&expr, State::new(&WalkContext::default())),
+            Index(3)
+        );
+    }
+
+    #[test]
+    fn gen_block_nesting_increment() {
+        let expr: Expr = parse_quote! {
+            gen move {
+                for element in iterable { // +2 (nesting = 1)
+                    yield element;
+                }
+            }
+        };
+        assert_eq!(
+            eval_expr(&expr, State::new(&WalkContext::default())),
+            Index(2)
+        );
+    }
+
+    #[test]
+    fn macro_arguments() {
+        let block: Block = parse_quote! {{
+            assert!(if a { b } else { c });   // +2
+            let v = vec![x.map(|x| if x { 1 } else { 2 }); n];   // +2
+            html!(<p>if a { 1 }</p>);
+        }};
+        assert_eq!(
+            eval_block(&block, State::new(&WalkContext::default())),
+            Index(4)
+        );
     }
 }
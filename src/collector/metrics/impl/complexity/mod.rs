@@ -3,11 +3,14 @@
 mod r#impl;
 
 use super::prelude::*;
-use syn::{visit, ExprClosure, ImplItemFn, ItemFn};
+use crate::collector::metrics::FileVisitor;
+use std::borrow::Cow;
+use std::marker::PhantomData;
+use util::Location;
 use util::Monoid;
 use util::Observer;
 
-#[derive(Default, Clone, Serialize)]
+#[derive(Default, Clone, Serialize, Deserialize)]
 struct ComplexityStats<Obs> {
     item_fn: Obs,
     impl_item_fn: Obs,
@@ -37,46 +40,109 @@ impl<T: Monoid> Monoid for ComplexityStats<T> {
     }
 }
 
-impl<Obs: Observer> Visit<'_> for ComplexityStats<Obs> {
-    fn visit_expr_closure(&mut self, i: &'_ ExprClosure) {
-        let value = r#impl::eval_expr(&i.body, Default::default()).0 as usize;
-        self.closure.observe(value);
-        self.all_fn.observe(value);
-        visit::visit_expr_closure(self, i);
+impl<Obs: Observer> NodeVisitor for ComplexityStats<Obs> {
+    fn enter(&mut self, node: Node<'_>, cx: &WalkContext) -> Walk {
+        match node {
+            Node::ExprClosure(i) => {
+                let value = r#impl::eval_expr(&i.body, r#impl::State::new(cx)).0 as usize;
+                let location = || Location::of(None, i);
+                self.closure.observe_at(value, location);
+                self.all_fn.observe_at(value, location);
+            }
+            Node::ImplItemFn(i) => {
+                let value = r#impl::eval_block(&i.block, r#impl::State::new(cx)).0 as usize;
+                let location = || Location::of(Some(i.sig.ident.to_string()), i);
+                self.impl_item_fn.observe_at(value, location);
+                self.all_fn.observe_at(value, location);
+            }
+            Node::ItemFn(i) => {
+                let value = r#impl::eval_block(&i.block, r#impl::State::new(cx)).0 as usize;
+                let location = || Location::of(Some(i.sig.ident.to_string()), i);
+                self.item_fn.observe_at(value, location);
+                self.all_fn.observe_at(value, location);
+            }
+            Node::TraitItemFn(i) => {
+                if let Some(block) = &i.default {
+                    let value = r#impl::eval_block(block, r#impl::State::new(cx)).0 as usize;
+                    let location = || Location::of(Some(i.sig.ident.to_string()), i);
+                    self.trait_default_fn.observe_at(value, location);
+                    self.all_fn.observe_at(value, location);
+                }
+            }
+            _ => {}
+        }
+        Walk::Continue
     }
+}
+
+const SCHEMA: &[MetricSchema] = &[
+    MetricSchema::new(
+        "all_fn",
+        Scope::Function,
+        Unit::Score,
+        "Cognitive complexity of a function, a method or a closure",
+    ),
+    MetricSchema::new(
+        "closure",
+        Scope::Function,
+        Unit::Score,
+        "Cognitive complexity of a closure",
+    ),
+    MetricSchema::new(
+        "impl_item_fn",
+        Scope::Function,
+        Unit::Score,
+        "Cognitive complexity of a function in an impl block",
+    ),
+    MetricSchema::new(
+        "item_fn",
+        Scope::Function,
+        Unit::Score,
+        "Cognitive complexity of a free-standing function",
+    ),
+    MetricSchema::new(
+        "trait_default_fn",
+        Scope::Function,
+        Unit::Score,
+        "Cognitive complexity of a default function of a trait",
+    ),
+];
 
-    fn visit_impl_item_fn(&mut self, i: &'_ ImplItemFn) {
-        let value = r#impl::eval_block(&i.block, Default::default()).0 as usize;
-        self.impl_item_fn.observe(value);
-        self.all_fn.observe(value);
-        visit::visit_impl_item_fn(self, i);
+impl<Obs: Observer> FileVisitor<ComplexityStats<Obs>> for ComplexityStats<Obs> {
+    fn finish(self: Box<Self>) -> Self {
+        *self
     }
+}
+
+/// A `NodeVisitor` rather than a `VisitorCollector`, as it looks into the macros through the `WalkContext`
+struct ComplexityCollector<Obs>(PhantomData<Obs>);
+
+impl<
+        Obs: Observer + Default + Serialize + DeserializeOwned + Clone + Monoid + Send + Sync + 'static,
+    > MetricCollector for ComplexityCollector<Obs>
+{
+    type Metric = ComplexityStats<Obs>;
+    type AggregatedMetric = ComplexityStats<Obs>;
 
-    fn visit_item_fn(&mut self, i: &'_ ItemFn) {
-        let value = r#impl::eval_block(&i.block, Default::default()).0 as usize;
-        self.item_fn.observe(value);
-        self.all_fn.observe(value);
-        visit::visit_item_fn(self, i);
+    fn name(&self) -> &'static str {
+        "complexity"
     }
 
-    fn visit_trait_item_fn(&mut self, i: &'_ syn::TraitItemFn) {
-        if let Some(block) = &i.default {
-            let value = r#impl::eval_block(block, Default::default()).0 as usize;
-            self.trait_default_fn.observe(value);
-            self.all_fn.observe(value);
-        }
-        visit::visit_trait_item_fn(self, i);
+    fn schema(&self) -> Cow<'static, [MetricSchema]> {
+        Cow::Borrowed(SCHEMA)
+    }
+
+    fn file_visitor(&self) -> Box<dyn FileVisitor<Self::Metric> + '_> {
+        Box::<ComplexityStats<Obs>>::default()
+    }
+
+    fn aggregate_metrics(&self, metric: &[Self::Metric]) -> Self::AggregatedMetric {
+        Monoid::reduce(metric.iter().cloned())
     }
 }
 
 pub fn make_collector<
-    Obs: Observer + Default + Serialize + Clone + Monoid + Send + Sync + 'static,
+    Obs: Observer + Default + Serialize + DeserializeOwned + Clone + Monoid + Send + Sync + 'static,
 >() -> MetricCollectorBox {
-    util::VisitorCollector::new(
-        "complexity",
-        ComplexityStats::<Obs>::default(),
-        |v| v,
-        |v| Monoid::reduce(v.iter().cloned()),
-    )
-    .make_box()
+    ComplexityCollector::<Obs>(PhantomData).make_box()
 }
@@ -4,9 +4,9 @@ use syn::{
 };
 
 use super::prelude::{util::Observer, *};
-use util::{Monoid, Unaggregated};
+use util::{Location, Monoid, Unaggregated};
 
-#[derive(Default, Clone, Serialize)]
+#[derive(Default, Clone, Serialize, Deserialize)]
 pub struct FnArgsCount<Obs = Unaggregated> {
     mutable: Obs,
 }
@@ -46,20 +46,23 @@ fn count_mutable(signature: &syn::Signature) -> usize {
 impl<Obs: Observer> Visit<'_> for FnArgsCount<Obs> {
     fn visit_impl_item_fn(&mut self, i: &'_ syn::ImplItemFn) {
         let mutable = count_mutable(&i.sig);
-        self.mutable.observe(mutable);
+        self.mutable
+            .observe_at(mutable, || Location::of(Some(i.sig.ident.to_string()), i));
         visit::visit_impl_item_fn(self, i);
     }
 
     fn visit_item_fn(&mut self, i: &'_ syn::ItemFn) {
         let mutable = count_mutable(&i.sig);
-        self.mutable.observe(mutable);
+        self.mutable
+            .observe_at(mutable, || Location::of(Some(i.sig.ident.to_string()), i));
         visit::visit_item_fn(self, i);
     }
 
     fn visit_trait_item_fn(&mut self, i: &'_ syn::TraitItemFn) {
         if i.default.is_some() {
             let mutable = count_mutable(&i.sig);
-            self.mutable.observe(mutable);
+            self.mutable
+                .observe_at(mutable, || Location::of(Some(i.sig.ident.to_string()), i));
         }
         visit::visit_trait_item_fn(self, i);
     }
@@ -81,16 +84,24 @@ impl<Obs: Observer> Visit<'_> for FnArgsCount<Obs> {
                 _ => false,
             })
             .count();
-        self.mutable.observe(mutable);
+        self.mutable.observe_at(mutable, || Location::of(None, i));
         visit::visit_expr_closure(self, i);
     }
 }
 
+const SCHEMA: &[MetricSchema] = &[MetricSchema::new(
+    "mutable",
+    Scope::Function,
+    Unit::Count,
+    "Number of mutable reference arguments (including `&mut self`) of a function or a closure",
+)];
+
 pub fn make_collector<
-    Obs: Observer + Default + Serialize + Clone + Monoid + Send + Sync + 'static,
+    Obs: Observer + Default + Serialize + DeserializeOwned + Clone + Monoid + Send + Sync + 'static,
 >() -> MetricCollectorBox {
     util::VisitorCollector::new(
         "fn_arg_count",
+        SCHEMA,
         FnArgsCount::<Obs>::default(),
         |v| v,
         |v: &[FnArgsCount<Obs>]| Monoid::reduce(v.into_iter().map(|args| args.to_owned())),
@@ -1,5 +1,5 @@
 use super::prelude::{
-    util::{Observer, Unaggregated},
+    util::{Location, Observer, Unaggregated},
     *,
 };
 use crate::collector::metrics::util::Monoid;
@@ -13,7 +13,7 @@ struct VisitorAvgMethodDepth<Obs = Unaggregated> {
 }
 
 impl<Obs: Observer> VisitorAvgMethodDepth<Obs> {
-    fn handle_depth(&mut self, inner: impl FnOnce(&mut Self)) {
+    fn handle_depth(&mut self, location: impl FnOnce() -> Location, inner: impl FnOnce(&mut Self)) {
         let start_depth = self.current_depth;
         // reset max_depth for this function
         let old_max_depth = self.max_depth;
@@ -23,7 +23,7 @@ impl<Obs: Observer> VisitorAvgMethodDepth<Obs> {
 
         let depth = self.max_depth - start_depth;
         assert_ne!(depth, 0, "depth should never be 0");
-        self.observer.observe(depth as usize);
+        self.observer.observe_at(depth as usize, location);
         self.max_depth = old_max_depth;
     }
 
@@ -45,33 +45,50 @@ impl<Obs: Observer> Visit<'_> for VisitorAvgMethodDepth<Obs> {
     }
 
     fn visit_expr_closure(&mut self, i: &'_ ExprClosure) {
-        self.handle_depth(|v| {
-            // add a fake block to the closure if it doesn't have one
-            let add_fake_block = !matches!(i.body.as_ref(), Expr::Block(_));
-            if add_fake_block {
-                v.add_depth();
-            }
-            syn::visit::visit_expr_closure(v, i);
-            if add_fake_block {
-                v.sub_depth();
-            }
-        });
+        self.handle_depth(
+            || Location::of(None, i),
+            |v| {
+                // add a fake block to the closure if it doesn't have one
+                let add_fake_block = !matches!(i.body.as_ref(), Expr::Block(_));
+                if add_fake_block {
+                    v.add_depth();
+                }
+                syn::visit::visit_expr_closure(v, i);
+                if add_fake_block {
+                    v.sub_depth();
+                }
+            },
+        );
     }
 
     fn visit_impl_item_fn(&mut self, i: &'_ ImplItemFn) {
-        self.handle_depth(|v| syn::visit::visit_impl_item_fn(v, i));
+        self.handle_depth(
+            || Location::of(Some(i.sig.ident.to_string()), i),
+            |v| syn::visit::visit_impl_item_fn(v, i),
+        );
     }
 
     fn visit_item_fn(&mut self, i: &'_ ItemFn) {
-        self.handle_depth(|v| syn::visit::visit_item_fn(v, i));
+        self.handle_depth(
+            || Location::of(Some(i.sig.ident.to_string()), i),
+            |v| syn::visit::visit_item_fn(v, i),
+        );
     }
 }
 
+const SCHEMA: &[MetricSchema] = &[MetricSchema::new(
+    "",
+    Scope::Function,
+    Unit::Levels,
+    "Maximum nesting depth of blocks in a function or a closure",
+)];
+
 pub fn make_collector<
-    Obs: Observer + Default + Serialize + Clone + Monoid + Send + Sync + 'static,
+    Obs: Observer + Default + Serialize + DeserializeOwned + Clone + Monoid + Send + Sync + 'static,
 >() -> MetricCollectorBox {
     util::VisitorCollector::new(
         "fn_depth",
+        SCHEMA,
         VisitorAvgMethodDepth::<Obs>::default(),
         |v| v.observer,
         |v| Monoid::reduce(v.iter().cloned()),
@@ -81,7 +98,7 @@ pub fn make_collector<
 
 #[cfg(test)]
 mod tests {
-    use crate::collector::metrics::util::Unaggregated;
+    use crate::collector::metrics::util::{Located, Unaggregated};
 
     use super::VisitorAvgMethodDepth;
     use expect_test::{expect, Expect};
@@ -166,4 +183,47 @@ mod tests {
             expect![["[5]"]],
         );
     }
+
+    #[test]
+    fn test_depth_located() {
+        let code =
+            syn::parse_file_with_path("fn foo() {\n    let f = || {};\n}\n", "src/lib.rs").unwrap();
+        let mut metric = VisitorAvgMethodDepth::<Located>::default();
+        metric.visit_file(&code);
+        let metric = serde_json::to_string_pretty(&metric.observer).unwrap();
+        expect![[r#"
+            {
+              "values": [
+                1,
+                1
+              ],
+              "locations": [
+                {
+                  "path": "src/lib.rs",
+                  "item": null,
+                  "start": {
+                    "line": 2,
+                    "column": 12
+                  },
+                  "end": {
+                    "line": 2,
+                    "column": 17
+                  }
+                },
+                {
+                  "path": "src/lib.rs",
+                  "item": "foo",
+                  "start": {
+                    "line": 1,
+                    "column": 0
+                  },
+                  "end": {
+                    "line": 3,
+                    "column": 1
+                  }
+                }
+              ]
+            }"#]]
+        .assert_eq(&metric);
+    }
 }
@@ -13,9 +13,17 @@ impl Visit<'_> for VisitorIfCount {
     }
 }
 
+const SCHEMA: &[MetricSchema] = &[MetricSchema::new(
+    "",
+    Scope::Repo,
+    Unit::Count,
+    "Number of `if` expressions",
+)];
+
 pub fn make_collector() -> MetricCollectorBox {
     util::VisitorCollector::new(
         "if_count",
+        SCHEMA,
         VisitorIfCount::default(),
         |v| v.ifcount,
         |v| v.iter().sum::<u64>(),
@@ -1,10 +1,10 @@
 use super::prelude::{
-    util::{Observer, Unaggregated},
+    util::{Location, Observer, Unaggregated},
     *,
 };
 use util::Monoid;
 
-#[derive(Default, Clone, Serialize)]
+#[derive(Default, Clone, Serialize, Deserialize)]
 struct MacroStats<Obs = Unaggregated> {
     argument_size: Obs,
     count_per_file: Obs,
@@ -29,20 +29,45 @@ impl<Obs: Observer> Visit<'_> for MacroStats<Obs> {
         let end_count = self.argument_size.count();
 
         self.count_per_file
-            .observe((end_count - start_count) as usize);
+            .observe_at(end_count - start_count, || Location::of(None, i));
     }
 
     fn visit_macro(&mut self, i: &'_ syn::Macro) {
         self.argument_size
-            .observe(i.tokens.clone().into_iter().count());
+            .observe_at(i.tokens.clone().into_iter().count(), || {
+                let name = i
+                    .path
+                    .segments
+                    .iter()
+                    .map(|s| s.ident.to_string())
+                    .collect::<Vec<_>>()
+                    .join("::");
+                Location::of(Some(name), i)
+            });
     }
 }
 
+const SCHEMA: &[MetricSchema] = &[
+    MetricSchema::new(
+        "argument_size",
+        Scope::MacroCall,
+        Unit::Tokens,
+        "Number of token trees in the arguments of a macro invocation",
+    ),
+    MetricSchema::new(
+        "count_per_file",
+        Scope::File,
+        Unit::Count,
+        "Number of macro invocations in a file",
+    ),
+];
+
 pub fn make_collector<
-    Obs: Observer + Default + Serialize + Clone + Monoid + Send + Sync + 'static,
+    Obs: Observer + Default + Serialize + DeserializeOwned + Clone + Monoid + Send + Sync + 'static,
 >() -> MetricCollectorBox {
     util::VisitorCollector::new(
         "macro",
+        SCHEMA,
         MacroStats::<Obs>::default(),
         |v| v,
         |v| Monoid::reduce(v.iter().cloned()),
@@ -2,7 +2,7 @@ use std::collections::HashMap;
 
 use syn::{ImplItem, ImplItemFn};
 
-use crate::collector::metrics::util::{Monoid, Observer, Unaggregated};
+use crate::collector::metrics::util::{Location, Monoid, Observer, Unaggregated};
 
 use super::{
     prelude::*,
@@ -10,7 +10,7 @@ use super::{
 };
 
 /// lack of cohesion of methods per impl block
-#[derive(Default, Serialize)]
+#[derive(Default, Serialize, Deserialize)]
 struct ImplLcom4<Obs = Unaggregated>(Obs);
 
 impl<Obs: Observer> Visit<'_> for ImplLcom4<Obs> {
@@ -50,15 +50,29 @@ impl<Obs: Observer> Visit<'_> for ImplLcom4<Obs> {
             neighbours.entry(func2).and_modify(|x| x.push(func1));
         }
 
-        self.0.observe(num_components(neighbours));
+        self.0.observe_at(num_components(neighbours), || {
+            let name = match i.self_ty.as_ref() {
+                syn::Type::Path(path) => path.path.segments.last().map(|s| s.ident.to_string()),
+                _ => None,
+            };
+            Location::of(name, i)
+        });
     }
 }
 
+const SCHEMA: &[MetricSchema] = &[MetricSchema::new(
+    "",
+    Scope::Type,
+    Unit::Count,
+    "Lack of cohesion of methods (LCOM4) of an impl block: the number of groups of methods not sharing fields or calls",
+)];
+
 pub fn make_collector<
-    Obs: Observer + Default + Serialize + Clone + Monoid + Send + Sync + 'static,
+    Obs: Observer + Default + Serialize + DeserializeOwned + Clone + Monoid + Send + Sync + 'static,
 >() -> MetricCollectorBox {
     util::VisitorCollector::new(
         "lcom4_per_impl_block",
+        SCHEMA,
         ImplLcom4::<Obs>::default(),
         |v| v.0,
         |v| Monoid::reduce(v.iter().cloned()),
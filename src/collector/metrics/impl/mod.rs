@@ -1,10 +1,13 @@
-use prelude::util::Unaggregated;
+use prelude::util::{Hist, Located, Monoid, Observer, ObserverKind, Unaggregated};
+use serde::{de::DeserializeOwned, Serialize};
 
 use super::MetricCollectorBox;
 
 mod prelude {
+    pub use crate::collector::metrics::schema::{MetricSchema, Scope, Unit};
+    pub use crate::collector::metrics::util::{Node, NodeVisitor, Walk, WalkContext};
     pub use crate::collector::metrics::{util, MetricCollector, MetricCollectorBox};
-    pub use serde::Serialize;
+    pub use serde::{de::DeserializeOwned, Deserialize, Serialize};
     pub use syn::visit::Visit;
 }
 
@@ -29,18 +32,28 @@ mod macros;
 mod methods;
 mod stmt_size;
 
-pub fn get_metric_collectors() -> Vec<MetricCollectorBox> {
+pub fn get_metric_collectors(observer: ObserverKind) -> Vec<MetricCollectorBox> {
+    match observer {
+        ObserverKind::Unaggregated => get_metric_collectors_with::<Unaggregated>(),
+        ObserverKind::Located => get_metric_collectors_with::<Located>(),
+        ObserverKind::Histogram => get_metric_collectors_with::<Hist>(),
+    }
+}
+
+fn get_metric_collectors_with<
+    Obs: Observer + Default + Serialize + DeserializeOwned + Clone + Monoid + Send + Sync + 'static,
+>() -> Vec<MetricCollectorBox> {
     collectors![
-        fn_depth::make_collector::<Unaggregated>(),
+        fn_depth::make_collector::<Obs>(),
         if_count::make_collector(),
-        fn_arg_count::make_collector::<Unaggregated>(),
-        basic_structs::make_collector::<Unaggregated>(),
-        basic_enums::make_collector::<Unaggregated>(),
-        basic_traits::make_collector::<Unaggregated>(),
-        complexity::make_collector::<Unaggregated>(),
-        stmt_size::make_collector::<Unaggregated>(),
-        basic_files::make_collector::<Unaggregated>(),
-        methods::make_collector::<Unaggregated>(),
-        macros::make_collector::<Unaggregated>(),
+        fn_arg_count::make_collector::<Obs>(),
+        basic_structs::make_collector::<Obs>(),
+        basic_enums::make_collector::<Obs>(),
+        basic_traits::make_collector::<Obs>(),
+        complexity::make_collector::<Obs>(),
+        stmt_size::make_collector::<Obs>(),
+        basic_files::make_collector::<Obs>(),
+        methods::make_collector::<Obs>(),
+        macros::make_collector::<Obs>(),
     ]
 }
@@ -1,7 +1,7 @@
 use syn::visit::{self, Visit};
 
 use super::prelude::{util::Observer, *};
-use util::{Monoid, Unaggregated};
+use util::{Location, Monoid, Unaggregated};
 
 #[derive(Default)]
 pub struct StatementSize<Obs = Unaggregated> {
@@ -22,19 +22,28 @@ impl<Obs: Observer> Visit<'_> for StatementSize<Obs> {
 
         visit::visit_stmt(self, i);
 
-        self.hist.observe(self.expr_count);
+        self.hist
+            .observe_at(self.expr_count, || Location::of(None, i));
         self.expr_count = old_expr_count;
     }
 }
 
+const SCHEMA: &[MetricSchema] = &[MetricSchema::new(
+    "",
+    Scope::Statement,
+    Unit::Count,
+    "Number of expressions in a statement",
+)];
+
 pub fn make_collector<
-    Obs: Observer + Default + Serialize + Clone + Monoid + Send + Sync + 'static,
+    Obs: Observer + Default + Serialize + DeserializeOwned + Clone + Monoid + Send + Sync + 'static,
 >() -> MetricCollectorBox {
     util::VisitorCollector::new(
         "statement_size",
+        SCHEMA,
         StatementSize::<Obs>::default(),
-        |v| v,
-        |v: &[StatementSize<Obs>]| Monoid::reduce(v.iter().map(|v| v.hist.to_owned())),
+        |v| v.hist,
+        |v| Monoid::reduce(v.iter().cloned()),
     )
     .make_box()
 }
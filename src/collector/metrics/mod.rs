@@ -1,9 +1,26 @@
 mod r#impl;
+pub mod query;
+pub mod schema;
 pub mod util;
 
-use rayon::prelude::*;
-use serde::Serialize;
-use tracing::{info_span, Span};
+use anyhow::{Context, Result};
+use schema::MetricSchema;
+use serde::{de::DeserializeOwned, Serialize};
+use std::any::Any;
+use std::borrow::Cow;
+use std::sync::Arc;
+use util::{walk_file, MacroCoverage, Node, NodeVisitor, Walk, WalkContext};
+
+/// A type-erased result of running a collector on a single file
+///
+/// Can only be aggregated by the collector that produced it.
+pub type FileMetric = Arc<dyn Any + Send + Sync>;
+
+/// The state of a collector while walking a single file
+pub trait FileVisitor<M>: NodeVisitor {
+    /// The metric of the file, after the walk is done
+    fn finish(self: Box<Self>) -> M;
+}
 
 /// A type-erased metric collector
 pub struct MetricCollectorBox(Box<dyn MetricCollectorBoxed + Send + Sync + 'static>);
@@ -13,8 +30,26 @@ impl MetricCollectorBox {
         self.0.name()
     }
 
-    pub fn collect_metric(&self, files: &[FileAst]) -> serde_json::Value {
-        self.0.collect_metric(files)
+    pub fn schema(&self) -> Cow<'static, [MetricSchema]> {
+        self.0.schema()
+    }
+
+    pub fn version(&self) -> u32 {
+        self.0.version()
+    }
+
+    /// Serializes a metric of a file, which must come from this collector, for caching
+    pub fn encode(&self, metric: &FileMetric) -> Result<Vec<u8>> {
+        self.0.encode_erased(metric)
+    }
+
+    pub fn decode(&self, bytes: &[u8]) -> Result<FileMetric> {
+        self.0.decode_erased(bytes)
+    }
+
+    /// Aggregate the per-file results, which must come from `collect_file` with this collector
+    pub fn aggregate(&self, metrics: &[FileMetric]) -> serde_json::Value {
+        self.0.aggregate_erased(metrics)
     }
 
     pub fn make_box(self) -> Self {
@@ -22,13 +57,62 @@ impl MetricCollectorBox {
     }
 }
 
+/// Runs all the collectors on a file, in a single walk over its syntax tree
+///
+/// Counts the macro coverage of the file along the way, if asked to.
+/// Returns the metrics in the same order as the collectors.
+pub fn collect_file(
+    collectors: &[MetricCollectorBox],
+    file: &FileAst,
+    macros: Option<&mut MacroCoverage>,
+) -> Vec<FileMetric> {
+    let mut visitors = collectors
+        .iter()
+        .map(|c| c.0.file_visitor_erased())
+        .collect::<Vec<_>>();
+    let mut nodes = visitors
+        .iter_mut()
+        .map(|v| &mut **v as &mut dyn NodeVisitor)
+        .collect::<Vec<_>>();
+    if let Some(macros) = macros {
+        nodes.push(macros);
+    }
+    walk_file(&file.content, &mut nodes);
+
+    visitors.into_iter().map(|v| v.finish()).collect()
+}
+
+/// Serializes a type-erased metric of a file, `M` must be its actual type
+pub fn encode_metric<M: Serialize + 'static>(metric: &FileMetric) -> Result<Vec<u8>> {
+    let metric = metric
+        .downcast_ref::<M>()
+        .expect("BUG: file metric from a different collector");
+    bincode::serialize(metric).context("Serializing file metric")
+}
+
+pub fn decode_metric<M: DeserializeOwned + Send + Sync + 'static>(
+    bytes: &[u8],
+) -> Result<FileMetric> {
+    let metric: M = bincode::deserialize(bytes).context("Deserializing file metric")?;
+    Ok(Arc::new(metric))
+}
+
 pub trait MetricCollector: Sized + Send + Sync + 'static {
-    type Metric: Send;
+    type Metric: Clone + Send + Sync + Serialize + DeserializeOwned;
     type AggregatedMetric: Serialize;
 
     fn name(&self) -> &'static str;
 
-    fn collect_file(&self, file: &FileAst) -> Self::Metric;
+    /// The metrics this collector produces
+    fn schema(&self) -> Cow<'static, [MetricSchema]>;
+
+    /// Has to be bumped whenever the metric of a file changes, so that the cached ones are not used
+    fn version(&self) -> u32 {
+        1
+    }
+
+    /// A fresh visitor for a file, it walks the file together with the visitors of the other collectors
+    fn file_visitor(&self) -> Box<dyn FileVisitor<Self::Metric> + '_>;
 
     fn aggregate_metrics(&self, metric: &[Self::Metric]) -> Self::AggregatedMetric;
 
@@ -37,11 +121,40 @@ pub trait MetricCollector: Sized + Send + Sync + 'static {
     }
 }
 
+/// Wraps the metric of a file visitor into a `FileMetric`
+struct ErasedFileVisitor<'a, M>(Box<dyn FileVisitor<M> + 'a>);
+
+impl<M> NodeVisitor for ErasedFileVisitor<'_, M> {
+    fn enter(&mut self, node: Node<'_>, cx: &WalkContext) -> Walk {
+        self.0.enter(node, cx)
+    }
+
+    fn exit(&mut self, node: Node<'_>) {
+        self.0.exit(node)
+    }
+}
+
+impl<M: Send + Sync + 'static> FileVisitor<FileMetric> for ErasedFileVisitor<'_, M> {
+    fn finish(self: Box<Self>) -> FileMetric {
+        Arc::new(self.0.finish())
+    }
+}
+
 // An object-safe wrapper for MetricCollector
 trait MetricCollectorBoxed {
     fn name(&self) -> &'static str;
 
-    fn collect_metric(&self, files: &[FileAst]) -> serde_json::Value;
+    fn schema(&self) -> Cow<'static, [MetricSchema]>;
+
+    fn version(&self) -> u32;
+
+    fn file_visitor_erased(&self) -> Box<dyn FileVisitor<FileMetric> + '_>;
+
+    fn encode_erased(&self, metric: &FileMetric) -> Result<Vec<u8>>;
+
+    fn decode_erased(&self, bytes: &[u8]) -> Result<FileMetric>;
+
+    fn aggregate_erased(&self, metrics: &[FileMetric]) -> serde_json::Value;
 }
 
 impl<M: Serialize, C: MetricCollector<AggregatedMetric = M>> MetricCollectorBoxed for C {
@@ -49,17 +162,33 @@ impl<M: Serialize, C: MetricCollector<AggregatedMetric = M>> MetricCollectorBoxe
         C::name(self)
     }
 
-    fn collect_metric(&self, files: &[FileAst]) -> serde_json::Value {
-        let span = Span::current();
+    fn schema(&self) -> Cow<'static, [MetricSchema]> {
+        C::schema(self)
+    }
+
+    fn version(&self) -> u32 {
+        C::version(self)
+    }
+
+    fn file_visitor_erased(&self) -> Box<dyn FileVisitor<FileMetric> + '_> {
+        Box::new(ErasedFileVisitor(self.file_visitor()))
+    }
 
-        let metrics = files
-            .par_iter()
-            .map(|file| {
-                let _span =
-                    info_span!(parent: span.id(), "collect_file", file = %file.path).entered();
-                let result = self.collect_file(file);
+    fn encode_erased(&self, metric: &FileMetric) -> Result<Vec<u8>> {
+        encode_metric::<C::Metric>(metric)
+    }
+
+    fn decode_erased(&self, bytes: &[u8]) -> Result<FileMetric> {
+        decode_metric::<C::Metric>(bytes)
+    }
 
-                result
+    fn aggregate_erased(&self, metrics: &[FileMetric]) -> serde_json::Value {
+        let metrics = metrics
+            .iter()
+            .map(|m| {
+                m.downcast_ref::<C::Metric>()
+                    .expect("BUG: file metric from a different collector")
+                    .clone()
             })
             .collect::<Vec<_>>();
         let metric = self.aggregate_metrics(&metrics);
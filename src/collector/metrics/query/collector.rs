@@ -0,0 +1,301 @@
+use super::Query;
+use crate::collector::metrics::schema::{MetricSchema, Scope, Unit};
+use crate::collector::metrics::util::{
+    Hist, Located, Location, Monoid, Node, NodeVisitor, Observer, ObserverKind, Unaggregated, Walk,
+    WalkContext,
+};
+use crate::collector::metrics::{FileVisitor, MetricCollector, MetricCollectorBox};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::any::{Any, TypeId};
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::hash::{Hash, Hasher};
+use std::marker::PhantomData;
+
+/// The name of the collector running the queries, their metrics are `queries.<name>`
+pub const QUERIES_COLLECTOR: &str = "queries";
+
+/// A metric counting the nodes matching a query, `[queries.<name>]` in the config
+///
+/// ```toml
+/// [queries.unwrap_calls]
+/// query = '(ExprMethodCall method: "unwrap")'
+/// per = "fn"
+/// description = "Number of `.unwrap()` calls in a function"
+/// ```
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct QueryMetric {
+    pub query: Query,
+    #[serde(default)]
+    pub per: QueryScope,
+    /// The query itself is used if not set
+    pub description: Option<String>,
+}
+
+/// What the matches of a query are counted in
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum QueryScope {
+    /// one observation per file
+    #[default]
+    File,
+    /// one observation per function or method with a body, the matches outside of functions are not counted
+    Fn,
+}
+
+/// Has to be bumped whenever the same query gives different counts
+const VERSION: u64 = 1;
+
+struct QueryCollector<Obs> {
+    queries: Vec<(String, QueryMetric)>,
+    phantom: PhantomData<Obs>,
+}
+
+impl<Obs: Observer + Default + Clone + Monoid + Send + Sync + 'static> MetricCollector
+    for QueryCollector<Obs>
+where
+    Obs: Serialize + DeserializeOwned,
+{
+    /// the observations of every query, in the order of `queries`
+    type Metric = Vec<Obs>;
+    type AggregatedMetric = BTreeMap<String, Obs>;
+
+    fn name(&self) -> &'static str {
+        QUERIES_COLLECTOR
+    }
+
+    fn schema(&self) -> Cow<'static, [MetricSchema]> {
+        self.queries
+            .iter()
+            .map(|(name, metric)| MetricSchema {
+                name: name.clone().into(),
+                scope: match metric.per {
+                    QueryScope::File => Scope::File,
+                    QueryScope::Fn => Scope::Function,
+                },
+                unit: Unit::Count,
+                description: match &metric.description {
+                    Some(description) => description.clone().into(),
+                    None => format!("Number of `{}`", metric.query.source()).into(),
+                },
+            })
+            .collect()
+    }
+
+    /// The cached metrics can only be used with the same queries
+    fn version(&self) -> u32 {
+        let mut hasher = rustc_hash::FxHasher::default();
+        VERSION.hash(&mut hasher);
+        for (name, metric) in &self.queries {
+            (name, metric.query.source(), metric.per).hash(&mut hasher);
+        }
+        hasher.finish() as u32
+    }
+
+    fn file_visitor(&self) -> Box<dyn FileVisitor<Self::Metric> + '_> {
+        Box::new(QueryVisitor {
+            queries: &self.queries,
+            observers: vec![Obs::default(); self.queries.len()],
+            file_counts: vec![0; self.queries.len()],
+            fn_counts: vec![],
+            entered: vec![],
+            last: None,
+        })
+    }
+
+    fn aggregate_metrics(&self, metrics: &[Self::Metric]) -> Self::AggregatedMetric {
+        self.queries
+            .iter()
+            .enumerate()
+            .map(|(i, (name, _))| {
+                let observer = Monoid::reduce(metrics.iter().map(|m| m[i].clone()));
+                (name.clone(), observer)
+            })
+            .collect()
+    }
+}
+
+pub fn make_collector(
+    queries: &BTreeMap<String, QueryMetric>,
+    observer: ObserverKind,
+) -> MetricCollectorBox {
+    fn make<Obs>(queries: &BTreeMap<String, QueryMetric>) -> QueryCollector<Obs> {
+        QueryCollector {
+            queries: queries
+                .iter()
+                .map(|(name, metric)| (name.clone(), metric.clone()))
+                .collect(),
+            phantom: PhantomData,
+        }
+    }
+
+    match observer {
+        ObserverKind::Unaggregated => make::<Unaggregated>(queries).make_box(),
+        ObserverKind::Located => make::<Located>(queries).make_box(),
+        ObserverKind::Histogram => make::<Hist>(queries).make_box(),
+    }
+}
+
+/// Runs the queries on every node of a file, as the shared walk enters them
+struct QueryVisitor<'q, Obs> {
+    queries: &'q [(String, QueryMetric)],
+    observers: Vec<Obs>,
+    /// the matches in the file, for every query
+    file_counts: Vec<usize>,
+    /// the matches in the functions the walk is in, for every query
+    fn_counts: Vec<Vec<usize>>,
+    /// for every node the walk is in, the function it starts, if it's the first of the nodes wrapping a function
+    entered: Vec<Option<Location>>,
+    /// the innermost node of the last entered node, if nothing was exited since then
+    last: Option<(*const (), TypeId)>,
+}
+
+/// The location of a function with a body
+fn function_location(node: &dyn Any) -> Option<Location> {
+    if let Some(i) = node.downcast_ref::<syn::ItemFn>() {
+        return Some(Location::of(Some(i.sig.ident.to_string()), i));
+    }
+    if let Some(i) = node.downcast_ref::<syn::ImplItemFn>() {
+        return Some(Location::of(Some(i.sig.ident.to_string()), i));
+    }
+    match node.downcast_ref::<syn::TraitItemFn>() {
+        Some(i) if i.default.is_some() => Some(Location::of(Some(i.sig.ident.to_string()), i)),
+        _ => None,
+    }
+}
+
+impl<Obs: Observer> NodeVisitor for QueryVisitor<'_, Obs> {
+    fn enter(&mut self, node: Node<'_>, _cx: &WalkContext) -> Walk {
+        let node = node.reflect();
+        // the variants wrapping a node (like `Stmt::Item` and `Item::Fn` of an `ItemFn`) are the same node,
+        // which is matched only once, as the outermost of them
+        let any = node.as_any();
+        let innermost = (any as *const dyn Any as *const (), any.type_id());
+        if self.last.replace(innermost) == Some(innermost) {
+            self.entered.push(None);
+            return Walk::Continue;
+        }
+
+        let function = function_location(any);
+        if function.is_some() {
+            self.fn_counts.push(vec![0; self.queries.len()]);
+        }
+        self.entered.push(function);
+
+        for (i, (_, metric)) in self.queries.iter().enumerate() {
+            if !metric.query.matches(node) {
+                continue;
+            }
+            match metric.per {
+                QueryScope::File => self.file_counts[i] += 1,
+                QueryScope::Fn => {
+                    if let Some(counts) = self.fn_counts.last_mut() {
+                        counts[i] += 1;
+                    }
+                }
+            }
+        }
+        Walk::Continue
+    }
+
+    fn exit(&mut self, node: Node<'_>) {
+        self.last = None;
+        if let Some(location) = self.entered.pop().flatten() {
+            let counts = self.fn_counts.pop().unwrap();
+            for (i, (_, metric)) in self.queries.iter().enumerate() {
+                if metric.per == QueryScope::Fn {
+                    self.observers[i].observe_at(counts[i], || location.clone());
+                }
+            }
+        }
+
+        if let Node::File(file) = node {
+            let location = || Location::of(None, file);
+            for (i, (_, metric)) in self.queries.iter().enumerate() {
+                if metric.per == QueryScope::File {
+                    self.observers[i]
+                        .observe_at(std::mem::take(&mut self.file_counts[i]), location);
+                }
+            }
+        }
+    }
+}
+
+impl<Obs: Observer> FileVisitor<Vec<Obs>> for QueryVisitor<'_, Obs> {
+    fn finish(self: Box<Self>) -> Vec<Obs> {
+        self.observers
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::collector::{Analyzer, CollectConfig, FileText};
+
+    #[test]
+    fn per_file_and_fn() {
+        let config: CollectConfig = toml::from_str(
+            r#"
+            collectors = ["queries"]
+
+            [queries.unwrap_calls]
+            query = '(ExprMethodCall method: "unwrap")'
+            per = "fn"
+
+            [queries.arc_mutex]
+            query = '(Field ty: _ @ty (#match? @ty "^Arc<Mutex<"))'
+            "#,
+        )
+        .unwrap();
+        config.validate().unwrap();
+        let code = r#"
+            struct A { a: Arc<Mutex<u32>> }
+            const B: u32 = C.unwrap();
+            fn foo() {
+                a.unwrap();
+                assert_eq!(b.unwrap(), 1);
+            }
+            impl A {
+                fn bar(&self) {
+                    fn baz() { c.unwrap() }
+                }
+            }
+        "#;
+        let result = Analyzer::new(&config)
+            .collect_files(vec![
+                FileText::new("src/lib.rs", code.into()),
+                FileText::new("src/main.rs", "fn main() {}".into()),
+            ])
+            .unwrap();
+        // functions are observed on the exit, so `baz` comes before `bar`
+        assert_eq!(
+            result.values("queries.unwrap_calls"),
+            Some(vec![2.0, 1.0, 0.0, 0.0])
+        );
+        assert_eq!(result.values("queries.arc_mutex"), Some(vec![1.0, 0.0]));
+    }
+
+    /// `Stmt::Item`, `Item::Fn` and `ItemFn` of a nested function are entered one after another, but matched once
+    #[test]
+    fn wrapped_nodes() {
+        let config: CollectConfig = toml::from_str(
+            r#"
+            collectors = ["queries"]
+
+            [queries.items]
+            query = '(Item)'
+
+            [queries.fns]
+            query = '(ItemFn)'
+            per = "fn"
+            "#,
+        )
+        .unwrap();
+        let code = "fn a() { fn b() {} let c = 1; }";
+        let result = Analyzer::new(&config)
+            .collect_files(vec![FileText::new("src/lib.rs", code.into())])
+            .unwrap();
+        assert_eq!(result.values("queries.items"), Some(vec![2.0]));
+        assert_eq!(result.values("queries.fns"), Some(vec![1.0, 1.0]));
+    }
+}
@@ -0,0 +1,332 @@
+//! Metrics defined in the config, as the number of syntax tree nodes matching a query
+
+mod collector;
+mod parse;
+pub mod reflect;
+
+use anyhow::Result;
+use parse::{Arg, Atom, Field, Op, Parsed, Pattern, Predicate};
+use reflect::{Reflect, Value};
+use serde::{de::Error as _, Deserialize, Deserializer};
+use std::fmt;
+
+pub use collector::{make_collector, QueryMetric, QueryScope, QUERIES_COLLECTOR};
+
+/// A pattern over the syntax tree, in a language modelled after the tree-sitter queries
+///
+/// ```text
+/// (ExprMatch arms: _* @arms (#gt? @arms 10))
+/// ```
+///
+/// - `(Kind field: pattern ...)` matches a node of the kind whose fields match the patterns. The kinds and the fields
+///   are the ones of the syn types, as described in `syn.json`. Enum variants can be used as kinds (`Expr::If`), and
+///   the variants that wrap a node are the same node: `(Expr)` and `(ExprIf)` both match an `if` expression.
+///   `(_ ...)` matches a node of any kind.
+/// - `!field` requires the field to be missing or empty.
+/// - `_` matches any value that is there, `"text"` matches a value with exactly this text (like `"unwrap"` for an
+///   identifier), and `[pattern ...]` matches any of the patterns.
+/// - A pattern matches a list (like `arms` or `attrs`) if any of its elements matches. With a `*` after the pattern,
+///   all of them have to match instead.
+/// - `@name` after a pattern captures the value it matched (the whole list with `*`) for the predicates:
+///   `(#eq? @a "text")`, `(#not-eq? @a @b)`, `(#match? @a "regex")` and `(#not-match? @a "regex")` compare the text,
+///   while `(#gt? @a 10)`, `#ge?`, `#lt?` and `#le?` compare the number of elements of a list, or a number.
+/// - `;` starts a comment.
+///
+/// The text of a value is its source with the whitespace removed, except between words: `Arc<Mutex<u32>>`.
+#[derive(Clone)]
+pub struct Query {
+    source: String,
+    parsed: Parsed,
+}
+
+impl Query {
+    pub fn parse(source: &str) -> Result<Self> {
+        Ok(Self {
+            source: source.to_string(),
+            parsed: parse::parse(source)?,
+        })
+    }
+
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    fn matches(&self, node: &dyn Reflect) -> bool {
+        let mut captures = vec![None; self.parsed.captures.len()];
+        self.parsed
+            .pattern
+            .matches(Value::Node(node), &mut captures, &mut |captures| {
+                self.parsed.predicates.iter().all(|p| p.holds(captures))
+            })
+    }
+}
+
+impl fmt::Debug for Query {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Query").field(&self.source).finish()
+    }
+}
+
+impl<'de> Deserialize<'de> for Query {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let source = String::deserialize(deserializer)?;
+        Query::parse(&source).map_err(|e| D::Error::custom(format!("{:#}", e)))
+    }
+}
+
+/// The values of the captures, `None` for the ones not captured (yet)
+type Captures<'a> = Vec<Option<Value<'a>>>;
+
+/// Called with the captures once a pattern has matched, tells whether the rest of the query matches too
+///
+/// This is what makes the matching backtrack: when the rest doesn't match, the next element of a list is tried.
+type Continuation<'k, 'a> = &'k mut dyn FnMut(&mut Captures<'a>) -> bool;
+
+/// Sets the capture for the continuation
+fn bind<'a>(
+    capture: Option<usize>,
+    value: Value<'a>,
+    captures: &mut Captures<'a>,
+    rest: Continuation<'_, 'a>,
+) -> bool {
+    let Some(index) = capture else {
+        return rest(captures);
+    };
+    let previous = captures[index].replace(value);
+    let matched = rest(captures);
+    captures[index] = previous;
+    matched
+}
+
+impl Pattern {
+    fn matches<'a>(
+        &self,
+        value: Value<'a>,
+        captures: &mut Captures<'a>,
+        rest: Continuation<'_, 'a>,
+    ) -> bool {
+        if self.all {
+            let elements = value.clone().into_elements();
+            return bind(self.capture, value, captures, &mut |captures| {
+                self.matches_all(&elements, captures, rest)
+            });
+        }
+        match value {
+            Value::List(elements) => elements
+                .into_iter()
+                .any(|element| self.matches_one(element, captures, rest)),
+            value => self.matches_one(value, captures, rest),
+        }
+    }
+
+    fn matches_one<'a>(
+        &self,
+        value: Value<'a>,
+        captures: &mut Captures<'a>,
+        rest: Continuation<'_, 'a>,
+    ) -> bool {
+        bind(self.capture, value.clone(), captures, &mut |captures| {
+            self.atom.matches(value.clone(), captures, rest)
+        })
+    }
+
+    fn matches_all<'a>(
+        &self,
+        elements: &[Value<'a>],
+        captures: &mut Captures<'a>,
+        rest: Continuation<'_, 'a>,
+    ) -> bool {
+        match elements.split_first() {
+            None => rest(captures),
+            Some((first, others)) => self.atom.matches(first.clone(), captures, &mut |captures| {
+                self.matches_all(others, captures, rest)
+            }),
+        }
+    }
+}
+
+impl Atom {
+    fn matches<'a>(
+        &self,
+        value: Value<'a>,
+        captures: &mut Captures<'a>,
+        rest: Continuation<'_, 'a>,
+    ) -> bool {
+        match self {
+            Atom::Any => !value.is_empty() && rest(captures),
+            Atom::Text(text) => value.text() == *text && rest(captures),
+            Atom::Alt(alternatives) => alternatives
+                .iter()
+                .any(|pattern| pattern.matches(value.clone(), captures, rest)),
+            Atom::Node { kind, fields } => match value {
+                Value::Node(node) if kind.as_deref().is_none_or(|kind| node.is(kind)) => {
+                    matches_fields(fields, node, captures, rest)
+                }
+                _ => false,
+            },
+        }
+    }
+}
+
+fn matches_fields<'a>(
+    fields: &[Field],
+    node: &'a dyn Reflect,
+    captures: &mut Captures<'a>,
+    rest: Continuation<'_, 'a>,
+) -> bool {
+    let Some((first, others)) = fields.split_first() else {
+        return rest(captures);
+    };
+    match first {
+        Field::Absent(name) => {
+            node.field(name).is_none_or(|value| value.is_empty())
+                && matches_fields(others, node, captures, rest)
+        }
+        Field::Matches(name, pattern) => match node.field(name) {
+            Some(value) => pattern.matches(value, captures, &mut |captures| {
+                matches_fields(others, node, captures, rest)
+            }),
+            None => false,
+        },
+    }
+}
+
+impl Predicate {
+    /// Predicates on the values that are not captured, or are not numbers for the numeric ones, don't hold
+    fn holds(&self, captures: &Captures<'_>) -> bool {
+        let Some(value) = &captures[self.capture] else {
+            return false;
+        };
+        let holds = match (&self.op, &self.arg) {
+            (Op::Match(regex), _) => regex.is_match(&value.text()),
+            (Op::Eq, Arg::Text(text)) => value.text() == *text,
+            (Op::Eq, Arg::Capture(other)) => captures[*other]
+                .as_ref()
+                .is_some_and(|other| other.text() == value.text()),
+            (op, arg) => {
+                let other = match arg {
+                    Arg::Number(number) => Some(*number),
+                    Arg::Capture(other) => captures[*other].as_ref().and_then(Value::number),
+                    Arg::Text(_) => None,
+                };
+                let (Some(a), Some(b)) = (value.number(), other) else {
+                    return false;
+                };
+                match op {
+                    Op::Eq => a == b,
+                    Op::Gt => a > b,
+                    Op::Ge => a >= b,
+                    Op::Lt => a < b,
+                    Op::Le => a <= b,
+                    Op::Match(_) => unreachable!(),
+                }
+            }
+        };
+        holds != self.negated
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::reflect::{Reflect, Value};
+    use super::Query;
+    use expect_test::{expect, Expect};
+
+    fn count(query: &str, code: &str) -> usize {
+        fn walk(query: &Query, node: &dyn Reflect, count: &mut usize) {
+            if query.matches(node) {
+                *count += 1;
+            }
+            node.children(&mut |child| walk(query, child, count));
+        }
+
+        let query = Query::parse(query).unwrap();
+        let file = syn::parse_file(code).unwrap();
+        let mut count = 0;
+        walk(&query, &file, &mut count);
+        count
+    }
+
+    #[test]
+    fn matching() {
+        let code = r#"
+            struct A {
+                a: Arc<Mutex<Vec<u8>>>,
+                b: Arc<u8>,
+            }
+            fn foo(x: Option<u32>) -> u32 {
+                let y = x.unwrap() + 1;
+                match x {
+                    Some(1) => 1,
+                    Some(z) if z > 10 => z.unwrap_or(2),
+                    _ => x.unwrap(),
+                }
+            }
+        "#;
+        assert_eq!(count(r#"(ExprMethodCall method: "unwrap")"#, code), 2);
+        assert_eq!(
+            count(r#"(Field ty: _ @ty (#match? @ty "^Arc<Mutex<"))"#, code),
+            1
+        );
+        assert_eq!(
+            count(
+                r#"(Field ty: (TypePath path: (Path segments: (PathSegment ident: "Arc"))))"#,
+                code
+            ),
+            2
+        );
+        assert_eq!(count("(ExprMatch arms: _* @arms (#gt? @arms 2))", code), 1);
+        assert_eq!(count("(ExprMatch arms: _* @arms (#gt? @arms 3))", code), 0);
+        assert_eq!(count("(Arm !guard)", code), 2);
+        assert_eq!(count("(ExprMatch arms: (Arm guard: _)*)", code), 0);
+        // enum variants and the nodes they wrap are the same node
+        assert_eq!(count("(Expr::MethodCall)", code), 3);
+        assert_eq!(count("(Expr)", code), 15);
+        assert_eq!(count("(ExprBinary op: (BinOp::Add))", code), 1);
+        assert_eq!(count("[(ExprBinary) (Pat::Wild)]", code), 3);
+        assert_eq!(count("(Signature output: (TypePath))", code), 1);
+        assert_eq!(
+            count(
+                "(ExprBinary left: _ @left right: _ @right (#not-eq? @left @right))",
+                code
+            ),
+            2
+        );
+    }
+
+    #[test]
+    fn text() {
+        let ty: syn::Type = syn::parse_quote!(&'a mut Arc<Mutex<dyn Fn(u32) -> u32 + Send>>);
+        let text = Value::Node(&ty).text();
+        assert_eq!(text, "&'a mut Arc<Mutex<dyn Fn(u32)->u32+Send>>");
+    }
+
+    fn check_error(query: &str, expect: Expect) {
+        let error = Query::parse(query).unwrap_err();
+        expect.assert_eq(&format!("{:#}", error));
+    }
+
+    #[test]
+    fn errors() {
+        check_error(
+            "(ExprMethodCal)",
+            expect!["Invalid query at 1:15: unknown node kind `ExprMethodCal`"],
+        );
+        check_error(
+            "(ExprMethodCall name: _)",
+            expect!["Invalid query at 1:21: `ExprMethodCall` has no field `name`, expected one of: args, attrs, dot_token, method, paren_token, receiver, turbofish"],
+        );
+        check_error(
+            "(ExprMatch arms: _\n  (#gt? @arms 10)",
+            expect!["Invalid query at 2:18: expected a field name, found the end of the query"],
+        );
+        check_error(
+            "(ExprMatch (#gt? @arms 10))",
+            expect!["`@arms` is used in a predicate, but is not captured"],
+        );
+        check_error(
+            r#"(Lit) @a (#match? @a "(")"#,
+            expect!["Invalid query at 1:10: expected the end of the query, found `(`"],
+        );
+    }
+}
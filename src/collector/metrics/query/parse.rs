@@ -0,0 +1,342 @@
+//! The syntax of the queries, see `Query` for the description
+
+use super::reflect::{all_fields, fields_of};
+use anyhow::{bail, Context, Result};
+use regex::Regex;
+
+#[derive(Debug, Clone)]
+pub struct Pattern {
+    pub atom: Atom,
+    /// `*`: all the elements of a list have to match, instead of any of them
+    pub all: bool,
+    pub capture: Option<usize>,
+}
+
+#[derive(Debug, Clone)]
+pub enum Atom {
+    /// `_`
+    Any,
+    /// `"text"`
+    Text(String),
+    /// `(Kind field: pattern !field)`, the kind is `None` for `(_ ...)`
+    Node {
+        kind: Option<String>,
+        fields: Vec<Field>,
+    },
+    /// `[pattern pattern]`
+    Alt(Vec<Pattern>),
+}
+
+#[derive(Debug, Clone)]
+pub enum Field {
+    Matches(String, Pattern),
+    /// `!field`: missing or empty
+    Absent(String),
+}
+
+/// `(#op? @capture arg)`
+#[derive(Debug, Clone)]
+pub struct Predicate {
+    pub op: Op,
+    /// `#not-eq?` and `#not-match?`
+    pub negated: bool,
+    pub capture: usize,
+    pub arg: Arg,
+}
+
+#[derive(Debug, Clone)]
+pub enum Op {
+    Eq,
+    Match(Regex),
+    Gt,
+    Ge,
+    Lt,
+    Le,
+}
+
+#[derive(Debug, Clone)]
+pub enum Arg {
+    Capture(usize),
+    Text(String),
+    Number(f64),
+}
+
+/// A parsed query, with the captures numbered in the order they are first mentioned
+#[derive(Debug, Clone)]
+pub struct Parsed {
+    pub pattern: Pattern,
+    pub predicates: Vec<Predicate>,
+    pub captures: Vec<String>,
+}
+
+pub fn parse(source: &str) -> Result<Parsed> {
+    let mut parser = Parser {
+        source,
+        pos: 0,
+        captures: vec![],
+        bound: vec![],
+        predicates: vec![],
+    };
+    let pattern = parser.pattern().map_err(|e| parser.located(e))?;
+    parser.skip_whitespace();
+    if parser.pos != source.len() {
+        return Err(parser.located(anyhow::anyhow!(
+            "expected the end of the query, found {}",
+            parser.found()
+        )));
+    }
+    if let Some(unbound) = (0..parser.captures.len()).find(|i| !parser.bound.contains(i)) {
+        bail!(
+            "`@{}` is used in a predicate, but is not captured",
+            parser.captures[unbound]
+        );
+    }
+    Ok(Parsed {
+        pattern,
+        predicates: parser.predicates,
+        captures: parser.captures,
+    })
+}
+
+struct Parser<'a> {
+    source: &'a str,
+    pos: usize,
+    captures: Vec<String>,
+    /// the captures that are attached to a pattern
+    bound: Vec<usize>,
+    predicates: Vec<Predicate>,
+}
+
+impl<'a> Parser<'a> {
+    /// Adds the line and column of the current position to an error
+    fn located(&self, error: anyhow::Error) -> anyhow::Error {
+        let before = &self.source[..self.pos];
+        let line = before.matches('\n').count() + 1;
+        let column = before.len() - before.rfind('\n').map_or(0, |i| i + 1) + 1;
+        error.context(format!("Invalid query at {}:{}", line, column))
+    }
+
+    fn rest(&self) -> &'a str {
+        &self.source[self.pos..]
+    }
+
+    /// What is at the current position, for the errors
+    fn found(&self) -> String {
+        match self.rest().chars().next() {
+            Some(c) => format!("`{}`", c),
+            None => "the end of the query".to_string(),
+        }
+    }
+
+    /// Skips whitespace and `;` comments
+    fn skip_whitespace(&mut self) {
+        loop {
+            let rest = self.rest();
+            let trimmed = rest.trim_start();
+            self.pos += rest.len() - trimmed.len();
+            if !trimmed.starts_with(';') {
+                break;
+            }
+            self.pos += trimmed.find('\n').unwrap_or(trimmed.len());
+        }
+    }
+
+    /// Consumes `token` if it's next
+    fn eat(&mut self, token: &str) -> bool {
+        self.skip_whitespace();
+        if self.rest().starts_with(token) {
+            self.pos += token.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, token: &str) -> Result<()> {
+        if !self.eat(token) {
+            bail!("expected `{}`, found {}", token, self.found());
+        }
+        Ok(())
+    }
+
+    /// A name of a kind, field, capture or predicate
+    fn name(&mut self, what: &str) -> Result<&'a str> {
+        self.skip_whitespace();
+        let rest = self.rest();
+        let len = rest
+            .find(|c: char| !(c.is_alphanumeric() || matches!(c, '_' | '-' | '?' | '.')))
+            .unwrap_or(rest.len());
+        if len == 0 {
+            bail!("expected {}, found {}", what, self.found());
+        }
+        self.pos += len;
+        Ok(&rest[..len])
+    }
+
+    fn string(&mut self) -> Result<String> {
+        self.expect("\"")?;
+        let mut string = String::new();
+        let mut chars = self.rest().char_indices();
+        while let Some((i, c)) = chars.next() {
+            match c {
+                '"' => {
+                    self.pos += i + 1;
+                    return Ok(string);
+                }
+                '\\' => match chars.next() {
+                    Some((_, 'n')) => string.push('\n'),
+                    Some((_, c)) => string.push(c),
+                    None => break,
+                },
+                c => string.push(c),
+            }
+        }
+        bail!("unterminated string")
+    }
+
+    fn capture(&mut self) -> Result<usize> {
+        let name = self.name("a capture name")?.to_string();
+        Ok(match self.captures.iter().position(|c| *c == name) {
+            Some(index) => index,
+            None => {
+                self.captures.push(name);
+                self.captures.len() - 1
+            }
+        })
+    }
+
+    fn pattern(&mut self) -> Result<Pattern> {
+        let atom = if self.eat("(") {
+            self.node()?
+        } else if self.eat("[") {
+            let mut alternatives = vec![];
+            while !self.eat("]") {
+                alternatives.push(self.pattern()?);
+            }
+            if alternatives.is_empty() {
+                bail!("expected a pattern in `[]`");
+            }
+            Atom::Alt(alternatives)
+        } else if self.eat("_") {
+            Atom::Any
+        } else if self.rest().starts_with('"') {
+            Atom::Text(self.string()?)
+        } else {
+            bail!("expected a pattern, found {}", self.found());
+        };
+        let all = self.eat("*");
+        let capture = if self.eat("@") {
+            let capture = self.capture()?;
+            self.bound.push(capture);
+            Some(capture)
+        } else {
+            None
+        };
+        Ok(Pattern { atom, all, capture })
+    }
+
+    /// The rest of `(Kind ...)`, after the `(`
+    fn node(&mut self) -> Result<Atom> {
+        let kind = if self.eat("_") {
+            None
+        } else {
+            let mut kind = self.name("a node kind")?.to_string();
+            if self.rest().starts_with("::") {
+                self.pos += 2;
+                kind = format!("{}::{}", kind, self.name("an enum variant")?);
+            }
+            if fields_of(&kind).is_none() {
+                bail!("unknown node kind `{}`", kind);
+            }
+            Some(kind)
+        };
+
+        let mut fields = vec![];
+        while !self.eat(")") {
+            if self.eat("(") {
+                self.expect("#")?;
+                let predicate = self.predicate()?;
+                self.predicates.push(predicate);
+                continue;
+            }
+            let absent = self.eat("!");
+            let name = self.name("a field name")?.to_string();
+            self.check_field(kind.as_deref(), &name)?;
+            if absent {
+                fields.push(Field::Absent(name));
+            } else {
+                self.expect(":")?;
+                fields.push(Field::Matches(name, self.pattern()?));
+            }
+        }
+        Ok(Atom::Node { kind, fields })
+    }
+
+    fn check_field(&self, kind: Option<&str>, name: &str) -> Result<()> {
+        match kind {
+            Some(kind) => {
+                let fields = fields_of(kind).unwrap_or_default();
+                if !fields.contains(&name) {
+                    bail!(
+                        "`{}` has no field `{}`, expected one of: {}",
+                        kind,
+                        name,
+                        fields.join(", ")
+                    );
+                }
+            }
+            None => {
+                if !all_fields().any(|f| f == name) {
+                    bail!("no node has a field `{}`", name);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// The rest of `(#op? @capture arg)`, after the `#`
+    fn predicate(&mut self) -> Result<Predicate> {
+        let name = self.name("a predicate")?.to_string();
+        self.expect("@")?;
+        let capture = self.capture()?;
+        let arg = if self.eat("@") {
+            Arg::Capture(self.capture()?)
+        } else if self.rest().starts_with('"') {
+            Arg::Text(self.string()?)
+        } else {
+            let number = self.name("a capture, a string or a number")?;
+            Arg::Number(
+                number
+                    .parse()
+                    .with_context(|| format!("`{}` is not a number", number))?,
+            )
+        };
+        self.expect(")")?;
+
+        let (op, negated) = match name.as_str() {
+            "eq?" => (Op::Eq, false),
+            "not-eq?" => (Op::Eq, true),
+            "match?" | "not-match?" => {
+                let Arg::Text(regex) = &arg else {
+                    bail!("`#{}` expects a regex string", name);
+                };
+                let regex = Regex::new(regex).context("Invalid regex")?;
+                (Op::Match(regex), name == "not-match?")
+            }
+            "gt?" => (Op::Gt, false),
+            "ge?" => (Op::Ge, false),
+            "lt?" => (Op::Lt, false),
+            "le?" => (Op::Le, false),
+            _ => bail!(
+                "unknown predicate `#{}`, expected one of: #eq?, #not-eq?, #match?, #not-match?, #gt?, #ge?, #lt?, #le?",
+                name
+            ),
+        };
+        Ok(Predicate {
+            op,
+            negated,
+            capture,
+            arg,
+        })
+    }
+}
@@ -0,0 +1,162 @@
+//! Access to the syntax tree nodes by the names of their kinds and fields, as they are in `syn.json`
+//!
+//! The impls for the syn types are generated by `build.rs`.
+
+use proc_macro2::{Delimiter, Ident, Literal, TokenStream, TokenTree};
+use quote::ToTokens;
+use std::any::Any;
+
+/// A syntax tree node
+///
+/// The variants of enums that wrap another node (like `Expr::If(ExprIf)`) are the same node as the one they wrap:
+/// it is of the kinds `Expr`, `Expr::If` and `ExprIf`, and has the fields of `ExprIf`.
+pub trait Reflect {
+    fn is(&self, kind: &str) -> bool;
+
+    /// `None` if the node has no such field, the unnamed fields of enum variants are called `0`, `1` and so on
+    fn field(&self, name: &str) -> Option<Value<'_>>;
+
+    /// Calls `f` on every child node, in the source order
+    fn children<'a>(&'a self, f: &mut dyn FnMut(&'a dyn Reflect));
+
+    /// The innermost node, to downcast it to its syn type
+    fn as_any(&self) -> &dyn Any;
+
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        self.children(&mut |child| child.to_tokens(tokens));
+    }
+}
+
+/// The value of a field
+///
+/// Punctuation fields are only looked at for whether they are present, so they are tokens with a fixed text.
+#[derive(Clone)]
+pub enum Value<'a> {
+    /// a missing optional value
+    None,
+    Node(&'a dyn Reflect),
+    List(Vec<Value<'a>>),
+    Token(&'static str),
+    Ident(&'a Ident),
+    Tokens(&'a TokenStream),
+    Literal(&'a Literal),
+    Str(&'a str),
+    Int(u64),
+    Bool(bool),
+}
+
+impl Value<'_> {
+    /// Missing or an empty list
+    pub fn is_empty(&self) -> bool {
+        match self {
+            Value::None => true,
+            Value::List(elements) => elements.is_empty(),
+            _ => false,
+        }
+    }
+
+    /// The source of the value, without the whitespace that is not needed to separate the tokens
+    ///
+    /// Elements of lists are separated with `, `.
+    pub fn text(&self) -> String {
+        let mut tokens = TokenStream::new();
+        match self {
+            Value::None => return String::new(),
+            Value::List(elements) => {
+                return elements
+                    .iter()
+                    .map(Value::text)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            }
+            Value::Token(token) => return token.to_string(),
+            Value::Str(s) => return s.to_string(),
+            Value::Int(n) => return n.to_string(),
+            Value::Bool(b) => return b.to_string(),
+            Value::Node(node) => node.to_tokens(&mut tokens),
+            Value::Ident(ident) => ident.to_tokens(&mut tokens),
+            Value::Tokens(stream) => stream.to_tokens(&mut tokens),
+            Value::Literal(literal) => literal.to_tokens(&mut tokens),
+        }
+        let mut text = String::new();
+        write_tokens(&mut text, tokens);
+        text
+    }
+
+    /// The length of a list, otherwise the value as a number if it is one
+    pub fn number(&self) -> Option<f64> {
+        match self {
+            Value::List(elements) => Some(elements.len() as f64),
+            Value::Int(n) => Some(*n as f64),
+            Value::None => None,
+            _ => self.text().parse().ok(),
+        }
+    }
+
+    /// The value as a list: missing values are empty lists, and the rest are single-element ones
+    pub fn into_elements(self) -> Vec<Self> {
+        match self {
+            Value::None => vec![],
+            Value::List(elements) => elements,
+            value => vec![value],
+        }
+    }
+}
+
+/// Words (identifiers and literals) are separated with a space, everything else is written together
+fn write_tokens(text: &mut String, tokens: TokenStream) {
+    // whether the last token written is a word
+    let mut word = false;
+    for tree in tokens {
+        match tree {
+            TokenTree::Group(group) => {
+                let (open, close) = match group.delimiter() {
+                    Delimiter::Parenthesis => ("(", ")"),
+                    Delimiter::Brace => ("{", "}"),
+                    Delimiter::Bracket => ("[", "]"),
+                    Delimiter::None => ("", ""),
+                };
+                text.push_str(open);
+                write_tokens(text, group.stream());
+                text.push_str(close);
+                word = false;
+            }
+            TokenTree::Punct(punct) => {
+                text.push(punct.as_char());
+                word = false;
+            }
+            TokenTree::Ident(_) | TokenTree::Literal(_) => {
+                if word {
+                    text.push(' ');
+                }
+                text.push_str(&tree.to_string());
+                word = true;
+            }
+        }
+    }
+}
+
+/// The fields a node of the kind (like `ExprIf` or `Expr::If`) can have, `None` for unknown kinds
+pub fn fields_of(kind: &str) -> Option<&'static [&'static str]> {
+    let index = generated::KINDS
+        .binary_search_by_key(&kind, |(k, _)| *k)
+        .ok()?;
+    Some(generated::KINDS[index].1)
+}
+
+/// All the field names a node can have
+pub fn all_fields() -> impl Iterator<Item = &'static str> {
+    generated::KINDS
+        .iter()
+        .flat_map(|(_, fields)| fields.iter().copied())
+}
+
+#[allow(clippy::all)]
+mod generated {
+    use super::{Reflect, Value};
+    use proc_macro2::TokenStream;
+    use quote::ToTokens;
+    use std::any::Any;
+
+    include!(concat!(env!("OUT_DIR"), "/reflect.rs"));
+}
@@ -0,0 +1,124 @@
+//! Descriptions of the metrics, known without collecting anything
+
+use serde::Serialize;
+use std::borrow::Cow;
+
+/// What a single observation of a metric is made on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Scope {
+    File,
+    /// functions, methods and closures
+    Function,
+    /// structs, enums, traits and impl blocks, or their fields and variants
+    Type,
+    Statement,
+    MacroCall,
+    /// a single value for the whole repository
+    Repo,
+}
+
+impl Scope {
+    pub const ALL: [Scope; 6] = [
+        Scope::File,
+        Scope::Function,
+        Scope::Type,
+        Scope::Statement,
+        Scope::MacroCall,
+        Scope::Repo,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Scope::File => "file",
+            Scope::Function => "function",
+            Scope::Type => "type",
+            Scope::Statement => "statement",
+            Scope::MacroCall => "macro_call",
+            Scope::Repo => "repo",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Unit {
+    Count,
+    Lines,
+    Tokens,
+    /// nesting levels
+    Levels,
+    /// a dimensionless complexity or quality score
+    Score,
+    Ratio,
+    Bits,
+    Seconds,
+    Kilobytes,
+}
+
+impl Unit {
+    pub fn name(self) -> &'static str {
+        match self {
+            Unit::Count => "count",
+            Unit::Lines => "lines",
+            Unit::Tokens => "tokens",
+            Unit::Levels => "levels",
+            Unit::Score => "score",
+            Unit::Ratio => "ratio",
+            Unit::Bits => "bits",
+            Unit::Seconds => "seconds",
+            Unit::Kilobytes => "kilobytes",
+        }
+    }
+}
+
+/// A metric declared by a collector, the name is relative to the name of the collector
+#[derive(Debug, Clone)]
+pub struct MetricSchema {
+    /// empty if the collector produces a single metric
+    pub name: Cow<'static, str>,
+    pub scope: Scope,
+    pub unit: Unit,
+    pub description: Cow<'static, str>,
+}
+
+impl MetricSchema {
+    pub const fn new(
+        name: &'static str,
+        scope: Scope,
+        unit: Unit,
+        description: &'static str,
+    ) -> Self {
+        Self {
+            name: Cow::Borrowed(name),
+            scope,
+            unit,
+            description: Cow::Borrowed(description),
+        }
+    }
+}
+
+/// A metric with its full name, as it appears in the results
+#[derive(Debug, Clone, Serialize)]
+pub struct MetricInfo {
+    pub name: String,
+    pub scope: Scope,
+    pub unit: Unit,
+    pub description: String,
+}
+
+impl MetricInfo {
+    pub fn of(prefix: &str, schema: &MetricSchema) -> Self {
+        let name = if schema.name.is_empty() {
+            prefix.to_string()
+        } else {
+            format!("{}.{}", prefix, schema.name)
+        };
+        Self {
+            name,
+            scope: schema.scope,
+            unit: schema.unit,
+            description: schema.description.to_string(),
+        }
+    }
+}
@@ -0,0 +1,47 @@
+use proc_macro2::{LineColumn, Span};
+use serde::{Deserialize, Serialize};
+use syn::spanned::Spanned;
+
+/// A line/column pair, lines are 1-indexed and columns are 0-indexed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Position {
+    pub line: usize,
+    pub column: usize,
+}
+
+impl From<LineColumn> for Position {
+    fn from(value: LineColumn) -> Self {
+        Self {
+            line: value.line,
+            column: value.column,
+        }
+    }
+}
+
+/// Where in the repository an observation was made
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Location {
+    /// path of the file, relative to repo root
+    pub path: String,
+    /// name of the item the observation is about (if it has one)
+    pub item: Option<String>,
+    pub start: Position,
+    pub end: Position,
+}
+
+impl Location {
+    /// Make a location from a span of a file parsed with `FileText::parse`
+    pub fn from_span(item: Option<String>, span: Span) -> Self {
+        Self {
+            path: span.source_file().path().to_string_lossy().into_owned(),
+            item,
+            start: span.start().into(),
+            end: span.end().into(),
+        }
+    }
+
+    /// Make a location spanning the whole syntax node
+    pub fn of(item: Option<String>, node: &impl Spanned) -> Self {
+        Self::from_span(item, node.span())
+    }
+}
@@ -0,0 +1,171 @@
+//! Parsing the arguments of macro invocations, so that the code inside `vec![..]`, `assert!(..)` and such is counted too
+
+use super::{Node, NodeVisitor, Walk, WalkContext};
+use serde::{Deserialize, Serialize};
+use syn::parse::{ParseStream, Parser, Result};
+use syn::punctuated::Punctuated;
+use syn::visit::Visit;
+use syn::{Block, Expr, Macro, Pat, Stmt, Token};
+
+/// The arguments of a macro invocation, parsed the way rustfmt does it
+pub enum MacroArgs {
+    /// comma-separated expressions, like in `vec![..]`, `assert!(..)` or `format!(..)`
+    Exprs(Vec<Expr>),
+    /// statements and items, like in `thread_local! { .. }`
+    Stmts(Vec<Stmt>),
+}
+
+impl MacroArgs {
+    /// `None` if the arguments are not Rust code (like the ones of `html!`), or it's a `macro_rules!` definition
+    pub fn parse(mac: &Macro) -> Option<Self> {
+        if is_definition(mac) {
+            return None;
+        }
+        let name = mac.path.segments.last()?.ident.to_string();
+        let parse_exprs = |parser: fn(ParseStream) -> Result<Vec<Expr>>| {
+            parser.parse2(mac.tokens.clone()).ok().map(Self::Exprs)
+        };
+        let known = match name.as_str() {
+            "matches" | "assert_matches" | "debug_assert_matches" => parse_exprs(parse_matches),
+            "vec" => parse_exprs(parse_repeat),
+            _ => None,
+        };
+        known.or_else(|| parse_exprs(parse_list)).or_else(|| {
+            Block::parse_within
+                .parse2(mac.tokens.clone())
+                .ok()
+                .map(Self::Stmts)
+        })
+    }
+
+    /// Visits the parsed arguments, as if they were children of the macro
+    pub fn visit<'ast>(&'ast self, visitor: &mut (impl Visit<'ast> + ?Sized)) {
+        match self {
+            Self::Exprs(exprs) => exprs.iter().for_each(|e| visitor.visit_expr(e)),
+            Self::Stmts(stmts) => stmts.iter().for_each(|s| visitor.visit_stmt(s)),
+        }
+    }
+}
+
+fn is_definition(mac: &Macro) -> bool {
+    mac.path.is_ident("macro_rules")
+}
+
+/// `a, b, c`, with an optional trailing comma
+fn parse_list(input: ParseStream) -> Result<Vec<Expr>> {
+    let exprs = Punctuated::<Expr, Token![,]>::parse_terminated(input)?;
+    Ok(exprs.into_iter().collect())
+}
+
+/// `expr, pattern if guard`, followed by the message for the asserts
+fn parse_matches(input: ParseStream) -> Result<Vec<Expr>> {
+    let mut exprs = vec![input.parse()?];
+    input.parse::<Token![,]>()?;
+    Pat::parse_multi_with_leading_vert(input)?;
+    if input.parse::<Option<Token![if]>>()?.is_some() {
+        exprs.push(input.parse()?);
+    }
+    if input.parse::<Option<Token![,]>>()?.is_some() {
+        exprs.extend(parse_list(input)?);
+    }
+    Ok(exprs)
+}
+
+/// `elem; len`
+fn parse_repeat(input: ParseStream) -> Result<Vec<Expr>> {
+    let elem = input.parse()?;
+    input.parse::<Token![;]>()?;
+    Ok(vec![elem, input.parse()?])
+}
+
+/// How many of the macro invocations in a file could be looked into, counted along the walk of the collectors
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MacroCoverage {
+    pub parsed: usize,
+    pub unparsed: usize,
+    /// lines taken by the arguments of the unparsed invocations
+    pub unparsed_lines: usize,
+}
+
+impl NodeVisitor for MacroCoverage {
+    fn enter(&mut self, node: Node<'_>, cx: &WalkContext) -> Walk {
+        match node {
+            Node::Macro(mac) if !is_definition(mac) => {
+                if cx.macro_args(mac).is_some() {
+                    self.parsed += 1;
+                } else {
+                    let span = mac.delimiter.span().join();
+                    self.unparsed += 1;
+                    self.unparsed_lines += span.end().line - span.start().line + 1;
+                }
+            }
+            _ => {}
+        }
+        Walk::Continue
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{MacroArgs, MacroCoverage};
+    use crate::collector::metrics::util::walk_file;
+    use syn::parse_quote;
+
+    fn parsed(mac: syn::Macro) -> Option<usize> {
+        match MacroArgs::parse(&mac)? {
+            MacroArgs::Exprs(exprs) => Some(exprs.len()),
+            MacroArgs::Stmts(stmts) => Some(stmts.len()),
+        }
+    }
+
+    #[test]
+    fn macro_args() {
+        assert_eq!(parsed(parse_quote!(println!())), Some(0));
+        assert_eq!(
+            parsed(parse_quote!(assert!(a, "{} {x}", b, x = c,))),
+            Some(4)
+        );
+        assert_eq!(
+            parsed(parse_quote!(vec![if a { 1 } else { 2 }; n])),
+            Some(2)
+        );
+        assert_eq!(
+            parsed(parse_quote!(matches!(a, Some(1 | 2) | None if b))),
+            Some(2)
+        );
+        assert_eq!(
+            parsed(parse_quote!(thread_local! { static A: u32 = 1; })),
+            Some(1)
+        );
+        assert_eq!(parsed(parse_quote!(html!(<p>{ a }</p>))), None);
+        let definition: syn::ItemMacro = parse_quote! {
+            macro_rules! a { () => { 1 }; }
+        };
+        assert_eq!(parsed(definition.mac), None);
+    }
+
+    #[test]
+    fn coverage() {
+        let file = syn::parse_file(
+            r#"
+macro_rules! ignored { ($a:expr) => { $a }; }
+fn foo() {
+    assert_eq!(vec![1, 2], bar!(
+        ~ 3
+    ));
+}
+"#,
+        )
+        .unwrap();
+        let mut coverage = MacroCoverage::default();
+        walk_file(&file, &mut [&mut coverage]);
+        assert_eq!(
+            coverage,
+            MacroCoverage {
+                parsed: 2,
+                unparsed: 1,
+                unparsed_lines: 3,
+            }
+        );
+    }
+}
@@ -4,10 +4,24 @@ pub use visitor_collector::VisitorCollector;
 mod monoid;
 pub use monoid::Monoid;
 
+#[cfg(test)]
 mod test;
+#[cfg(test)]
 pub use test::check;
 
+mod walk;
+pub use walk::{walk_file, Node, NodeVisitor, Visiting, Walk, WalkContext, WALK_VERSION};
+
+mod macro_args;
+pub use macro_args::{MacroArgs, MacroCoverage};
+
+mod location;
+pub use location::{Location, Position};
+
 mod observer;
+pub use observer::FloatHist;
 pub use observer::Hist;
+pub use observer::Located;
 pub use observer::Observer;
+pub use observer::ObserverKind;
 pub use observer::Unaggregated;
@@ -1,6 +1,6 @@
 use rustc_hash::FxHashMap;
 
-use serde::Serialize;
+use serde::{Deserialize, Deserializer, Serialize};
 
 use super::{super::Monoid, Observer};
 
@@ -13,12 +13,26 @@ pub struct Hist {
     buckets: FxHashMap<usize, u32>,
 }
 
+/// Only the summary goes to the human-readable formats, the binary ones (used for caching) get all the buckets
 impl Serialize for Hist {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
         S: serde::Serializer,
     {
-        self.describe().serialize(serializer)
+        if serializer.is_human_readable() {
+            self.describe().serialize(serializer)
+        } else {
+            self.buckets.serialize(serializer)
+        }
+    }
+}
+
+/// Reads the buckets, as written to the binary formats
+impl<'de> Deserialize<'de> for Hist {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(Self {
+            buckets: FxHashMap::deserialize(deserializer)?,
+        })
     }
 }
 
@@ -133,6 +147,92 @@ impl std::ops::Add for Hist {
     }
 }
 
+/// Same as `Hist`, but for fractional observations (every distinct value gets its own bucket)
+#[derive(Clone, Default)]
+pub struct FloatHist {
+    /// keyed by `f64::to_bits`
+    buckets: FxHashMap<u64, u32>,
+}
+
+#[derive(Serialize)]
+pub struct FloatHistSummary {
+    pub sum: f64,
+    pub avg: f64,
+    pub mode: Option<f64>,
+}
+
+/// Same as for `Hist`, only the binary formats get the buckets
+impl Serialize for FloatHist {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        if serializer.is_human_readable() {
+            self.describe().serialize(serializer)
+        } else {
+            self.buckets.serialize(serializer)
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for FloatHist {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(Self {
+            buckets: FxHashMap::deserialize(deserializer)?,
+        })
+    }
+}
+
+impl Monoid for FloatHist {
+    fn init() -> Self {
+        Self::default()
+    }
+
+    fn unite(mut self, rhs: Self) -> Self {
+        for (k, v) in rhs.buckets {
+            *self.buckets.entry(k).or_insert(0) += v;
+        }
+        self
+    }
+}
+
+impl Observer<f64> for FloatHist {
+    fn observe(&mut self, value: f64) {
+        *self.buckets.entry(value.to_bits()).or_insert(0) += 1;
+    }
+
+    fn count(&self) -> usize {
+        self.buckets.values().map(|&v| v as usize).sum()
+    }
+}
+
+impl FloatHist {
+    pub fn describe(&self) -> FloatHistSummary {
+        let sum = self
+            .buckets
+            .iter()
+            .map(|(&val, &count)| f64::from_bits(val) * count as f64)
+            .sum::<f64>();
+        let mut buckets = self
+            .buckets
+            .iter()
+            .map(|(&val, &count)| (f64::from_bits(val), count))
+            .collect::<Vec<_>>();
+        // same tie-breaking as in `Hist::mode`
+        buckets.sort_by(|(val1, _), (val2, _)| val1.total_cmp(val2));
+        let mode = buckets
+            .iter()
+            .max_by_key(|(_, count)| *count)
+            .map(|&(mode, _)| mode);
+
+        FloatHistSummary {
+            sum,
+            avg: sum / self.count() as f64,
+            mode,
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::collector::metrics::util::Monoid;
@@ -216,6 +316,22 @@ mod tests {
         assert_eq!(hist.into_values(), vec![1, 1, 1, 2, 3, 4, 5, 6]);
     }
 
+    #[test]
+    fn float_hist() {
+        use crate::collector::metrics::util::Observer;
+
+        let mut hist = super::FloatHist::init();
+        for value in [0.5, 1.5, 1.5, 2.0, 2.0, 4.0] {
+            hist.observe(value);
+        }
+        let summary = hist.describe();
+
+        assert_eq!(hist.count(), 6);
+        assert_eq!(summary.sum, 11.5);
+        assert_eq!(summary.avg, 11.5 / 6.0);
+        assert_eq!(summary.mode, Some(2.0));
+    }
+
     #[test]
     fn determenistic_mode() {
         let mut hist1 = super::Hist::init();
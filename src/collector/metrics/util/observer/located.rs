@@ -0,0 +1,45 @@
+use serde::{Deserialize, Serialize};
+
+use super::{super::Location, super::Monoid, Observer};
+
+/// Like `Unaggregated`, but also remembers where each observation was made
+///
+/// Serialized as `{"values": [...], "locations": [...]}` with the two arrays being parallel,
+/// `collector::extract_locations` later moves the locations out of the metrics tree.
+#[derive(Default, Clone, Serialize, Deserialize)]
+pub struct Located<T = usize> {
+    values: Vec<T>,
+    /// None for observations made with plain `observe`
+    locations: Vec<Option<Location>>,
+}
+
+impl<T> Monoid for Located<T> {
+    fn init() -> Self {
+        Self {
+            values: Vec::new(),
+            locations: Vec::new(),
+        }
+    }
+
+    fn unite(mut self, mut rhs: Self) -> Self {
+        self.values.append(&mut rhs.values);
+        self.locations.append(&mut rhs.locations);
+        self
+    }
+}
+
+impl<T> Observer<T> for Located<T> {
+    fn observe(&mut self, value: T) {
+        self.values.push(value);
+        self.locations.push(None);
+    }
+
+    fn observe_at(&mut self, value: T, location: impl FnOnce() -> Location) {
+        self.values.push(value);
+        self.locations.push(Some(location()));
+    }
+
+    fn count(&self) -> usize {
+        self.values.len()
+    }
+}
@@ -1,11 +1,36 @@
+use super::Location;
+use serde::Deserialize;
+
 // polymorphic over type of observations
 pub trait Observer<T = usize> {
     fn observe(&mut self, value: T);
+    /// Same as `observe`, but also tells where in the source the observation was made
+    ///
+    /// The location is computed lazily, as most observers just ignore it
+    fn observe_at(&mut self, value: T, location: impl FnOnce() -> Location) {
+        let _ = location;
+        self.observe(value);
+    }
     fn count(&self) -> usize;
 }
 
+/// Which observer the collectors should use
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ObserverKind {
+    /// Plain arrays of observed values
+    #[default]
+    Unaggregated,
+    /// Observed values along with their locations in the source
+    Located,
+    /// Only the sum, average and mode of the observed values
+    Histogram,
+}
+
 mod histogram;
+mod located;
 mod unaggregated;
 
-pub use histogram::Hist;
+pub use histogram::{FloatHist, Hist};
+pub use located::Located;
 pub use unaggregated::Unaggregated;
@@ -1,4 +1,4 @@
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 use super::{super::Monoid, Observer};
 
@@ -23,6 +23,17 @@ impl<T: Serialize> Serialize for Unaggregated<T> {
     }
 }
 
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for Unaggregated<T> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        Ok(Self {
+            observations: Vec::deserialize(deserializer)?,
+        })
+    }
+}
+
 impl<T> Monoid for Unaggregated<T> {
     fn init() -> Self {
         Self {
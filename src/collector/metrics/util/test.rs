@@ -1,15 +1,14 @@
+use super::{walk_file, Visiting};
 use expect_test::Expect;
 use serde::Serialize;
 use syn::{visit::Visit, File};
 
-#[allow(dead_code)]
-// used in tests
 pub fn check<T>(syntax_tree: File, expect: Expect)
 where
     T: for<'ast> Visit<'ast> + Default + Serialize,
 {
-    let mut metric = T::default();
-    metric.visit_file(&syntax_tree);
-    let representation = serde_json::to_string_pretty(&metric).unwrap();
+    let mut metric = Visiting(T::default());
+    walk_file(&syntax_tree, &mut [&mut metric]);
+    let representation = serde_json::to_string_pretty(&metric.0).unwrap();
     expect.assert_eq(&representation);
 }
@@ -1,6 +1,8 @@
-use crate::collector::metrics::MetricCollector;
-use crate::collector::FileAst;
-use serde::Serialize;
+use super::{Node, NodeVisitor, Visiting, Walk, WalkContext};
+use crate::collector::metrics::schema::MetricSchema;
+use crate::collector::metrics::{FileVisitor, MetricCollector};
+use serde::{de::DeserializeOwned, Serialize};
+use std::borrow::Cow;
 use std::marker::PhantomData;
 
 /// A helper for collecting metrics using a syn visitor
@@ -12,6 +14,7 @@ pub struct VisitorCollector<
     Aggregate: Fn(&[M]) -> AM + 'static,
 > {
     name: &'static str,
+    schema: &'static [MetricSchema],
     extract: Extract,
     aggregate: Aggregate,
     phantom: PhantomData<(V, Extract, Aggregate)>,
@@ -27,10 +30,17 @@ impl<
 {
     /// Create a new visitor collector
     ///
-    /// You need to supply: a metric name, a visitor (used only for getting a type), a function to extract a metric from a visitor (that was run on a file), and a function to aggregate metrics across files.
-    pub fn new(name: &'static str, _visitor: V, extract: Extract, aggregate: Aggregate) -> Self {
+    /// You need to supply: a metric name, the schema of the metrics, a visitor (used only for getting a type), a function to extract a metric from a visitor (that was run on a file), and a function to aggregate metrics across files.
+    pub fn new(
+        name: &'static str,
+        schema: &'static [MetricSchema],
+        _visitor: V,
+        extract: Extract,
+        aggregate: Aggregate,
+    ) -> Self {
         Self {
             name,
+            schema,
             extract,
             aggregate,
             phantom: PhantomData,
@@ -38,9 +48,35 @@ impl<
     }
 }
 
+/// A visitor of a single file, with the way to get the metric out of it
+struct ExtractingVisitor<'a, V, Extract> {
+    visitor: Visiting<V>,
+    extract: &'a Extract,
+}
+
+impl<V: for<'ast> syn::visit::Visit<'ast>, Extract> NodeVisitor
+    for ExtractingVisitor<'_, V, Extract>
+{
+    fn enter(&mut self, node: Node<'_>, cx: &WalkContext) -> Walk {
+        self.visitor.enter(node, cx)
+    }
+
+    fn exit(&mut self, node: Node<'_>) {
+        self.visitor.exit(node)
+    }
+}
+
+impl<V: for<'ast> syn::visit::Visit<'ast>, M, Extract: Fn(V) -> M> FileVisitor<M>
+    for ExtractingVisitor<'_, V, Extract>
+{
+    fn finish(self: Box<Self>) -> M {
+        (self.extract)(self.visitor.0)
+    }
+}
+
 impl<
         V: for<'ast> syn::visit::Visit<'ast> + Default + Send + Sync + 'static,
-        M: Send + 'static,
+        M: Clone + Send + Sync + Serialize + DeserializeOwned + 'static,
         AM: Serialize + 'static,
         Extract: Fn(V) -> M + Send + Sync + 'static,
         Aggregate: Fn(&[M]) -> AM + Send + Sync + 'static,
@@ -53,10 +89,15 @@ impl<
         self.name
     }
 
-    fn collect_file(&self, file: &FileAst) -> Self::Metric {
-        let mut visitor = V::default();
-        visitor.visit_file(&file.content);
-        (self.extract)(visitor)
+    fn schema(&self) -> Cow<'static, [MetricSchema]> {
+        Cow::Borrowed(self.schema)
+    }
+
+    fn file_visitor(&self) -> Box<dyn FileVisitor<Self::Metric> + '_> {
+        Box::new(ExtractingVisitor {
+            visitor: Visiting(V::default()),
+            extract: &self.extract,
+        })
     }
 
     fn aggregate_metrics(&self, metric: &[Self::Metric]) -> Self::AggregatedMetric {
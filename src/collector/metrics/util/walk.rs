@@ -0,0 +1,271 @@
+//! A single walk over a syntax tree, shared by all the collectors
+
+use super::MacroArgs;
+use rustc_hash::FxHashMap;
+use std::cell::RefCell;
+use std::rc::Rc;
+use syn::visit::Visit;
+
+/// Has to be bumped whenever the walk reaches different nodes, as it changes the metrics of all the collectors
+pub const WALK_VERSION: u32 = 3;
+
+/// Whether the walk should go into the children of a node
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Walk {
+    Continue,
+    /// the visitor is not called for the children, but is still called on the exit from the node
+    SkipChildren,
+}
+
+/// A visitor which does not drive the traversal by itself, so that many of them can share a walk
+///
+/// Unlike with `syn::visit::Visit`, the code before and after visiting the children goes to `enter` and `exit`.
+/// `exit` is called for every node `enter` was called for.
+pub trait NodeVisitor {
+    fn enter(&mut self, _node: Node<'_>, _cx: &WalkContext) -> Walk {
+        Walk::Continue
+    }
+
+    fn exit(&mut self, _node: Node<'_>) {}
+}
+
+/// Runs a `syn::visit::Visit` as a part of the shared walk, with its state kept apart from the other visitors
+///
+/// A `Visit` drives its own traversal, so it's called on the roots the walk reaches rather than on every node: on the
+/// file, and on the parsed arguments of every macro, which its own traversal doesn't look into.
+pub struct Visiting<V>(pub V);
+
+impl<V: for<'ast> Visit<'ast>> NodeVisitor for Visiting<V> {
+    fn enter(&mut self, node: Node<'_>, cx: &WalkContext) -> Walk {
+        match node {
+            Node::File(file) => self.0.visit_file(file),
+            Node::Macro(mac) => {
+                if let Some(args) = cx.macro_args(mac) {
+                    args.visit(&mut self.0);
+                }
+            }
+            _ => {}
+        }
+        Walk::Continue
+    }
+}
+
+/// What the walk keeps for the visitors, besides the node they are on
+#[derive(Default)]
+pub struct WalkContext {
+    /// the parsed arguments of the macros, by the address of the macro
+    ///
+    /// The macros inside the parsed arguments stay at their address, as the arguments are never dropped.
+    macros: RefCell<FxHashMap<*const syn::Macro, Option<Rc<MacroArgs>>>>,
+}
+
+impl WalkContext {
+    /// The arguments of a macro of the walked file, they are parsed once no matter who asks first
+    ///
+    /// Lets the visitors that evaluate a whole function on its entry look into the macros before the walk gets there.
+    pub fn macro_args(&self, mac: &syn::Macro) -> Option<Rc<MacroArgs>> {
+        self.macros
+            .borrow_mut()
+            .entry(mac)
+            .or_insert_with(|| MacroArgs::parse(mac).map(Rc::new))
+            .clone()
+    }
+}
+
+/// The walk over a file, dispatching the nodes to the visitors
+struct FusedWalk<'v, 'a> {
+    visitors: &'v mut [&'a mut dyn NodeVisitor],
+    cx: WalkContext,
+    /// for every visitor, the depth of the node whose children it skips
+    skipping: Vec<Option<usize>>,
+    depth: usize,
+}
+
+impl FusedWalk<'_, '_> {
+    fn node(&mut self, node: Node<'_>, children: impl FnOnce(&mut Self)) {
+        for (visitor, skipping) in self.visitors.iter_mut().zip(&mut self.skipping) {
+            if skipping.is_none() && visitor.enter(node, &self.cx) == Walk::SkipChildren {
+                *skipping = Some(self.depth);
+            }
+        }
+
+        // no need to go any further when no one is interested
+        if self.skipping.iter().any(Option::is_none) {
+            self.depth += 1;
+            children(self);
+            self.depth -= 1;
+        }
+
+        for (visitor, skipping) in self.visitors.iter_mut().zip(&mut self.skipping) {
+            match *skipping {
+                None => visitor.exit(node),
+                Some(depth) if depth == self.depth => {
+                    visitor.exit(node);
+                    *skipping = None;
+                }
+                Some(_) => {}
+            }
+        }
+    }
+}
+
+/// The parsed arguments of a macro are walked as its children, after its own syntax tree
+fn walk_macro(walk: &mut FusedWalk, mac: &syn::Macro) {
+    syn::visit::visit_macro(walk, mac);
+    if let Some(args) = walk.cx.macro_args(mac) {
+        args.visit(walk);
+    }
+}
+
+/// `Node`, and the `Visit` impl calling the visitors on every node, generated by `build.rs` from `syn.json`
+#[allow(clippy::all)]
+mod generated {
+    use super::{walk_macro, FusedWalk};
+    use crate::collector::metrics::query::reflect::Reflect;
+    use syn::visit::Visit;
+
+    include!(concat!(env!("OUT_DIR"), "/nodes.rs"));
+}
+
+pub use generated::Node;
+
+/// Walks the file once, calling all the visitors on every node
+pub fn walk_file(file: &syn::File, visitors: &mut [&mut dyn NodeVisitor]) {
+    let skipping = vec![None; visitors.len()];
+    FusedWalk {
+        visitors,
+        cx: WalkContext::default(),
+        skipping,
+        depth: 0,
+    }
+    .visit_file(file);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{walk_file, MacroArgs, Node, NodeVisitor, Visiting, Walk, WalkContext};
+    use std::rc::Rc;
+    use syn::parse_quote;
+
+    /// Records the enters and exits, skipping the children of functions
+    #[derive(Default)]
+    struct Trace(Vec<String>);
+
+    fn name(node: Node<'_>) -> &'static str {
+        match node {
+            Node::File(_) => "file",
+            Node::ItemFn(_) => "fn",
+            Node::ExprIf(_) => "if",
+            _ => "",
+        }
+    }
+
+    impl NodeVisitor for Trace {
+        fn enter(&mut self, node: Node<'_>, _cx: &WalkContext) -> Walk {
+            let name = name(node);
+            if !name.is_empty() {
+                self.0.push(format!("+{}", name));
+            }
+            if matches!(node, Node::ItemFn(_)) {
+                Walk::SkipChildren
+            } else {
+                Walk::Continue
+            }
+        }
+
+        fn exit(&mut self, node: Node<'_>) {
+            let name = name(node);
+            if !name.is_empty() {
+                self.0.push(format!("-{}", name));
+            }
+        }
+    }
+
+    #[derive(Default)]
+    struct IfCount(usize);
+
+    impl NodeVisitor for IfCount {
+        fn enter(&mut self, node: Node<'_>, _cx: &WalkContext) -> Walk {
+            if let Node::ExprIf(_) = node {
+                self.0 += 1;
+            }
+            Walk::Continue
+        }
+    }
+
+    #[test]
+    fn shared_walk() {
+        let file = parse_quote! {
+            fn foo() { if a { if b {} } }
+            const C: u32 = if c { 1 } else { 2 };
+        };
+        let mut trace = Trace::default();
+        let mut ifs = IfCount::default();
+        walk_file(&file, &mut [&mut trace, &mut ifs]);
+
+        assert_eq!(trace.0, ["+file", "+fn", "-fn", "+if", "-if", "-file"]);
+        assert_eq!(ifs.0, 3);
+    }
+
+    #[derive(Default)]
+    struct VisitIfCount(usize);
+
+    impl syn::visit::Visit<'_> for VisitIfCount {
+        fn visit_expr_if(&mut self, i: &syn::ExprIf) {
+            self.0 += 1;
+            syn::visit::visit_expr_if(self, i);
+        }
+    }
+
+    #[test]
+    fn visit_in_shared_walk() {
+        let file = parse_quote! {
+            fn foo() {
+                if a {}
+                assert!(if b { c } else { d });
+                vec![if e { 1 } else { 2 }, format!("{}", if f { 3 } else { 4 })];
+            }
+        };
+        let mut visit = Visiting(VisitIfCount::default());
+        let mut ifs = IfCount::default();
+        walk_file(&file, &mut [&mut visit, &mut ifs]);
+
+        assert_eq!(visit.0 .0, 4);
+        assert_eq!(ifs.0, 4);
+    }
+
+    /// The arguments of the macros it meets, once on the entry to their function and once on the macro itself
+    #[derive(Default)]
+    struct MacroArgsSeen(Vec<*const MacroArgs>);
+
+    impl NodeVisitor for MacroArgsSeen {
+        fn enter(&mut self, node: Node<'_>, cx: &WalkContext) -> Walk {
+            let mac = match node {
+                Node::ItemFn(i) => match &i.block.stmts[..] {
+                    [syn::Stmt::Macro(stmt)] => &stmt.mac,
+                    _ => return Walk::Continue,
+                },
+                Node::Macro(mac) => mac,
+                _ => return Walk::Continue,
+            };
+            if let Some(args) = cx.macro_args(mac) {
+                self.0.push(Rc::as_ptr(&args));
+            }
+            Walk::Continue
+        }
+    }
+
+    #[test]
+    fn macro_args_parsed_once() {
+        let file = parse_quote! {
+            fn foo() { assert!(if a { b } else { c }); }
+        };
+        let mut seen = MacroArgsSeen::default();
+        let mut ifs = IfCount::default();
+        walk_file(&file, &mut [&mut seen, &mut ifs]);
+
+        assert_eq!(seen.0.len(), 2);
+        assert_eq!(seen.0[0], seen.0[1]);
+        assert_eq!(ifs.0, 1);
+    }
+}
@@ -1,26 +1,44 @@
+mod cache;
+mod class;
+mod config;
+mod diagnostics;
 mod git;
-mod metrics;
+mod history;
+mod metric_list;
+pub(crate) mod metrics;
 mod rust_code_analysis;
+mod workspace;
 
 use crate::collector::{
-    git::RepoMetadata, metrics::util::Unaggregated, rust_code_analysis::RCAMetricsKinded,
+    cache::{CachedFile, MetricCache},
+    class::FileClass,
+    metrics::{util::MacroCoverage, FileMetric, MetricCollectorBox},
+    rust_code_analysis::RcaCollector,
+    workspace::{is_manifest, Workspace},
 };
 use ::rust_code_analysis::{FuncSpace, ParserTrait, RustParser};
-use anyhow::{Context, Result};
+use anyhow::{bail, Context, Result};
 use indicatif::ProgressStyle;
+use rayon::iter::Either;
 use rayon::prelude::*;
 use relative_path::RelativePathBuf;
 use serde::{Deserialize, Serialize};
+use std::borrow::Borrow;
 use std::collections::BTreeMap;
 use std::ffi::OsStr;
-use std::ops::Deref;
 use std::path::Path;
-use tracing::{error, info, info_span, instrument, Span};
+use std::sync::Arc;
+use tokio::sync::mpsc::{self, Receiver};
+use tracing::{error, info, info_span, instrument, warn, Span};
 use tracing_indicatif::span_ext::IndicatifSpanExt;
 
-pub use git::LimitedCrab;
-
-use self::metrics::util::Observer;
+pub use config::CollectConfig;
+pub use diagnostics::{Coverage, Diagnostics, SkippedFile, Stage};
+pub use git::{FetchStrategy, GithubError, GithubRepo, GithubUrls, LimitedCrab, RepoMetadata};
+pub use history::{history_commits, HistoryCollector};
+pub use metric_list::{metric_list, render as render_metric_list, ListFormat};
+pub use metrics::query::{Query, QueryMetric, QueryScope};
+pub use metrics::util::{Location, ObserverKind, Position};
 
 #[derive(Clone)]
 pub struct File<T> {
@@ -32,43 +50,187 @@ pub struct File<T> {
 pub type FileText = File<String>;
 pub type FileAst = File<syn::File>;
 
+impl<T> File<T> {
+    /// path of the file, relative to repo root
+    pub fn path(&self) -> &str {
+        self.path.as_str()
+    }
+
+    pub fn content(&self) -> &T {
+        &self.content
+    }
+}
+
 impl FileText {
+    /// A file that does not come from disk, its `path` still tells whether it's a test, an example etc.
+    pub fn new(path: &str, content: String) -> Self {
+        Self {
+            path: path.into(),
+            content,
+        }
+    }
+
     #[tracing::instrument]
-    pub fn read(repo_dir: &Path, path: &Path) -> Option<Self> {
+    pub fn read(repo_dir: &Path, path: &Path) -> Result<Self, SkippedFile> {
         let Ok(path) = RelativePathBuf::from_path(path) else {
             error!("Filename is not UTF-8: `{}`", path.display());
-            return None;
+            return Err(SkippedFile::new(
+                path.to_string_lossy(),
+                Stage::Read,
+                "Filename is not UTF-8",
+            ));
         };
         let content = match std::fs::read_to_string(path.to_path(repo_dir)) {
             Ok(v) => v,
             Err(e) => {
                 error!("Error while reading: {:?}", e);
-                return None;
+                return Err(SkippedFile::new(path.as_str(), Stage::Read, e.to_string()));
             }
         };
-        Some(Self { path, content })
+        Ok(Self { path, content })
     }
 
-    #[tracing::instrument(skip(self, span), parent = span, fields(path = %self.path))]
-    pub fn parse(self, span: Span) -> Option<FileAst> {
-        let content = match syn::parse_file(&self.content) {
+    /// Parses the file, along with the number of items that could not be parsed
+    ///
+    /// Such items are kept as `Item::Verbatim`, so that the rest of the file still counts.
+    // not instrumented: a span per file would cost more than parsing most of them
+    pub fn parse(&self) -> Result<ParsedFile, SkippedFile> {
+        // the path is recorded in the spans, so that `Located` observers can find it later
+        let (parsed, source) = syn::parse_file_recovering(&self.content, self.path.as_str());
+        let (content, errors) = match parsed {
             Ok(v) => v,
             Err(e) => {
-                error!("Error while parsing: {:?}", e);
-                return None;
+                error!("Error while parsing {}: {:?}", self.path, e);
+                return Err(
+                    SkippedFile::new(self.path.as_str(), Stage::Syn, e.to_string())
+                        .at(e.span().start().into()),
+                );
             }
         };
-        Some(File {
-            path: self.path,
+        for e in &errors {
+            let start = e.span().start();
+            warn!(
+                "Skipping an item of {} at {}:{}: {}",
+                self.path, start.line, start.column, e
+            );
+        }
+        let ast = File {
+            path: self.path.clone(),
             content,
+        };
+        Ok(ParsedFile {
+            ast,
+            recovered_items: errors.len(),
+            _source: source,
         })
     }
 }
 
+/// The syntax tree of a file, see `FileText::parse`
+pub struct ParsedFile {
+    pub ast: FileAst,
+    /// The items that could not be parsed, and are kept as `Item::Verbatim`
+    pub recovered_items: usize,
+    /// Keeps the text of the file for the locations of the spans, it's freed along with the tree
+    _source: proc_macro2::SourceFileGuard,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct RepoResult {
     pub meta: RepoMetadata,
     pub metrics: BTreeMap<String, serde_json::Value>,
+    /// Locations of the observations, in the same order as the values in `metrics`
+    ///
+    /// Only collected with `ObserverKind::Located`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub locations: BTreeMap<String, serde_json::Value>,
+    /// The files missing from the metrics, and how many of them there are
+    #[serde(default)]
+    pub diagnostics: Diagnostics,
+}
+
+impl RepoResult {
+    /// The value of a metric, `None` if there is no such metric
+    pub fn metric(&self, metric: &str) -> Option<MetricValue> {
+        self.metrics.get(metric).and_then(MetricValue::from_json)
+    }
+
+    /// All the metrics, by their flattened names like `complexity.all_fn.avg`
+    pub fn iter_metrics(&self) -> impl Iterator<Item = (&str, MetricValue)> + '_ {
+        self.metrics
+            .iter()
+            .filter_map(|(name, value)| Some((name.as_str(), MetricValue::from_json(value)?)))
+    }
+
+    /// The observations of a metric, a single value for the metrics that are not arrays (like `if_count`)
+    ///
+    /// `None` if there is no such metric, or it's not numeric (like the `mode` of an empty histogram).
+    pub fn values(&self, metric: &str) -> Option<Vec<f64>> {
+        self.metric(metric)?.values()
+    }
+
+    /// Where the observations of a metric come from, in the same order as `values`
+    ///
+    /// Empty if the locations were not collected, see `ObserverKind::Located`.
+    pub fn locations(&self, metric: &str) -> Result<Vec<Option<Location>>> {
+        metric_locations(&self.locations, metric)
+    }
+}
+
+/// The value of a flattened metric of `RepoResult`
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum MetricValue {
+    /// an aggregated value, like `if_count` or the `avg` of a histogram
+    Number(f64),
+    /// every observation, with the `Unaggregated` and `Located` observers
+    Observations(Vec<f64>),
+    /// a statistic the observations don't have, like the `mode` of an empty histogram
+    Missing,
+}
+
+impl MetricValue {
+    /// `None` if the value is not a flattened metric
+    pub fn from_json(value: &serde_json::Value) -> Option<Self> {
+        use serde_json::Value;
+
+        match value {
+            Value::Null => Some(Self::Missing),
+            Value::Number(n) => Some(Self::Number(n.as_f64()?)),
+            Value::Array(values) => values
+                .iter()
+                .map(Value::as_f64)
+                .collect::<Option<_>>()
+                .map(Self::Observations),
+            _ => None,
+        }
+    }
+
+    /// The observations, a single one for a number, `None` if the value is missing
+    pub fn values(&self) -> Option<Vec<f64>> {
+        match self {
+            Self::Number(n) => Some(vec![*n]),
+            Self::Observations(values) => Some(values.clone()),
+            Self::Missing => None,
+        }
+    }
+}
+
+/// The values of a flattened metric, see `RepoResult::values`
+pub fn metric_values(value: &serde_json::Value) -> Option<Vec<f64>> {
+    MetricValue::from_json(value)?.values()
+}
+
+/// The locations of a flattened metric, see `RepoResult::locations`
+pub fn metric_locations(
+    locations: &BTreeMap<String, serde_json::Value>,
+    metric: &str,
+) -> Result<Vec<Option<Location>>> {
+    match locations.get(metric) {
+        Some(locations) => serde_json::from_value(locations.clone())
+            .with_context(|| format!("Reading locations of `{}`", metric)),
+        None => Ok(Vec::new()),
+    }
 }
 
 // TODO: collect timings?
@@ -90,6 +252,10 @@ fn count_submetrics(value: &serde_json::Value) -> usize {
             if obj.contains_key("avg") && obj.contains_key("mode") && obj.contains_key("sum") {
                 return 1;
             }
+            // same for located observations
+            if is_located(obj) {
+                return 1;
+            }
 
             obj.values().map(count_submetrics).sum::<usize>()
         }
@@ -100,6 +266,60 @@ fn count_metrics(metrics: &BTreeMap<String, serde_json::Value>) -> usize {
     metrics.values().map(count_submetrics).sum::<usize>()
 }
 
+/// Detects the serialized form of the `Located` observer
+fn is_located(obj: &serde_json::Map<String, serde_json::Value>) -> bool {
+    obj.len() == 2 && obj.contains_key("values") && obj.contains_key("locations")
+}
+
+fn extract_sublocations(
+    pre_path: &mut String,
+    result: &mut BTreeMap<String, serde_json::Value>,
+    value: &mut serde_json::Value,
+) {
+    use serde_json::Value;
+
+    let Value::Object(obj) = value else {
+        return;
+    };
+
+    if is_located(obj) {
+        let locations = obj.remove("locations").unwrap();
+        let values = obj.remove("values").unwrap();
+        result.insert(pre_path.clone(), locations);
+        *value = values;
+        return;
+    }
+
+    for (name, value) in obj.iter_mut() {
+        pre_path.push('.');
+        pre_path.push_str(name);
+        extract_sublocations(pre_path, result, value);
+
+        for _ in 0..name.len() + 1 {
+            pre_path.pop();
+        }
+    }
+}
+
+/// Moves the locations recorded by `Located` observers out of the metrics
+///
+/// The metrics are left with plain arrays of values (as if `Unaggregated` was used),
+/// the locations are returned keyed by the flattened metric names.
+pub fn extract_locations(
+    metrics: &mut BTreeMap<String, serde_json::Value>,
+) -> BTreeMap<String, serde_json::Value> {
+    let mut result = BTreeMap::new();
+    let mut pre_path = String::new();
+
+    for (name, value) in metrics.iter_mut() {
+        pre_path.push_str(name);
+        extract_sublocations(&mut pre_path, &mut result, value);
+        pre_path.clear();
+    }
+
+    result
+}
+
 fn flatten_submetrics(
     pre_path: &mut String,
     result: &mut BTreeMap<String, serde_json::Value>,
@@ -143,169 +363,725 @@ pub fn flatten_metrics(
     result
 }
 
-fn collect_file_metrics(files: &[FileAst]) -> Result<BTreeMap<String, serde_json::Value>> {
-    let collectors = metrics::get_metric_collectors();
+/// The metrics of a single file (or a part of it), before the aggregation
+#[derive(Default)]
+struct FileMetrics {
+    /// one per collector, `None` if the file could not be parsed by syn
+    collectors: Option<Vec<FileMetric>>,
+    /// `None` if RCA metrics are not collected or the file was rejected by RCA
+    rca: Option<FileMetric>,
+}
 
-    info!("Collecting metrics from {} files...", files.len());
-    let collect_metrics_span = info_span!("collect_metrics").entered();
-    let metrics = collectors
-        // I would __like__ to use `par_iter`, but we hit deadlocks for some reason..
-        .iter()
-        .map(|collector| {
-            let _span = info_span!(parent: collect_metrics_span.id(), "collect_metric", metric = collector.name()).entered();
+/// Everything collected from a single file
+struct FileResult {
+    path: String,
+    /// size of the file in bytes, 0 if it could not be read
+    bytes: usize,
+    whole: Arc<FileMetrics>,
+    /// the file split by class, usually it's a single part sharing the metrics with `whole`
+    parts: Vec<(FileClass, Arc<FileMetrics>)>,
+    /// why some of the metrics of the file are missing
+    skipped: Vec<SkippedFile>,
+    /// the number of items syn could not parse, the rest of the file is still counted
+    recovered_items: usize,
+    /// the macro invocations whose arguments are counted, and the ones whose are not
+    macros: MacroCoverage,
+}
 
-            (
-                collector.name().to_string(),
-                collector.collect_metric(files),
-            )
-        })
-        .collect::<BTreeMap<_, _>>();
-    collect_metrics_span.exit();
+impl FileResult {
+    fn unreadable(skipped: SkippedFile) -> Self {
+        Self {
+            path: skipped.path.clone(),
+            bytes: 0,
+            recovered_items: 0,
+            macros: Default::default(),
+            whole: Default::default(),
+            parts: vec![],
+            skipped: vec![skipped],
+        }
+    }
+}
+
+/// The metrics of a file before it's classified: of the whole file, and of its main and test parts if it has test items
+///
+/// The parts only depend on the content of the file, so they can be cached.
+struct FileParts {
+    /// whether the whole file is `#![cfg(test)]`
+    test_file: bool,
+    whole: FileMetrics,
+    split: Option<(FileMetrics, FileMetrics)>,
+    /// the stages the file failed at, such parts are not cached
+    skipped: Vec<SkippedFile>,
+    /// see `FileResult::recovered_items`
+    recovered_items: usize,
+    macros: MacroCoverage,
+}
+
+impl FileParts {
+    fn parts(&self) -> impl Iterator<Item = &FileMetrics> {
+        std::iter::once(&self.whole)
+            .chain(self.split.iter().flat_map(|(main, tests)| [main, tests]))
+    }
+}
+
+/// The outcome of parsing a file, `Err` tells why the file is skipped
+type Parsed<T> = Result<T, SkippedFile>;
+
+/// Parses a file with syn and, if `rca` is set, with Rust Code Analysis
+///
+/// A file rejected by one of them still has the metrics of the other one.
+fn parse_file(file: &FileText, rca: bool) -> (Parsed<ParsedFile>, Option<Parsed<FuncSpace>>) {
+    // both of the parsers are slow on large files, so they run side by side
+    let (funcspace, ast) = rayon::join(|| rca.then(|| parse_rca(file)), || file.parse());
+    (ast, funcspace)
+}
+
+/// The collectors to run on the files, along with the cache of their results
+pub struct Analyzer {
+    config: CollectConfig,
+    collectors: Vec<MetricCollectorBox>,
+    rca: Option<RcaCollector>,
+    cache: Option<MetricCache>,
+}
+
+impl Analyzer {
+    pub fn new(config: &CollectConfig) -> Self {
+        Self {
+            config: config.clone(),
+            collectors: config.metric_collectors(),
+            rca: config.rca_collector(),
+            cache: config
+                .cache_dir
+                .as_ref()
+                .map(|dir| MetricCache::new(dir.clone(), config.observer)),
+        }
+    }
+
+    /// Runs a custom collector along with the ones enabled by the config
+    ///
+    /// Its metrics are named after it, like the ones of the built-in collectors.
+    pub fn with_collector(mut self, collector: MetricCollectorBox) -> Self {
+        self.collectors.push(collector);
+        self
+    }
+
+    fn collect_parts(&self, file: &FileText) -> FileParts {
+        let (parsed, funcspace) = parse_file(file, self.rca.is_some());
+        let mut skipped = vec![];
+        // the spans are only looked up while `parsed` is alive, so the source map can drop the file after it
+        let parsed = parsed.map_err(|e| skipped.push(e)).ok();
+        let ast = parsed.as_ref().map(|parsed| &parsed.ast);
+        let recovered_items = parsed.as_ref().map(|parsed| parsed.recovered_items);
+        let funcspace = funcspace.and_then(|space| space.map_err(|e| skipped.push(e)).ok());
+
+        let metrics_of =
+            |ast: Option<&FileAst>,
+             funcspace: Option<&FuncSpace>,
+             macros: Option<&mut MacroCoverage>| FileMetrics {
+                collectors: ast.map(|ast| metrics::collect_file(&self.collectors, ast, macros)),
+                rca: self
+                    .rca
+                    .as_ref()
+                    .zip(funcspace)
+                    .map(|(rca, space)| rca.collect_file(space)),
+            };
+
+        // only the whole file is looked at for the coverage, the split parts would count the macros again
+        let mut macros = MacroCoverage::default();
+        let (whole, split) = rayon::join(
+            || metrics_of(ast, funcspace.as_ref(), Some(&mut macros)),
+            || {
+                let split = class::split_tests(ast?)?;
+                let (main_space, test_space) = funcspace
+                    .as_ref()
+                    .map(|space| class::split_funcspace(space, &split.test_lines))
+                    .unzip();
+                Some(rayon::join(
+                    || metrics_of(Some(&split.main), main_space.as_ref(), None),
+                    || metrics_of(Some(&split.tests), test_space.as_ref(), None),
+                ))
+            },
+        );
+
+        FileParts {
+            test_file: ast.is_some_and(class::is_test_file),
+            whole,
+            split,
+            skipped,
+            recovered_items: recovered_items.unwrap_or(0),
+            macros,
+        }
+    }
+
+    /// Takes the parts from the cache, `None` if any of the collectors is missing there
+    fn decode_parts(&self, cached: &CachedFile) -> Result<Option<FileParts>> {
+        let decode_part = |part: usize| -> Result<Option<FileMetrics>> {
+            let mut collectors = Vec::with_capacity(self.collectors.len());
+            for collector in &self.collectors {
+                let Some(encoded) = cached.metrics.get(&cache::collector_key(collector)) else {
+                    return Ok(None);
+                };
+                collectors.push(collector.decode(&encoded[part])?);
+            }
+            let rca = match &self.rca {
+                Some(rca) => match cached.metrics.get(&cache::rca_key(rca)) {
+                    Some(encoded) => Some(rca.decode(&encoded[part])?),
+                    None => return Ok(None),
+                },
+                None => None,
+            };
+            Ok(Some(FileMetrics {
+                collectors: Some(collectors),
+                rca,
+            }))
+        };
+
+        let Some(whole) = decode_part(0)? else {
+            return Ok(None);
+        };
+        let split = if cached.split {
+            match (decode_part(1)?, decode_part(2)?) {
+                (Some(main), Some(tests)) => Some((main, tests)),
+                _ => return Ok(None),
+            }
+        } else {
+            None
+        };
+
+        Ok(Some(FileParts {
+            test_file: cached.test_file,
+            whole,
+            split,
+            skipped: vec![],
+            recovered_items: cached.recovered_items,
+            macros: cached.macros,
+        }))
+    }
+
+    /// Adds the metrics of the collectors to the cached ones (which could be collected with other collectors)
+    fn encode_parts(&self, parts: &FileParts, cached: &mut CachedFile) -> Result<()> {
+        cached.test_file = parts.test_file;
+        cached.recovered_items = parts.recovered_items;
+        cached.macros = parts.macros;
+        cached.split = parts.split.is_some();
+        for (i, collector) in self.collectors.iter().enumerate() {
+            let encoded = parts
+                .parts()
+                .map(|part| {
+                    collector.encode(&part.collectors.as_ref().expect("BUG: no metrics")[i])
+                })
+                .collect::<Result<Vec<_>>>()?;
+            cached
+                .metrics
+                .insert(cache::collector_key(collector), encoded);
+        }
+        if let Some(rca) = &self.rca {
+            let encoded = parts
+                .parts()
+                .map(|part| rca.encode(part.rca.as_ref().expect("BUG: no RCA metrics")))
+                .collect::<Result<Vec<_>>>()?;
+            cached.metrics.insert(cache::rca_key(rca), encoded);
+        }
+        Ok(())
+    }
+
+    /// Computes the parts of a file or takes them from the cache
+    ///
+    /// The files that failed to parse are not cached, so that they are reported again by the next runs.
+    fn file_parts(&self, file: &FileText) -> FileParts {
+        let Some(cache) = &self.cache else {
+            return self.collect_parts(file);
+        };
+
+        let mut cached = cache.get(file).unwrap_or_default();
+        match self.decode_parts(&cached) {
+            Ok(Some(parts)) => return parts,
+            Ok(None) => {}
+            Err(e) => warn!("Cannot decode cached metrics of {}: {:#}", file.path, e),
+        }
+
+        let parts = self.collect_parts(file);
+        if !parts.skipped.is_empty() {
+            return parts;
+        }
+        match self.encode_parts(&parts, &mut cached) {
+            Ok(()) => cache.set(file, &cached),
+            Err(e) => warn!("Cannot encode metrics of {}: {:#}", file.path, e),
+        }
+        parts
+    }
+
+    fn analyze_file(&self, file: FileText) -> FileResult {
+        let parts = self.file_parts(&file);
+        let class = if parts.test_file {
+            FileClass::Test
+        } else {
+            class::classify(&file)
+        };
+
+        let whole = Arc::new(parts.whole);
+        let split = match parts.split {
+            Some((main, tests)) if class.has_inline_tests() => {
+                vec![(class, Arc::new(main)), (FileClass::Test, Arc::new(tests))]
+            }
+            _ => vec![(class, whole.clone())],
+        };
+
+        FileResult {
+            path: file.path.to_string(),
+            bytes: file.content.len(),
+            whole,
+            parts: split,
+            skipped: parts.skipped,
+            recovered_items: parts.recovered_items,
+            macros: parts.macros,
+        }
+    }
+
+    /// Analyzes a file, or reports that it could not be read
+    fn analyze_source(&self, file: Result<FileText, SkippedFile>) -> FileResult {
+        match file {
+            Ok(file) => self.analyze_file(file),
+            Err(skipped) => FileResult::unreadable(skipped),
+        }
+    }
+
+    fn analyze_files(&self, raw_files: Vec<FileText>) -> Vec<FileResult> {
+        self.analyze_sources(raw_files, Ok)
+    }
+
+    /// Analyzes the files one by one, loading each of them only when it's its turn
+    ///
+    /// Only the metrics are kept, so the memory is not proportional to the size of the repo,
+    /// the text and the syntax trees of a file are dropped as soon as it's analyzed.
+    fn analyze_sources<T: Send>(
+        &self,
+        sources: Vec<T>,
+        load: impl Fn(T) -> Result<FileText, SkippedFile> + Sync,
+    ) -> Vec<FileResult> {
+        info!("Collecting metrics from {} files...", sources.len());
+        // a single span for all the files: the progress bars of the spans are updated under a lock
+        let span = info_span!("collect_files");
+        span.pb_set_style(&progressbar_style());
+        span.pb_set_length(sources.len() as u64);
+        let _enter = span.enter();
+
+        sources
+            .into_par_iter()
+            .map(|source| {
+                let result = self.analyze_source(load(source));
+                span.pb_inc(1);
+                result
+            })
+            .collect()
+    }
+
+    /// Analyzes the files as they are received, while the rest of them are still being downloaded
+    ///
+    /// The manifests are kept aside for the packages, the results are sorted by path so that they don't depend on the
+    /// order the files came in.
+    fn analyze_received(
+        &self,
+        mut files: Receiver<Result<FileText, SkippedFile>>,
+    ) -> (Vec<FileText>, Vec<FileResult>) {
+        info!("Collecting metrics from the files as they are downloaded...");
+        let span = info_span!("collect_files");
+        span.pb_set_style(&progressbar_style());
+        let _enter = span.enter();
+
+        let (manifests, mut results) = std::iter::from_fn(|| files.blocking_recv())
+            .inspect(|_| span.pb_inc_length(1))
+            .par_bridge()
+            .partition_map::<Vec<_>, Vec<_>, _, _, _>(|file| {
+                let result = match file {
+                    Ok(file) if is_manifest(file.path.as_str()) => Either::Left(file),
+                    file => Either::Right(self.analyze_source(file)),
+                };
+                span.pb_inc(1);
+                result
+            });
+        results.sort_by(|a, b| a.path.cmp(&b.path));
+        (manifests, results)
+    }
+
+    fn aggregate_file_metrics(
+        &self,
+        files: &[&FileMetrics],
+    ) -> Result<BTreeMap<String, serde_json::Value>> {
+        let collect_rca = |rca: &RcaCollector| {
+            let metrics = files
+                .iter()
+                .filter_map(|f| f.rca.clone())
+                .collect::<Vec<_>>();
+            Ok::<_, anyhow::Error>(("rca".to_string(), rca.aggregate(&metrics)?))
+        };
+        let (mut metrics, rca_metrics) = rayon::join(
+            || {
+                self.collectors
+                    .par_iter()
+                    .enumerate()
+                    .map(|(i, collector)| {
+                        let file_metrics = files
+                            .iter()
+                            .filter_map(|f| Some(f.collectors.as_ref()?[i].clone()))
+                            .collect::<Vec<_>>();
+                        (
+                            collector.name().to_string(),
+                            collector.aggregate(&file_metrics),
+                        )
+                    })
+                    .collect::<BTreeMap<_, _>>()
+            },
+            || self.rca.as_ref().map(collect_rca).transpose(),
+        );
+        metrics.extend(rca_metrics?);
+
+        Ok(metrics)
+    }
+}
+
+/// A set of files the metrics are aggregated over
+enum FileGroup<'a> {
+    Repo,
+    Class(FileClass),
+    Package(&'a str),
+}
+
+/// Aggregates the metrics over all the files, and then separately for every class of files and every package
+fn aggregate_results(
+    analyzer: &Analyzer,
+    workspace: &Workspace,
+    results: &[impl Borrow<FileResult>],
+) -> Result<BTreeMap<String, serde_json::Value>> {
+    let mut groups = vec![(
+        FileGroup::Repo,
+        results
+            .iter()
+            .map(|r| &*r.borrow().whole)
+            .collect::<Vec<_>>(),
+    )];
+
+    for class in FileClass::ALL {
+        let parts = results
+            .iter()
+            .flat_map(|r| &r.borrow().parts)
+            .filter(|(c, _)| *c == class)
+            .map(|(_, part)| &**part)
+            .collect::<Vec<_>>();
+        if !parts.is_empty() {
+            groups.push((FileGroup::Class(class), parts));
+        }
+    }
+
+    // a single package would just repeat the repo-wide metrics
+    if workspace.packages().len() > 1 {
+        let mut package_files = BTreeMap::<_, Vec<_>>::new();
+        for result in results {
+            let result = result.borrow();
+            if let Some(package) = workspace.package_of(&result.path) {
+                package_files
+                    .entry(package.name.as_str())
+                    .or_default()
+                    .push(&*result.whole);
+            }
+        }
+        groups.extend(
+            package_files
+                .into_iter()
+                .map(|(name, files)| (FileGroup::Package(name), files)),
+        );
+    }
+
+    let aggregated = groups
+        .into_par_iter()
+        .map(|(group, files)| Ok((group, analyzer.aggregate_file_metrics(&files)?)))
+        .collect::<Result<Vec<_>>>()?;
+
+    let mut metrics = BTreeMap::new();
+    let mut packages = BTreeMap::new();
+    for (group, group_metrics) in aggregated {
+        match group {
+            FileGroup::Repo => metrics.extend(group_metrics),
+            FileGroup::Class(class) => {
+                metrics.insert(
+                    class.name().to_string(),
+                    serde_json::to_value(group_metrics)?,
+                );
+            }
+            FileGroup::Package(name) => {
+                packages.insert(name, serde_json::to_value(group_metrics)?);
+            }
+        }
+    }
+    if !packages.is_empty() {
+        metrics.insert("package".to_string(), serde_json::to_value(packages)?);
+    }
 
     info!(
-        "Collected {} file metrics (histograms are counted as one)!",
+        "Collected {} metrics (histograms are counted as one)!",
         count_metrics(&metrics)
     );
 
     Ok(metrics)
 }
 
-pub fn collect_local_repo(repo_path: &Path) -> Result<RepoResult> {
-    // we could have implemented it with gix, but it's a large dep for minor gains
-    let meta = RepoMetadata {
-        url: "<LOCAL>".to_string(),
-        commit: "<LOCAL>".to_string(),
-    };
+impl Analyzer {
+    /// Collects the metrics of the files, the manifests among them are used to find the packages
+    ///
+    /// The files don't have to come from disk, and the `meta` of the result is left unknown.
+    pub fn collect_files(&self, files: Vec<FileText>) -> Result<RepoResult> {
+        let (metrics, diagnostics) = self.collect_text_files(files)?;
+        Ok(repo_result(RepoMetadata::unknown(), metrics, diagnostics))
+    }
 
-    info!("Loading files from {}...", repo_path.display());
-    let load_files_span = info_span!("load_files").entered();
-    let raw_files = ignore::WalkBuilder::new(repo_path)
-        .sort_by_file_name(Ord::cmp)
-        .require_git(true)
-        .build()
-        .filter_map(|v| v.map_err(|e| error!("Error during listing: {:?}", e)).ok())
-        .filter(|v| v.path().extension() == Some(OsStr::new("rs")))
-        .filter(|v| v.file_type().is_some_and(|t| t.is_file()))
-        .filter_map(|v| {
-            let path =
-                pathdiff::diff_paths(v.path(), repo_path).expect("BUG: found path not in repo");
-            File::read(repo_path, &path)
-        })
-        .collect::<Vec<_>>();
+    fn collect_text_files(
+        &self,
+        files: Vec<FileText>,
+    ) -> Result<(BTreeMap<String, serde_json::Value>, Diagnostics)> {
+        let (raw_files, workspace) = Workspace::split_manifests(files);
+        let results = self.analyze_files(raw_files);
+        let metrics = aggregate_results(self, &workspace, &results)?;
+        Ok((metrics, Diagnostics::of(&results)))
+    }
 
-    let (files, rca_files) = raw_files
-        .into_par_iter()
-        .filter_map(|f| Some((File::parse(f.clone(), Span::current())?, f)))
-        .filter_map(|(file, rca_file)| {
-            let funcspace = parse_rca(&rca_file);
-            if funcspace.is_none() {
-                error!(
-                    "Failed to parse {} with Rust Code Analysis, skipping..",
-                    rca_file.path
-                )
+    /// Collects the metrics of a checked out repository, with the uncommitted changes
+    pub fn collect_local_repo(&self, repo_path: &Path) -> Result<RepoResult> {
+        let filter = self.config.file_filter()?;
+
+        // we could have implemented it with gix, but it's a large dep for minor gains
+        let meta = match git::LocalRepo::open(repo_path).and_then(|r| r.working_tree_metadata()) {
+            Ok(meta) => meta,
+            Err(e) => {
+                warn!("Cannot read git metadata: {:#}", e);
+                RepoMetadata::unknown()
             }
-            Some((file, funcspace?))
-        })
-        .collect::<(Vec<_>, Vec<_>)>();
-    load_files_span.exit();
+        };
+
+        info!("Listing files in {}...", repo_path.display());
+        let list_files_span = info_span!("list_files").entered();
+        let (manifests, paths) = ignore::WalkBuilder::new(repo_path)
+            .sort_by_file_name(Ord::cmp)
+            .require_git(true)
+            .build()
+            .filter_map(|v| v.map_err(|e| error!("Error during listing: {:?}", e)).ok())
+            .filter(|v| {
+                v.path().extension() == Some(OsStr::new("rs"))
+                    || v.file_name() == OsStr::new("Cargo.toml")
+            })
+            .filter(|v| v.file_type().is_some_and(|t| t.is_file()))
+            .map(|v| {
+                pathdiff::diff_paths(v.path(), repo_path).expect("BUG: found path not in repo")
+            })
+            // non-UTF-8 paths are passed through, so that `File::read` reports them
+            .filter(|path| {
+                RelativePathBuf::from_path(path).map_or(true, |p| filter.wants(p.as_str()))
+            })
+            .partition::<Vec<_>, _>(|path| path.file_name() == Some(OsStr::new("Cargo.toml")));
+        list_files_span.exit();
+
+        let manifests = manifests
+            .iter()
+            .filter_map(|path| File::read(repo_path, path).ok())
+            .collect::<Vec<_>>();
+        let workspace = Workspace::from_manifests(&manifests);
+
+        // the rust files are read as they are analyzed, so that only a few of them are in memory at a time
+        let results = self.analyze_sources(paths, |path| File::read(repo_path, &path));
+        let metrics = aggregate_results(self, &workspace, &results)?;
+
+        Ok(repo_result(meta, metrics, Diagnostics::of(&results)))
+    }
+
+    /// Collects the metrics of a local git repository at the given revision
+    ///
+    /// The working tree is not touched, the files are read straight from the git object database.
+    pub fn collect_local_revision(&self, repo_path: &Path, rev: &str) -> Result<RepoResult> {
+        let repo = git::LocalRepo::open(repo_path)?;
+        let meta = repo.metadata(rev)?;
+
+        info!(
+            "Loading files from {} at {}...",
+            repo_path.display(),
+            meta.commit
+        );
+        let (manifests, raw_files) = repo
+            .read_files_at(&meta.commit, &self.config.file_filter()?)?
+            .into_iter()
+            .partition::<Vec<_>, _>(|f| f.as_ref().is_ok_and(|f| is_manifest(f.path.as_str())));
+        let workspace =
+            Workspace::from_manifests(&manifests.into_iter().flatten().collect::<Vec<_>>());
+
+        let results = self.analyze_sources(raw_files, |f| f);
+        let metrics = aggregate_results(self, &workspace, &results)?;
 
-    let rust_analysis_metrics = collect_rust_code_analysis::<Unaggregated<f64>>(&rca_files)?;
-    let mut metrics = collect_file_metrics(&files)?;
-    metrics.extend(rust_analysis_metrics);
+        Ok(repo_result(meta, metrics, Diagnostics::of(&results)))
+    }
+}
+
+fn repo_result(
+    meta: RepoMetadata,
+    mut metrics: BTreeMap<String, serde_json::Value>,
+    diagnostics: Diagnostics,
+) -> RepoResult {
+    let locations = extract_locations(&mut metrics);
     let metrics = flatten_metrics(&metrics);
 
-    Ok(RepoResult { meta, metrics })
+    if !diagnostics.skipped.is_empty() {
+        warn!(
+            "{} of {} files are missing from some of the metrics, see `diagnostics`",
+            diagnostics.coverage.files_seen - diagnostics.coverage.files_analyzed,
+            diagnostics.coverage.files_seen
+        );
+    }
+    if diagnostics.listing_truncated {
+        warn!("Some of the files could not be listed, they are missing from the metrics");
+    }
+
+    RepoResult {
+        meta,
+        metrics,
+        locations,
+        diagnostics,
+    }
+}
+
+/// Collects the metrics of a checked out repository, see `Analyzer::collect_local_repo`
+pub fn collect_local_repo(repo_path: &Path, config: &CollectConfig) -> Result<RepoResult> {
+    Analyzer::new(config).collect_local_repo(repo_path)
+}
+
+/// Collect metrics for the files of a local git repository at the given revision
+///
+/// The working tree is not touched, the files are read straight from the git object database.
+pub fn collect_local_revision(
+    repo_path: &Path,
+    rev: &str,
+    config: &CollectConfig,
+) -> Result<RepoResult> {
+    Analyzer::new(config).collect_local_revision(repo_path, rev)
 }
 
-fn valid_space(space: &FuncSpace) -> Option<()> {
+/// Finds a space RCA could not make sense of
+fn invalid_space(space: &FuncSpace) -> Option<&FuncSpace> {
     use ::rust_code_analysis::SpaceKind;
     if let SpaceKind::Unknown = space.kind {
-        return None;
+        return Some(space);
     }
-    space
-        .spaces
-        .iter()
-        .map(valid_space)
-        .collect::<Option<Vec<()>>>()?;
-    Some(())
+    space.spaces.iter().find_map(invalid_space)
 }
 
-fn parse_rca(file: &File<String>) -> Option<FuncSpace> {
+fn parse_rca(file: &File<String>) -> Result<FuncSpace, SkippedFile> {
+    let rejected = |message: &str| {
+        error!(
+            "Failed to parse {} with Rust Code Analysis ({}), skipping..",
+            file.path, message
+        );
+        SkippedFile::new(file.path.as_str(), Stage::Rca, message)
+    };
+
     let parser = RustParser::new(
         file.content.as_bytes().to_vec(),
         &file.path.to_path(""),
         None,
     );
-    let space = ::rust_code_analysis::metrics(&parser, &file.path.to_path(""))?;
-    valid_space(&space)?;
-    Some(space)
-}
-
-pub fn collect_rust_code_analysis<Obs: Observer<f64> + Default + Serialize>(
-    file_metrics: &[FuncSpace],
-) -> Result<BTreeMap<String, serde_json::Value>> {
-    let mut statisics = RCAMetricsKinded::<Obs>::default();
-    for function_space in file_metrics {
-        statisics.observe_spaces(&function_space);
+    let space = ::rust_code_analysis::metrics(&parser, &file.path.to_path(""))
+        .ok_or_else(|| rejected("no metrics computed"))?;
+    if let Some(invalid) = invalid_space(&space) {
+        let message = match &invalid.name {
+            Some(name) => format!("unknown kind of space `{}`", name),
+            None => "unknown kind of space".to_string(),
+        };
+        return Err(rejected(&message).at(Position {
+            line: invalid.start_line,
+            column: 0,
+        }));
     }
-
-    let metrics = serde_json::to_value(statisics)?;
-
-    info!("Collected {} RCA metrics!", count_submetrics(&metrics));
-
-    Ok(BTreeMap::from([("rca".to_string(), metrics)]))
+    Ok(space)
 }
 
+/// Collects a GitHub repository, `owner/repo` or `owner/repo@ref` (a branch, a tag or a commit)
 #[instrument(skip(crab))]
-pub async fn collect_github_repo(crab: &LimitedCrab, repo_name: &str) -> Result<RepoResult> {
-    info!("Downloading https://github.com/{}...", repo_name);
-
-    let commit = crab.get_latest_commit(repo_name).await?;
+pub async fn collect_github_repo(
+    crab: &LimitedCrab,
+    repo: &str,
+    config: &CollectConfig,
+) -> Result<RepoResult> {
+    let repo: GithubRepo = repo.parse()?;
+    let repo_name = repo.name.as_str();
+    info!("Downloading {}...", repo);
 
-    let text_files = git::fetch_repo(crab, repo_name, &commit)
+    let info = crab
+        .get_repo_info(repo_name)
         .await
-        .context("Fetching repo")?;
-
-    let (files, rca_files) = tokio::task::block_in_place(|| {
-        let span = info_span!("parse_files").entered();
+        .context("Cannot get repo info")?;
+    let (commit, branch) = match &repo.reference {
+        Some(reference) => {
+            let commit = crab
+                .get_commit(repo_name, reference)
+                .await
+                .with_context(|| format!("Cannot resolve `{}`", reference))?;
+            let is_branch = crab.is_branch(repo_name, reference).await?;
+            (commit, is_branch.then(|| reference.clone()))
+        }
+        // the latest commit is taken from the default branch
+        None => (
+            crab.get_latest_commit(repo_name).await?,
+            info.default_branch.clone(),
+        ),
+    };
 
-        text_files
-            .into_par_iter()
-            .filter_map(|f| Some((File::parse(f.clone(), span.deref().clone())?, f)))
-            .filter_map(|(file, rca_file)| {
-                let funcspace = parse_rca(&rca_file);
-                if funcspace.is_none() {
-                    error!(
-                        "Failed to parse {} for with Rust Code Analysis, skipping completely..",
-                        &rca_file.path
-                    )
-                }
-                Some((file, funcspace?))
-            })
-            .collect::<(Vec<_>, Vec<_>)>()
+    // the files are analyzed as they are downloaded, so that only a few of them are in memory at a time
+    let (sender, receiver) = mpsc::channel(64);
+    let analyzer = Analyzer::new(config);
+    let analysis = tokio::task::spawn_blocking(move || {
+        let (manifests, results) = analyzer.analyze_received(receiver);
+        let workspace = Workspace::from_manifests(&manifests);
+        let metrics = aggregate_results(&analyzer, &workspace, &results)?;
+        Ok::<_, anyhow::Error>((metrics, Diagnostics::of(&results)))
     });
+    let fetched = git::fetch_repo(
+        crab,
+        repo_name,
+        &commit.sha,
+        &config.file_filter()?,
+        config.fetch,
+        &sender,
+    )
+    .await;
+    // lets the analysis finish
+    drop(sender);
+    let analyzed = analysis.await.context("Analyzing the files")?;
+    let complete = fetched.context("Fetching repo")?;
 
-    let mut metrics = tokio::task::block_in_place(move || collect_file_metrics(&files))?;
-    let rca_metrics = tokio::task::block_in_place(|| {
-        collect_rust_code_analysis::<Unaggregated<f64>>(&rca_files)
-    })?;
-    metrics.extend(rca_metrics);
-    metrics.extend(
-        git::get_repo_metrics(crab, repo_name)
-            .await
-            .context("Getting repo metrics")?,
-    );
+    let (mut metrics, mut diagnostics) = analyzed?;
+    diagnostics.listing_truncated = !complete;
+    if config.repo_enabled() {
+        metrics.extend(
+            git::get_repo_metrics(crab, repo_name, &commit.sha, &info)
+                .await
+                .context("Getting repo metrics")?,
+        );
+    }
 
     info!("Collected {} total metrics", count_metrics(&metrics));
-    let metrics = flatten_metrics(&metrics);
-    info!("Flattened metrics have {} values", metrics.len());
 
     let meta = RepoMetadata {
-        url: format!("git@github.com:{}.git", repo_name),
-        commit,
+        // the host is different for GitHub Enterprise
+        url: info
+            .ssh_url
+            .clone()
+            .unwrap_or_else(|| format!("git@github.com:{}.git", repo_name)),
+        commit: commit.sha,
+        branch,
+        dirty: false,
+        timestamp: commit.commit.committer.and_then(|c| c.date),
+        reference: repo.reference,
     };
 
-    Ok(RepoResult { meta, metrics })
+    let result = repo_result(meta, metrics, diagnostics);
+    info!("Flattened metrics have {} values", result.metrics.len());
+
+    Ok(result)
 }
 
 fn progressbar_style() -> ProgressStyle {
@@ -315,11 +1091,18 @@ fn progressbar_style() -> ProgressStyle {
         .progress_chars("#>-")
 }
 
+/// Collects the repositories one by one, skipping (and logging) the ones that fail
 #[instrument(skip(crab, repo_list))]
 pub async fn bulk_collect_github_repos(
     crab: &LimitedCrab,
     repo_list: &[&str],
+    config: &CollectConfig,
 ) -> Result<Vec<RepoResult>> {
+    // fail on a typo before spending the time on the repos before it
+    for repo in repo_list {
+        repo.parse::<GithubRepo>()?;
+    }
+
     let span = Span::current();
     span.pb_set_style(&progressbar_style());
     span.pb_set_length(repo_list.len() as u64);
@@ -327,14 +1110,144 @@ pub async fn bulk_collect_github_repos(
     let mut results = Vec::with_capacity(repo_list.len());
 
     for repo_name in repo_list {
-        let result = collect_github_repo(crab, repo_name)
-            .await
-            .with_context(|| format!("Collecting metrics for {}", repo_name))?;
+        // an empty or deleted repository shouldn't lose the results of the others
+        match collect_github_repo(crab, repo_name, config).await {
+            Ok(result) => results.push(result),
+            Err(e) => error!(
+                "Cannot collect metrics for {}, skipping: {:#}",
+                repo_name, e
+            ),
+        }
 
         span.pb_inc(1);
+    }
 
-        results.push(result);
+    if results.is_empty() && !repo_list.is_empty() {
+        bail!("Could not collect metrics for any of the repositories");
     }
+    if results.len() < repo_list.len() {
+        warn!(
+            "Skipped {} of {} repositories, see the errors above",
+            repo_list.len() - results.len(),
+            repo_list.len()
+        );
+    }
+
+    Ok(results)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Analyzer, CollectConfig, FileText, MetricValue};
+    use crate::collector::metrics::schema::{MetricSchema, Scope, Unit};
+    use crate::collector::metrics::util::VisitorCollector;
+    use crate::collector::metrics::MetricCollector;
+    use crate::collector::ObserverKind;
+    use std::sync::mpsc;
+    use std::time::Duration;
+
+    #[derive(Default)]
+    struct FnCount(u64);
 
-    Ok(results.into_iter().collect::<Vec<_>>())
+    impl syn::visit::Visit<'_> for FnCount {
+        fn visit_item_fn(&mut self, i: &syn::ItemFn) {
+            self.0 += 1;
+            syn::visit::visit_item_fn(self, i);
+        }
+    }
+
+    const SCHEMA: &[MetricSchema] = &[MetricSchema::new(
+        "",
+        Scope::Repo,
+        Unit::Count,
+        "Number of free functions",
+    )];
+
+    #[test]
+    fn custom_collector() {
+        let collector = VisitorCollector::new(
+            "fn_count",
+            SCHEMA,
+            FnCount::default(),
+            |v| v.0,
+            |v| v.iter().sum::<u64>(),
+        );
+        let result = Analyzer::new(&CollectConfig::default())
+            .with_collector(collector.make_box())
+            .collect_files(vec![
+                FileText::new("src/lib.rs", "fn a() {} fn b(x: bool) { if x {} }".into()),
+                FileText::new("src/main.rs", "fn main() {}".into()),
+            ])
+            .unwrap();
+        assert_eq!(result.values("fn_count"), Some(vec![3.0]));
+        assert_eq!(result.values("if_count"), Some(vec![1.0]));
+        assert_eq!(result.values("no_such_metric"), None);
+    }
+
+    #[test]
+    fn typed_metrics() {
+        let config = CollectConfig {
+            observer: ObserverKind::Histogram,
+            ..Default::default()
+        };
+        let result = Analyzer::new(&config)
+            .collect_files(vec![FileText::new("src/lib.rs", "struct A;".into())])
+            .unwrap();
+        assert_eq!(result.metric("if_count"), Some(MetricValue::Number(0.0)));
+        assert_eq!(
+            result.metric("complexity.all_fn.mode"),
+            Some(MetricValue::Missing)
+        );
+        assert_eq!(result.values("complexity.all_fn.mode"), None);
+        assert!(result
+            .iter_metrics()
+            .any(|(name, value)| name == "complexity.all_fn.sum"
+                && value == MetricValue::Number(0.0)));
+    }
+
+    /// The files are parsed, walked and dropped on many threads at once, with the spans resolved on whichever
+    /// thread runs the collectors, and the collectors aggregated in parallel too
+    ///
+    /// It runs on a watchdog, so that a deadlock fails the test instead of hanging it.
+    #[test]
+    fn nested_parallelism() {
+        let files = (0..200)
+            .map(|i| {
+                let content = format!("{}fn f{}() {{ if true {{}} }}\n", "\n".repeat(i % 7), i);
+                FileText::new(&format!("src/m{}.rs", i), content)
+            })
+            .collect::<Vec<_>>();
+        let config = CollectConfig {
+            observer: ObserverKind::Located,
+            ..CollectConfig::default()
+        };
+
+        let (sender, receiver) = mpsc::channel();
+        std::thread::spawn(move || {
+            let pool = rayon::ThreadPoolBuilder::new()
+                .num_threads(8)
+                .build()
+                .unwrap();
+            let result = pool.install(|| Analyzer::new(&config).collect_files(files));
+            let _ = sender.send(result);
+        });
+        let result = receiver
+            .recv_timeout(Duration::from_secs(120))
+            .expect("deadlocked while collecting")
+            .unwrap();
+
+        let mut locations = result
+            .locations("fn_depth")
+            .unwrap()
+            .into_iter()
+            .map(|location| location.unwrap())
+            .map(|location| (location.path, location.start.line))
+            .collect::<Vec<_>>();
+        locations.sort();
+        let mut expected = (0..200)
+            .map(|i| (format!("src/m{}.rs", i), i % 7 + 1))
+            .collect::<Vec<_>>();
+        expected.sort();
+        assert_eq!(locations, expected);
+    }
 }
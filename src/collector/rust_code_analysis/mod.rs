@@ -1,11 +1,40 @@
+mod schema;
+
+pub use schema::schema;
+
+use anyhow::Result;
 use rust_code_analysis::{
     cognitive, cyclomatic, exit, halstead, loc, mi, nargs, nom, CodeMetrics, FuncSpace,
 };
-use serde::Serialize;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::marker::PhantomData;
+use std::sync::Arc;
 
-use super::metrics::util::{Observer, Unaggregated};
+use super::metrics::util::{
+    FloatHist, Located, Location, Monoid, Observer, ObserverKind, Position, Unaggregated,
+};
+use super::metrics::{decode_metric, encode_metric, FileMetric};
 
-#[derive(Default, Serialize)]
+/// Implements `Monoid` for a struct of observers by uniting them field by field
+macro_rules! monoid_fields {
+    ($name:ident { $($field:ident),* $(,)? }) => {
+        impl<Obs: Monoid> Monoid for $name<Obs> {
+            fn init() -> Self {
+                Self {
+                    $($field: Monoid::init()),*
+                }
+            }
+
+            fn unite(self, rhs: Self) -> Self {
+                Self {
+                    $($field: self.$field.unite(rhs.$field)),*
+                }
+            }
+        }
+    };
+}
+
+#[derive(Default, Clone, Serialize, Deserialize)]
 pub struct Cognitive<Obs> {
     average: Obs,
     max: Obs,
@@ -14,19 +43,19 @@ pub struct Cognitive<Obs> {
 }
 
 impl<Obs: Observer<f64>> Cognitive<Obs> {
-    fn observe(&mut self, stats: &cognitive::Stats) {
+    fn observe(&mut self, stats: &cognitive::Stats, location: &impl Fn() -> Location) {
         let avg = stats.cognitive_average();
         if !avg.is_finite() {
             return;
         }
-        self.average.observe(avg);
-        self.max.observe(stats.cognitive_max());
-        self.min.observe(stats.cognitive_min());
-        self.sum.observe(stats.cognitive_sum());
+        self.average.observe_at(avg, location);
+        self.max.observe_at(stats.cognitive_max(), location);
+        self.min.observe_at(stats.cognitive_min(), location);
+        self.sum.observe_at(stats.cognitive_sum(), location);
     }
 }
 
-#[derive(Default, Serialize)]
+#[derive(Default, Clone, Serialize, Deserialize)]
 pub struct Cyclomatic<Obs> {
     average: Obs,
     max: Obs,
@@ -35,15 +64,16 @@ pub struct Cyclomatic<Obs> {
 }
 
 impl<Obs: Observer<f64>> Cyclomatic<Obs> {
-    fn observe(&mut self, stats: &cyclomatic::Stats) {
-        self.average.observe(stats.cyclomatic_average());
-        self.max.observe(stats.cyclomatic_max());
-        self.min.observe(stats.cyclomatic_min());
-        self.sum.observe(stats.cyclomatic_sum());
+    fn observe(&mut self, stats: &cyclomatic::Stats, location: &impl Fn() -> Location) {
+        self.average
+            .observe_at(stats.cyclomatic_average(), location);
+        self.max.observe_at(stats.cyclomatic_max(), location);
+        self.min.observe_at(stats.cyclomatic_min(), location);
+        self.sum.observe_at(stats.cyclomatic_sum(), location);
     }
 }
 
-#[derive(Default, Serialize)]
+#[derive(Default, Clone, Serialize, Deserialize)]
 #[allow(non_snake_case)]
 pub struct Halstead<Obs> {
     N1: Obs,
@@ -63,32 +93,32 @@ pub struct Halstead<Obs> {
 }
 
 impl<Obs: Observer<f64>> Halstead<Obs> {
-    fn observe(&mut self, stats: &halstead::Stats) {
+    fn observe(&mut self, stats: &halstead::Stats, location: &impl Fn() -> Location) {
         let mut stats = stats;
         let default_stats = halstead::Stats::default();
         // most metrics don't work with 0 u_operands or u_operators
         if stats.u_operands() == 0. || stats.u_operators() == 0. {
             stats = &default_stats;
         }
-        self.N1.observe(stats.operators());
-        self.N2.observe(stats.operands());
-        self.n1.observe(stats.u_operators());
-        self.n2.observe(stats.u_operands());
-        self.bugs.observe(stats.bugs());
-        self.difficulty.observe(stats.difficulty());
-        self.effort.observe(stats.effort());
+        self.N1.observe_at(stats.operators(), location);
+        self.N2.observe_at(stats.operands(), location);
+        self.n1.observe_at(stats.u_operators(), location);
+        self.n2.observe_at(stats.u_operands(), location);
+        self.bugs.observe_at(stats.bugs(), location);
+        self.difficulty.observe_at(stats.difficulty(), location);
+        self.effort.observe_at(stats.effort(), location);
         self.estimated_program_length
-            .observe(stats.estimated_program_length());
-        self.length.observe(stats.length());
-        self.level.observe(stats.level());
-        self.purity_ratio.observe(stats.purity_ratio());
-        self.time.observe(stats.time());
-        self.vocabulary.observe(stats.vocabulary());
-        self.volume.observe(stats.volume());
+            .observe_at(stats.estimated_program_length(), location);
+        self.length.observe_at(stats.length(), location);
+        self.level.observe_at(stats.level(), location);
+        self.purity_ratio.observe_at(stats.purity_ratio(), location);
+        self.time.observe_at(stats.time(), location);
+        self.vocabulary.observe_at(stats.vocabulary(), location);
+        self.volume.observe_at(stats.volume(), location);
     }
 }
 
-#[derive(Default, Serialize)]
+#[derive(Default, Clone, Serialize, Deserialize)]
 pub struct Loc<Obs> {
     blank: Obs,
     blank_average: Obs,
@@ -113,31 +143,32 @@ pub struct Loc<Obs> {
 }
 
 impl<Obs: Observer<f64>> Loc<Obs> {
-    fn observe(&mut self, stats: &loc::Stats) {
-        self.blank.observe(stats.blank());
-        self.blank_average.observe(stats.blank_average());
-        self.blank_max.observe(stats.blank_max());
-        self.blank_min.observe(stats.blank_min());
-        self.cloc.observe(stats.cloc());
-        self.cloc_average.observe(stats.cloc_average());
-        self.cloc_max.observe(stats.cloc_max());
-        self.cloc_min.observe(stats.cloc_min());
-        self.lloc.observe(stats.lloc());
-        self.lloc_average.observe(stats.lloc_average());
-        self.lloc_max.observe(stats.lloc_max());
-        self.lloc_min.observe(stats.lloc_min());
-        self.ploc.observe(stats.ploc());
-        self.ploc_average.observe(stats.ploc_average());
-        self.ploc_max.observe(stats.ploc_max());
-        self.ploc_min.observe(stats.ploc_min());
-        self.sloc.observe(stats.sloc());
-        self.sloc_average.observe(stats.sloc_average());
-        self.sloc_max.observe(stats.sloc_max());
-        self.sloc_min.observe(stats.sloc_min());
+    fn observe(&mut self, stats: &loc::Stats, location: &impl Fn() -> Location) {
+        self.blank.observe_at(stats.blank(), location);
+        self.blank_average
+            .observe_at(stats.blank_average(), location);
+        self.blank_max.observe_at(stats.blank_max(), location);
+        self.blank_min.observe_at(stats.blank_min(), location);
+        self.cloc.observe_at(stats.cloc(), location);
+        self.cloc_average.observe_at(stats.cloc_average(), location);
+        self.cloc_max.observe_at(stats.cloc_max(), location);
+        self.cloc_min.observe_at(stats.cloc_min(), location);
+        self.lloc.observe_at(stats.lloc(), location);
+        self.lloc_average.observe_at(stats.lloc_average(), location);
+        self.lloc_max.observe_at(stats.lloc_max(), location);
+        self.lloc_min.observe_at(stats.lloc_min(), location);
+        self.ploc.observe_at(stats.ploc(), location);
+        self.ploc_average.observe_at(stats.ploc_average(), location);
+        self.ploc_max.observe_at(stats.ploc_max(), location);
+        self.ploc_min.observe_at(stats.ploc_min(), location);
+        self.sloc.observe_at(stats.sloc(), location);
+        self.sloc_average.observe_at(stats.sloc_average(), location);
+        self.sloc_max.observe_at(stats.sloc_max(), location);
+        self.sloc_min.observe_at(stats.sloc_min(), location);
     }
 }
 
-#[derive(Default, Serialize)]
+#[derive(Default, Clone, Serialize, Deserialize)]
 pub struct MI<Obs> {
     mi_original: Obs,
     mi_sei: Obs,
@@ -145,7 +176,7 @@ pub struct MI<Obs> {
 }
 
 impl<Obs: Observer<f64>> MI<Obs> {
-    fn observe(&mut self, stats: &mi::Stats) {
+    fn observe(&mut self, stats: &mi::Stats, location: &impl Fn() -> Location) {
         let mut stats = stats;
         let default_stats = mi::Stats::default();
         if !stats.mi_original().is_finite()
@@ -154,13 +185,14 @@ impl<Obs: Observer<f64>> MI<Obs> {
         {
             stats = &default_stats;
         }
-        self.mi_original.observe(stats.mi_original());
-        self.mi_sei.observe(stats.mi_sei());
-        self.mi_visual_studio.observe(stats.mi_visual_studio());
+        self.mi_original.observe_at(stats.mi_original(), location);
+        self.mi_sei.observe_at(stats.mi_sei(), location);
+        self.mi_visual_studio
+            .observe_at(stats.mi_visual_studio(), location);
     }
 }
 
-#[derive(Default, Serialize)]
+#[derive(Default, Clone, Serialize, Deserialize)]
 pub struct Nargs<Obs> {
     average: Obs,
     average_closures: Obs,
@@ -175,21 +207,27 @@ pub struct Nargs<Obs> {
 }
 
 impl<Obs: Observer<f64>> Nargs<Obs> {
-    fn observe(&mut self, stats: &nargs::Stats) {
-        self.average.observe(stats.nargs_average());
-        self.average_closures.observe(stats.closure_args_average());
-        self.average_functions.observe(stats.fn_args_average());
-        self.closures_max.observe(stats.closure_args_max());
-        self.closures_min.observe(stats.closure_args_min());
-        self.functions_max.observe(stats.fn_args_max());
-        self.functions_min.observe(stats.fn_args_min());
-        self.total.observe(stats.nargs_total());
-        self.total_closures.observe(stats.closure_args_sum());
-        self.total_functions.observe(stats.fn_args_sum());
+    fn observe(&mut self, stats: &nargs::Stats, location: &impl Fn() -> Location) {
+        self.average.observe_at(stats.nargs_average(), location);
+        self.average_closures
+            .observe_at(stats.closure_args_average(), location);
+        self.average_functions
+            .observe_at(stats.fn_args_average(), location);
+        self.closures_max
+            .observe_at(stats.closure_args_max(), location);
+        self.closures_min
+            .observe_at(stats.closure_args_min(), location);
+        self.functions_max.observe_at(stats.fn_args_max(), location);
+        self.functions_min.observe_at(stats.fn_args_min(), location);
+        self.total.observe_at(stats.nargs_total(), location);
+        self.total_closures
+            .observe_at(stats.closure_args_sum(), location);
+        self.total_functions
+            .observe_at(stats.fn_args_sum(), location);
     }
 }
 
-#[derive(Default, Serialize)]
+#[derive(Default, Clone, Serialize, Deserialize)]
 pub struct Nexits<Obs> {
     average: Obs,
     max: Obs,
@@ -198,19 +236,19 @@ pub struct Nexits<Obs> {
 }
 
 impl<Obs: Observer<f64>> Nexits<Obs> {
-    fn observe(&mut self, stats: &exit::Stats) {
+    fn observe(&mut self, stats: &exit::Stats, location: &impl Fn() -> Location) {
         let avg = stats.exit_average();
         if !avg.is_finite() {
             return;
         }
-        self.average.observe(avg);
-        self.max.observe(stats.exit_max());
-        self.min.observe(stats.exit_min());
-        self.sum.observe(stats.exit_sum());
+        self.average.observe_at(avg, location);
+        self.max.observe_at(stats.exit_max(), location);
+        self.min.observe_at(stats.exit_min(), location);
+        self.sum.observe_at(stats.exit_sum(), location);
     }
 }
 
-#[derive(Default, Serialize)]
+#[derive(Default, Clone, Serialize, Deserialize)]
 pub struct Nom<Obs = Unaggregated<f64>> {
     average: Obs,
     closures: Obs,
@@ -225,21 +263,25 @@ pub struct Nom<Obs = Unaggregated<f64>> {
 }
 
 impl<Obs: Observer<f64>> Nom<Obs> {
-    fn observe(&mut self, stats: &nom::Stats) {
-        self.average.observe(stats.average());
-        self.closures.observe(stats.closures_sum());
-        self.closures_average.observe(stats.closures_average());
-        self.closures_max.observe(stats.closures_max());
-        self.closures_min.observe(stats.closures_min());
-        self.functions.observe(stats.functions_sum());
-        self.functions_average.observe(stats.functions_average());
-        self.functions_max.observe(stats.functions_max());
-        self.functions_min.observe(stats.functions_min());
-        self.total.observe(stats.total());
+    fn observe(&mut self, stats: &nom::Stats, location: &impl Fn() -> Location) {
+        self.average.observe_at(stats.average(), location);
+        self.closures.observe_at(stats.closures_sum(), location);
+        self.closures_average
+            .observe_at(stats.closures_average(), location);
+        self.closures_max.observe_at(stats.closures_max(), location);
+        self.closures_min.observe_at(stats.closures_min(), location);
+        self.functions.observe_at(stats.functions_sum(), location);
+        self.functions_average
+            .observe_at(stats.functions_average(), location);
+        self.functions_max
+            .observe_at(stats.functions_max(), location);
+        self.functions_min
+            .observe_at(stats.functions_min(), location);
+        self.total.observe_at(stats.total(), location);
     }
 }
 
-#[derive(Default, Serialize)]
+#[derive(Default, Clone, Serialize, Deserialize)]
 pub struct RCAMetrics<Obs> {
     pub cognitive: Cognitive<Obs>,
     pub cyclomatic: Cyclomatic<Obs>,
@@ -252,19 +294,19 @@ pub struct RCAMetrics<Obs> {
 }
 
 impl<Obs: Observer<f64>> RCAMetrics<Obs> {
-    pub fn observe_metrics(&mut self, stats: &CodeMetrics) {
-        self.cognitive.observe(&stats.cognitive);
-        self.cyclomatic.observe(&stats.cyclomatic);
-        self.halstead.observe(&stats.halstead);
-        self.loc.observe(&stats.loc);
-        self.mi.observe(&stats.mi);
-        self.nargs.observe(&stats.nargs);
-        self.nexits.observe(&stats.nexits);
-        self.nom.observe(&stats.nom);
+    pub fn observe_metrics(&mut self, stats: &CodeMetrics, location: &impl Fn() -> Location) {
+        self.cognitive.observe(&stats.cognitive, location);
+        self.cyclomatic.observe(&stats.cyclomatic, location);
+        self.halstead.observe(&stats.halstead, location);
+        self.loc.observe(&stats.loc, location);
+        self.mi.observe(&stats.mi, location);
+        self.nargs.observe(&stats.nargs, location);
+        self.nexits.observe(&stats.nexits, location);
+        self.nom.observe(&stats.nom, location);
     }
 }
 
-#[derive(Default, Serialize)]
+#[derive(Default, Clone, Serialize, Deserialize)]
 pub struct RCAMetricsKinded<Obs> {
     function: RCAMetrics<Obs>,
     r#struct: RCAMetrics<Obs>,
@@ -272,25 +314,149 @@ pub struct RCAMetricsKinded<Obs> {
     r#impl: RCAMetrics<Obs>,
 }
 
+monoid_fields!(Cognitive {
+    average,
+    max,
+    min,
+    sum
+});
+monoid_fields!(Cyclomatic {
+    average,
+    max,
+    min,
+    sum
+});
+monoid_fields!(Halstead {
+    N1,
+    N2,
+    bugs,
+    difficulty,
+    effort,
+    estimated_program_length,
+    length,
+    level,
+    n1,
+    n2,
+    purity_ratio,
+    time,
+    vocabulary,
+    volume
+});
+monoid_fields!(Loc {
+    blank,
+    blank_average,
+    blank_max,
+    blank_min,
+    cloc,
+    cloc_average,
+    cloc_max,
+    cloc_min,
+    lloc,
+    lloc_average,
+    lloc_max,
+    lloc_min,
+    ploc,
+    ploc_average,
+    ploc_max,
+    ploc_min,
+    sloc,
+    sloc_average,
+    sloc_max,
+    sloc_min
+});
+monoid_fields!(MI {
+    mi_original,
+    mi_sei,
+    mi_visual_studio
+});
+monoid_fields!(Nargs {
+    average,
+    average_closures,
+    average_functions,
+    closures_max,
+    closures_min,
+    functions_max,
+    functions_min,
+    total,
+    total_closures,
+    total_functions
+});
+monoid_fields!(Nexits {
+    average,
+    max,
+    min,
+    sum
+});
+monoid_fields!(Nom {
+    average,
+    closures,
+    closures_average,
+    closures_max,
+    closures_min,
+    functions,
+    functions_average,
+    functions_max,
+    functions_min,
+    total
+});
+monoid_fields!(RCAMetrics {
+    cognitive,
+    cyclomatic,
+    halstead,
+    loc,
+    mi,
+    nargs,
+    nexits,
+    nom
+});
+monoid_fields!(RCAMetricsKinded {
+    function,
+    r#struct,
+    r#trait,
+    r#impl
+});
+
 impl<Obs: Observer<f64>> RCAMetricsKinded<Obs> {
-    pub fn observe_spaces(&mut self, space: &FuncSpace) {
+    /// Observe all the spaces of a file (as returned by `rust_code_analysis::metrics`)
+    pub fn observe_file(&mut self, space: &FuncSpace) {
+        // the root space is named after the file
+        let path = space.name.as_deref().unwrap_or_default();
+        self.observe_spaces(path, space);
+    }
+
+    fn observe_spaces(&mut self, path: &str, space: &FuncSpace) {
+        let location = || Location {
+            path: path.to_string(),
+            item: space.name.clone(),
+            // RCA only knows about lines
+            start: Position {
+                line: space.start_line,
+                column: 0,
+            },
+            end: Position {
+                line: space.end_line,
+                column: 0,
+            },
+        };
+
         match space.kind {
             rust_code_analysis::SpaceKind::Unknown => space
                 .spaces
                 .iter()
-                .for_each(|space| self.observe_spaces(space)),
+                .for_each(|space| self.observe_spaces(path, space)),
             rust_code_analysis::SpaceKind::Function => {
                 let mut this_space = space.clone();
                 this_space.spaces.clear();
-                self.function.observe_metrics(&this_space.metrics);
+                self.function
+                    .observe_metrics(&this_space.metrics, &location);
                 space
                     .spaces
                     .iter()
-                    .for_each(|space| self.observe_spaces(space));
+                    .for_each(|space| self.observe_spaces(path, space));
             }
             rust_code_analysis::SpaceKind::Class => panic!("Class funcspace in rust code"),
             rust_code_analysis::SpaceKind::Struct => {
-                self.r#struct.observe_metrics(&space.metrics);
+                self.r#struct.observe_metrics(&space.metrics, &location);
                 assert_eq!(
                     space.spaces.len(),
                     0,
@@ -298,31 +464,126 @@ impl<Obs: Observer<f64>> RCAMetricsKinded<Obs> {
                 );
             }
             rust_code_analysis::SpaceKind::Trait => {
-                self.r#trait.observe_metrics(&space.metrics);
+                self.r#trait.observe_metrics(&space.metrics, &location);
                 space
                     .spaces
                     .iter()
-                    .for_each(|space| self.observe_spaces(space));
+                    .for_each(|space| self.observe_spaces(path, space));
             }
             rust_code_analysis::SpaceKind::Impl => {
                 let mut this_space = space.clone();
                 this_space.spaces.clear();
-                self.r#impl.observe_metrics(&this_space.metrics);
+                self.r#impl.observe_metrics(&this_space.metrics, &location);
                 space
                     .spaces
                     .iter()
-                    .for_each(|space| self.observe_spaces(space));
+                    .for_each(|space| self.observe_spaces(path, space));
             }
             rust_code_analysis::SpaceKind::Unit => space
                 .spaces
                 .iter()
-                .for_each(|space| self.observe_spaces(space)),
+                .for_each(|space| self.observe_spaces(path, space)),
             rust_code_analysis::SpaceKind::Namespace => panic!("Namespace funcspace in rust code"),
             rust_code_analysis::SpaceKind::Interface => panic!("Interface funcspace in rust code"),
         }
     }
 }
 
+/// Observes the metrics of the files with the observer chosen at runtime, like `MetricCollectorBox` does
+pub struct RcaCollector(Box<dyn RcaCollectorBoxed + Send + Sync>);
+
+impl RcaCollector {
+    pub fn new(observer: ObserverKind) -> Self {
+        match observer {
+            ObserverKind::Unaggregated => Self::with::<Unaggregated<f64>>(),
+            ObserverKind::Located => Self::with::<Located<f64>>(),
+            ObserverKind::Histogram => Self::with::<FloatHist>(),
+        }
+    }
+
+    fn with<Obs: RcaObserver>() -> Self {
+        Self(Box::new(RcaCollectorWith::<Obs>(PhantomData)))
+    }
+
+    /// Same as `MetricCollector::version`
+    pub fn version(&self) -> u32 {
+        1
+    }
+
+    /// Observes the spaces of a file (as returned by `rust_code_analysis::metrics`)
+    pub fn collect_file(&self, space: &FuncSpace) -> FileMetric {
+        self.0.collect_file(space)
+    }
+
+    pub fn aggregate(&self, metrics: &[FileMetric]) -> Result<serde_json::Value> {
+        self.0.aggregate(metrics)
+    }
+
+    pub fn encode(&self, metric: &FileMetric) -> Result<Vec<u8>> {
+        self.0.encode(metric)
+    }
+
+    pub fn decode(&self, bytes: &[u8]) -> Result<FileMetric> {
+        self.0.decode(bytes)
+    }
+}
+
+trait RcaObserver:
+    Observer<f64> + Monoid + Default + Clone + Serialize + DeserializeOwned + Send + Sync + 'static
+{
+}
+
+impl<
+        T: Observer<f64>
+            + Monoid
+            + Default
+            + Clone
+            + Serialize
+            + DeserializeOwned
+            + Send
+            + Sync
+            + 'static,
+    > RcaObserver for T
+{
+}
+
+struct RcaCollectorWith<Obs>(PhantomData<Obs>);
+
+trait RcaCollectorBoxed {
+    fn collect_file(&self, space: &FuncSpace) -> FileMetric;
+
+    fn aggregate(&self, metrics: &[FileMetric]) -> Result<serde_json::Value>;
+
+    fn encode(&self, metric: &FileMetric) -> Result<Vec<u8>>;
+
+    fn decode(&self, bytes: &[u8]) -> Result<FileMetric>;
+}
+
+impl<Obs: RcaObserver> RcaCollectorBoxed for RcaCollectorWith<Obs> {
+    fn collect_file(&self, space: &FuncSpace) -> FileMetric {
+        let mut metrics = RCAMetricsKinded::<Obs>::default();
+        metrics.observe_file(space);
+        Arc::new(metrics)
+    }
+
+    fn aggregate(&self, metrics: &[FileMetric]) -> Result<serde_json::Value> {
+        let metrics = metrics.iter().map(|m| {
+            m.downcast_ref::<RCAMetricsKinded<Obs>>()
+                .expect("BUG: file metric from a different observer")
+                .clone()
+        });
+        Ok(serde_json::to_value(Monoid::reduce(metrics))?)
+    }
+
+    fn encode(&self, metric: &FileMetric) -> Result<Vec<u8>> {
+        encode_metric::<RCAMetricsKinded<Obs>>(metric)
+    }
+
+    fn decode(&self, bytes: &[u8]) -> Result<FileMetric> {
+        decode_metric::<RCAMetricsKinded<Obs>>(bytes)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::path::Path;
@@ -347,13 +608,13 @@ mod tests {
         stats.spaces.clear();
         let metrics = stats.metrics;
         let mut statistics = RCAMetrics::<Unaggregated<f64>>::default();
-        statistics.observe_metrics(&metrics);
+        statistics.observe_metrics(&metrics, &|| unreachable!("locations are not used"));
         let actual = serde_json::to_string_pretty(&statistics).unwrap();
         expect![[r#"
             {
               "cognitive": {
                 "average": [
-                  0.4117647058823529
+                  0.25
                 ],
                 "max": [
                   2.0
@@ -362,12 +623,12 @@ mod tests {
                   0.0
                 ],
                 "sum": [
-                  7.0
+                  8.0
                 ]
               },
               "cyclomatic": {
                 "average": [
-                  1.5714285714285714
+                  1.5
                 ],
                 "max": [
                   10.0
@@ -376,59 +637,59 @@ mod tests {
                   1.0
                 ],
                 "sum": [
-                  44.0
+                  72.0
                 ]
               },
               "halstead": {
                 "N1": [
-                  1123.0
+                  1654.0
                 ],
                 "N2": [
-                  426.0
+                  802.0
                 ],
                 "bugs": [
-                  2.369138531712961
+                  3.4519265207419307
                 ],
                 "difficulty": [
-                  57.851851851851855
+                  54.95939086294416
                 ],
                 "effort": [
-                  599194.15231629
+                  1053838.8282039848
                 ],
                 "estimated_program_length": [
-                  611.6353458436752
+                  1629.9261709913199
                 ],
                 "length": [
-                  1549.0
+                  2456.0
                 ],
                 "level": [
-                  0.017285531370038413
+                  0.018195252609217696
                 ],
                 "n1": [
-                  22.0
+                  27.0
                 ],
                 "n2": [
-                  81.0
+                  197.0
                 ],
                 "purity_ratio": [
-                  0.39485819615472895
+                  0.663650721087671
                 ],
                 "time": [
-                  33288.56401757166
+                  58546.60156688804
                 ],
                 "vocabulary": [
-                  103.0
+                  224.0
                 ],
                 "volume": [
-                  10357.389316606805
+                  19174.863688573474
                 ]
               },
               "loc": {
                 "blank": [
-                  1753.0
+                  1779.0
                 ],
                 "blank_average": [
-                  62.607142857142854
+                  37.0625
                 ],
                 "blank_max": [
                   1500.0
@@ -437,60 +698,60 @@ mod tests {
                   0.0
                 ],
                 "cloc": [
-                  1.0
+                  8.0
                 ],
                 "cloc_average": [
-                  0.03571428571428571
+                  0.16666666666666666
                 ],
                 "cloc_max": [
-                  1.0
+                  3.0
                 ],
                 "cloc_min": [
                   0.0
                 ],
                 "lloc": [
-                  124.0
+                  141.0
                 ],
                 "lloc_average": [
-                  4.428571428571429
+                  2.9375
                 ],
                 "lloc_max": [
                   20.0
                 ],
                 "lloc_min": [
-                  4.0
+                  0.0
                 ],
                 "ploc": [
-                  343.0
+                  571.0
                 ],
                 "ploc_average": [
-                  12.25
+                  11.895833333333334
                 ],
                 "ploc_max": [
-                  50.0
+                  67.0
                 ],
                 "ploc_min": [
-                  8.0
+                  4.0
                 ],
                 "sloc": [
-                  2097.0
+                  2358.0
                 ],
                 "sloc_average": [
-                  74.89285714285714
+                  49.125
                 ],
                 "sloc_max": [
                   1515.0
                 ],
                 "sloc_min": [
-                  8.0
+                  4.0
                 ]
               },
               "mi": {
                 "mi_original": [
-                  -11.09822963608174
+                  -22.641267804258916
                 ],
                 "mi_sei": [
-                  -85.54094450523321
+                  -96.52859892328496
                 ],
                 "mi_visual_studio": [
                   0.0
@@ -498,13 +759,13 @@ mod tests {
               },
               "nargs": {
                 "average": [
-                  1.4705882352941178
+                  1.75
                 ],
                 "average_closures": [
-                  1.0
+                  0.75
                 ],
                 "average_functions": [
-                  1.6666666666666667
+                  2.0833333333333335
                 ],
                 "closures_max": [
                   1.0
@@ -513,24 +774,24 @@ mod tests {
                   0.0
                 ],
                 "functions_max": [
-                  2.0
+                  3.0
                 ],
                 "functions_min": [
                   0.0
                 ],
                 "total": [
-                  25.0
+                  56.0
                 ],
                 "total_closures": [
-                  5.0
+                  6.0
                 ],
                 "total_functions": [
-                  20.0
+                  50.0
                 ]
               },
               "nexits": {
                 "average": [
-                  0.11764705882352941
+                  0.40625
                 ],
                 "max": [
                   1.0
@@ -539,18 +800,18 @@ mod tests {
                   0.0
                 ],
                 "sum": [
-                  2.0
+                  13.0
                 ]
               },
               "nom": {
                 "average": [
-                  0.6071428571428571
+                  0.6666666666666666
                 ],
                 "closures": [
-                  5.0
+                  8.0
                 ],
                 "closures_average": [
-                  0.17857142857142858
+                  0.16666666666666666
                 ],
                 "closures_max": [
                   1.0
@@ -559,10 +820,10 @@ mod tests {
                   0.0
                 ],
                 "functions": [
-                  12.0
+                  24.0
                 ],
                 "functions_average": [
-                  0.42857142857142855
+                  0.5
                 ],
                 "functions_max": [
                   1.0
@@ -571,7 +832,7 @@ mod tests {
                   0.0
                 ],
                 "total": [
-                  17.0
+                  32.0
                 ]
               }
             }"#]]
@@ -643,7 +904,7 @@ mod tests {
 
         let stats = ::rust_code_analysis::metrics(&parser, &path).unwrap();
         let mut statistics = RCAMetricsKinded::<Unaggregated<f64>>::default();
-        statistics.observe_spaces(&stats);
+        statistics.observe_file(&stats);
         let actual = serde_json::to_string_pretty(&statistics).unwrap();
         expect![[r#"
             {
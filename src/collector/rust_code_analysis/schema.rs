@@ -0,0 +1,286 @@
+//! Descriptions of the metrics collected with Rust Code Analysis
+
+use crate::collector::metrics::schema::{MetricInfo, Scope, Unit};
+
+/// The kinds of spaces the metrics are reported for, with how they are called in the descriptions
+const KINDS: [(&str, Scope, &str); 4] = [
+    ("function", Scope::Function, "a function"),
+    ("impl", Scope::Type, "an impl block"),
+    ("struct", Scope::Type, "a struct"),
+    ("trait", Scope::Type, "a trait"),
+];
+
+/// The metrics of a space, `{}` in the descriptions is replaced with the kind of the space
+const METRICS: &[(&str, Unit, &str)] = &[
+    (
+        "cognitive.average",
+        Unit::Score,
+        "Average cognitive complexity of the functions in {}",
+    ),
+    (
+        "cognitive.max",
+        Unit::Score,
+        "Maximum cognitive complexity of a function in {}",
+    ),
+    (
+        "cognitive.min",
+        Unit::Score,
+        "Minimum cognitive complexity of a function in {}",
+    ),
+    ("cognitive.sum", Unit::Score, "Cognitive complexity of {}"),
+    (
+        "cyclomatic.average",
+        Unit::Score,
+        "Average cyclomatic complexity of the spaces in {}",
+    ),
+    (
+        "cyclomatic.max",
+        Unit::Score,
+        "Maximum cyclomatic complexity of a space in {}",
+    ),
+    (
+        "cyclomatic.min",
+        Unit::Score,
+        "Minimum cyclomatic complexity of a space in {}",
+    ),
+    ("cyclomatic.sum", Unit::Score, "Cyclomatic complexity of {}"),
+    ("halstead.N1", Unit::Tokens, "Number of operators in {}"),
+    ("halstead.N2", Unit::Tokens, "Number of operands in {}"),
+    (
+        "halstead.bugs",
+        Unit::Count,
+        "Estimated number of bugs in {}",
+    ),
+    (
+        "halstead.difficulty",
+        Unit::Score,
+        "Halstead difficulty of {}",
+    ),
+    (
+        "halstead.effort",
+        Unit::Score,
+        "Halstead effort needed to write {}",
+    ),
+    (
+        "halstead.estimated_program_length",
+        Unit::Tokens,
+        "Program length of {} estimated from its vocabulary",
+    ),
+    (
+        "halstead.length",
+        Unit::Tokens,
+        "Number of operators and operands in {}",
+    ),
+    (
+        "halstead.level",
+        Unit::Ratio,
+        "Halstead level (inverse of the difficulty) of {}",
+    ),
+    (
+        "halstead.n1",
+        Unit::Count,
+        "Number of distinct operators in {}",
+    ),
+    (
+        "halstead.n2",
+        Unit::Count,
+        "Number of distinct operands in {}",
+    ),
+    (
+        "halstead.purity_ratio",
+        Unit::Ratio,
+        "Ratio of the estimated to the actual program length of {}",
+    ),
+    (
+        "halstead.time",
+        Unit::Seconds,
+        "Estimated time needed to write {}",
+    ),
+    (
+        "halstead.vocabulary",
+        Unit::Count,
+        "Number of distinct operators and operands in {}",
+    ),
+    ("halstead.volume", Unit::Bits, "Halstead volume of {}"),
+    (
+        "mi.mi_original",
+        Unit::Score,
+        "Maintainability index of {} (original formula)",
+    ),
+    (
+        "mi.mi_sei",
+        Unit::Score,
+        "Maintainability index of {} (SEI formula)",
+    ),
+    (
+        "mi.mi_visual_studio",
+        Unit::Score,
+        "Maintainability index of {} (Visual Studio formula)",
+    ),
+    (
+        "nargs.average",
+        Unit::Count,
+        "Average number of arguments of the functions and closures in {}",
+    ),
+    (
+        "nargs.average_closures",
+        Unit::Count,
+        "Average number of arguments of the closures in {}",
+    ),
+    (
+        "nargs.average_functions",
+        Unit::Count,
+        "Average number of arguments of the functions in {}",
+    ),
+    (
+        "nargs.closures_max",
+        Unit::Count,
+        "Maximum number of arguments of a closure in {}",
+    ),
+    (
+        "nargs.closures_min",
+        Unit::Count,
+        "Minimum number of arguments of a closure in {}",
+    ),
+    (
+        "nargs.functions_max",
+        Unit::Count,
+        "Maximum number of arguments of a function in {}",
+    ),
+    (
+        "nargs.functions_min",
+        Unit::Count,
+        "Minimum number of arguments of a function in {}",
+    ),
+    (
+        "nargs.total",
+        Unit::Count,
+        "Number of arguments of the functions and closures in {}",
+    ),
+    (
+        "nargs.total_closures",
+        Unit::Count,
+        "Number of arguments of the closures in {}",
+    ),
+    (
+        "nargs.total_functions",
+        Unit::Count,
+        "Number of arguments of the functions in {}",
+    ),
+    (
+        "nexits.average",
+        Unit::Count,
+        "Average number of exit points of the functions in {}",
+    ),
+    (
+        "nexits.max",
+        Unit::Count,
+        "Maximum number of exit points of a function in {}",
+    ),
+    (
+        "nexits.min",
+        Unit::Count,
+        "Minimum number of exit points of a function in {}",
+    ),
+    ("nexits.sum", Unit::Count, "Number of exit points in {}"),
+    (
+        "nom.average",
+        Unit::Count,
+        "Average number of functions and closures per space in {}",
+    ),
+    ("nom.closures", Unit::Count, "Number of closures in {}"),
+    (
+        "nom.closures_average",
+        Unit::Count,
+        "Average number of closures per space in {}",
+    ),
+    (
+        "nom.closures_max",
+        Unit::Count,
+        "Maximum number of closures in a space of {}",
+    ),
+    (
+        "nom.closures_min",
+        Unit::Count,
+        "Minimum number of closures in a space of {}",
+    ),
+    ("nom.functions", Unit::Count, "Number of functions in {}"),
+    (
+        "nom.functions_average",
+        Unit::Count,
+        "Average number of functions per space in {}",
+    ),
+    (
+        "nom.functions_max",
+        Unit::Count,
+        "Maximum number of functions in a space of {}",
+    ),
+    (
+        "nom.functions_min",
+        Unit::Count,
+        "Minimum number of functions in a space of {}",
+    ),
+    (
+        "nom.total",
+        Unit::Count,
+        "Number of functions and closures in {}",
+    ),
+];
+
+/// The kinds of lines counted by the `loc` metrics
+const LINES: [(&str, &str); 5] = [
+    ("blank", "blank lines"),
+    ("cloc", "comment lines"),
+    ("lloc", "logical lines (statements)"),
+    ("ploc", "physical lines of code"),
+    ("sloc", "source lines"),
+];
+
+fn loc_metrics() -> Vec<(String, Unit, String)> {
+    LINES
+        .iter()
+        .flat_map(|(name, lines)| {
+            [
+                ("", format!("Number of {} in {{}}", lines)),
+                (
+                    "_average",
+                    format!("Average number of {} per space in {{}}", lines),
+                ),
+                (
+                    "_max",
+                    format!("Maximum number of {} of a space in {{}}", lines),
+                ),
+                (
+                    "_min",
+                    format!("Minimum number of {} of a space in {{}}", lines),
+                ),
+            ]
+            .map(|(suffix, description)| {
+                (format!("loc.{}{}", name, suffix), Unit::Lines, description)
+            })
+        })
+        .collect()
+}
+
+/// All the `rca.*` metrics
+pub fn schema() -> Vec<MetricInfo> {
+    let metrics = METRICS
+        .iter()
+        .map(|&(name, unit, description)| (name.to_string(), unit, description.to_string()))
+        .chain(loc_metrics())
+        .collect::<Vec<_>>();
+
+    KINDS
+        .iter()
+        .flat_map(|&(kind, scope, what)| {
+            metrics
+                .iter()
+                .map(move |(name, unit, description)| MetricInfo {
+                    name: format!("rca.{}.{}", kind, name),
+                    scope,
+                    unit: *unit,
+                    description: description.replace("{}", what),
+                })
+        })
+        .collect()
+}
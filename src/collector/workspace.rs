@@ -0,0 +1,366 @@
+//! Finding the cargo packages the files belong to
+
+use super::FileText;
+use globset::{GlobBuilder, GlobMatcher};
+use relative_path::RelativePath;
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashSet};
+use tracing::warn;
+
+const MANIFEST_NAME: &str = "Cargo.toml";
+
+#[derive(Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct Manifest {
+    package: Option<ManifestPackage>,
+    workspace: Option<ManifestWorkspace>,
+    #[serde(default)]
+    dependencies: BTreeMap<String, toml::Value>,
+    #[serde(default)]
+    dev_dependencies: BTreeMap<String, toml::Value>,
+    #[serde(default)]
+    build_dependencies: BTreeMap<String, toml::Value>,
+}
+
+#[derive(Deserialize)]
+struct ManifestPackage {
+    name: String,
+}
+
+#[derive(Deserialize)]
+struct ManifestWorkspace {
+    #[serde(default)]
+    members: Vec<String>,
+    #[serde(default)]
+    exclude: Vec<String>,
+    #[serde(default)]
+    dependencies: BTreeMap<String, toml::Value>,
+}
+
+/// A manifest, with the paths made relative to the repo root
+struct ParsedManifest {
+    /// directory of the manifest (empty for the root one)
+    root: String,
+    package: Option<String>,
+    workspace: Option<ManifestWorkspace>,
+    /// directories of the path dependencies
+    path_dependencies: Vec<String>,
+}
+
+impl ParsedManifest {
+    fn parse(manifest: &FileText) -> Option<Self> {
+        let parsed = match toml::from_str::<Manifest>(&manifest.content) {
+            Ok(parsed) => parsed,
+            Err(e) => {
+                warn!("Cannot parse {}: {}", manifest.path, e);
+                return None;
+            }
+        };
+        let root = manifest
+            .path
+            .parent()
+            .map_or_else(String::new, |p| p.as_str().to_string());
+
+        let workspace_dependencies = parsed.workspace.iter().flat_map(|w| &w.dependencies);
+        let path_dependencies = parsed
+            .dependencies
+            .iter()
+            .chain(&parsed.dev_dependencies)
+            .chain(&parsed.build_dependencies)
+            .chain(workspace_dependencies)
+            .filter_map(|(_, dependency)| dependency.get("path")?.as_str())
+            .filter_map(|path| join_dir(&root, path))
+            .collect();
+
+        Some(Self {
+            root,
+            package: parsed.package.map(|p| p.name),
+            workspace: parsed.workspace,
+            path_dependencies,
+        })
+    }
+}
+
+/// A path of the repo relative to `dir`, `None` if it's not in there
+fn relative_to<'a>(path: &'a str, dir: &str) -> Option<&'a str> {
+    if dir.is_empty() {
+        return Some(path);
+    }
+    match path.strip_prefix(dir)? {
+        "" => Some(""),
+        rest => rest.strip_prefix('/'),
+    }
+}
+
+/// Joins a path of a manifest to its directory, `None` if it leads out of the repo
+fn join_dir(dir: &str, path: &str) -> Option<String> {
+    let joined = RelativePath::new(dir).join_normalized(path);
+    let joined = joined.as_str().trim_end_matches('/');
+    (!joined.starts_with("..")).then(|| joined.to_string())
+}
+
+/// The members of a workspace, as listed in the `members` and `exclude` of its manifest
+struct Members {
+    root: String,
+    members: Vec<GlobMatcher>,
+    exclude: Vec<String>,
+}
+
+impl Members {
+    fn new(root: &str, workspace: &ManifestWorkspace) -> Self {
+        let members = workspace
+            .members
+            .iter()
+            .filter_map(|member| {
+                let member = member.trim_start_matches("./").trim_end_matches('/');
+                // like in cargo, `*` does not match across the directories
+                match GlobBuilder::new(member).literal_separator(true).build() {
+                    Ok(glob) => Some(glob.compile_matcher()),
+                    Err(e) => {
+                        warn!("Invalid workspace member `{}` in {}: {}", member, root, e);
+                        None
+                    }
+                }
+            })
+            .collect();
+        let exclude = workspace
+            .exclude
+            .iter()
+            .filter_map(|path| join_dir(root, path))
+            .collect();
+        Self {
+            root: root.to_string(),
+            members,
+            exclude,
+        }
+    }
+
+    fn is_excluded(&self, root: &str) -> bool {
+        self.exclude
+            .iter()
+            .any(|excluded| relative_to(root, excluded).is_some())
+    }
+
+    /// Whether a package is listed in `members` and not excluded, the root package and the path
+    /// dependencies are members even if they are not listed
+    fn is_listed(&self, root: &str) -> bool {
+        relative_to(root, &self.root).is_some_and(|path| {
+            path.is_empty()
+                || (self.members.iter().any(|glob| glob.is_match(path)) && !self.is_excluded(root))
+        })
+    }
+}
+
+pub fn is_manifest(path: &str) -> bool {
+    path.rsplit('/').next() == Some(MANIFEST_NAME)
+}
+
+/// A cargo package found in the repository
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Package {
+    /// name of the package, with the path of the manifest appended if the name is not unique
+    pub name: String,
+    /// directory of the manifest, relative to the repo root (empty for the root package)
+    pub root: String,
+}
+
+impl Package {
+    fn contains(&self, path: &str) -> bool {
+        self.root.is_empty()
+            || path
+                .strip_prefix(&self.root)
+                .is_some_and(|rest| rest.starts_with('/'))
+    }
+}
+
+/// The packages of a repository, whether it's a cargo workspace or not
+#[derive(Debug, Default)]
+pub struct Workspace {
+    /// the most nested packages first, so that the first one containing a file owns it
+    packages: Vec<Package>,
+    /// directories of the packages that are not members of the workspace they are in, like test
+    /// fixtures or `exclude`d packages; their files belong to no package
+    outside: Vec<String>,
+}
+
+impl Workspace {
+    /// Finds the packages in the manifests, the manifests of virtual workspaces are skipped
+    ///
+    /// A package in the directory of a workspace only counts if it's one of its members: it
+    /// matches the `members` globs and not the `exclude` paths, or it's a path dependency of a
+    /// member (or of the workspace).
+    pub fn from_manifests(manifests: &[FileText]) -> Self {
+        let manifests = manifests
+            .iter()
+            .filter_map(ParsedManifest::parse)
+            .collect::<Vec<_>>();
+        let workspaces = manifests
+            .iter()
+            .filter_map(|m| Some(Members::new(&m.root, m.workspace.as_ref()?)))
+            .collect::<Vec<_>>();
+        // the closest workspace a package is in, the packages in no workspace are on their own
+        let workspace_of = |root: &str| {
+            workspaces
+                .iter()
+                .filter(|w| relative_to(root, &w.root).is_some())
+                .max_by_key(|w| w.root.len())
+        };
+
+        let mut members = manifests
+            .iter()
+            .filter(|m| m.package.is_some())
+            .filter(|m| workspace_of(&m.root).is_none_or(|w| w.is_listed(&m.root)))
+            .map(|m| m.root.as_str())
+            .collect::<HashSet<_>>();
+        // the path dependencies of the members are members too, as long as they are not excluded
+        loop {
+            let dependencies = manifests
+                .iter()
+                .filter(|m| members.contains(m.root.as_str()) || m.workspace.is_some())
+                .flat_map(|m| &m.path_dependencies)
+                .filter(|dependency| !members.contains(dependency.as_str()))
+                .filter_map(|dependency| {
+                    manifests
+                        .iter()
+                        .find(|m| m.package.is_some() && m.root == *dependency)
+                })
+                .filter(|m| workspace_of(&m.root).is_none_or(|w| !w.is_excluded(&m.root)))
+                .map(|m| m.root.as_str())
+                .collect::<Vec<_>>();
+            if dependencies.is_empty() {
+                break;
+            }
+            members.extend(dependencies);
+        }
+
+        let mut packages = manifests
+            .iter()
+            .filter(|m| members.contains(m.root.as_str()))
+            .filter_map(|m| {
+                Some(Package {
+                    name: m.package.clone()?,
+                    root: m.root.clone(),
+                })
+            })
+            .collect::<Vec<_>>();
+        let outside = manifests
+            .iter()
+            .filter(|m| m.package.is_some() && !members.contains(m.root.as_str()))
+            .map(|m| m.root.clone())
+            .collect();
+
+        // the packages outside of a workspace can reuse the names
+        packages.sort_by(|a, b| a.root.cmp(&b.root));
+        let mut seen = HashSet::new();
+        for package in &mut packages {
+            if !seen.insert(package.name.clone()) {
+                package.name = format!("{}@{}", package.name, package.root);
+            }
+        }
+
+        packages.sort_by_key(|p| std::cmp::Reverse(p.root.len()));
+        Self { packages, outside }
+    }
+
+    /// Separates the manifests from the rust files
+    pub fn split_manifests(files: Vec<FileText>) -> (Vec<FileText>, Self) {
+        let (manifests, files) = files
+            .into_iter()
+            .partition::<Vec<_>, _>(|f| is_manifest(f.path.as_str()));
+        (files, Self::from_manifests(&manifests))
+    }
+
+    pub fn packages(&self) -> &[Package] {
+        &self.packages
+    }
+
+    /// The package owning the file, `None` if it's in no package or in one outside the workspace
+    pub fn package_of(&self, path: &str) -> Option<&Package> {
+        let package = self.packages.iter().find(|p| p.contains(path))?;
+        let in_outside = self
+            .outside
+            .iter()
+            .any(|root| root.len() > package.root.len() && relative_to(path, root).is_some());
+        (!in_outside).then_some(package)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Workspace;
+    use crate::collector::FileText;
+
+    fn manifest(path: &str, content: &str) -> FileText {
+        FileText {
+            path: path.into(),
+            content: content.to_string(),
+        }
+    }
+
+    #[test]
+    fn packages() {
+        let workspace = Workspace::from_manifests(&[
+            manifest(
+                "Cargo.toml",
+                r#"
+[workspace]
+members = ["crates/*"]
+exclude = ["crates/legacy"]
+[package]
+name = "app"
+[dev-dependencies]
+gen = { path = "./tools/gen/" }
+"#,
+            ),
+            manifest("crates/foo/Cargo.toml", "[package]\nname = \"foo\""),
+            manifest("crates/foo-bar/Cargo.toml", "[package]\nname = \"foo-bar\""),
+            manifest(
+                "crates/foo/nested/Cargo.toml",
+                "[package]\nname = \"nested\"",
+            ),
+            manifest("crates/legacy/Cargo.toml", "[package]\nname = \"legacy\""),
+            manifest("crates/virtual/Cargo.toml", "[workspace]"),
+            manifest("tools/gen/Cargo.toml", "[package]\nname = \"gen\""),
+            manifest("tests/fixture/Cargo.toml", "[package]\nname = \"foo\""),
+            manifest("broken/Cargo.toml", "[package"),
+        ]);
+
+        let owner = |path| workspace.package_of(path).map(|p| p.name.as_str());
+        assert_eq!(owner("src/main.rs"), Some("app"));
+        assert_eq!(owner("crates/foo/src/lib.rs"), Some("foo"));
+        assert_eq!(owner("crates/foo-bar/src/lib.rs"), Some("foo-bar"));
+        assert_eq!(owner("crates/virtual/src/lib.rs"), Some("app"));
+        assert_eq!(owner("tools/gen/src/main.rs"), Some("gen"));
+        // not members: `*` does not match `foo/nested`, and `legacy` is excluded
+        assert_eq!(owner("crates/foo/nested/src/lib.rs"), None);
+        assert_eq!(owner("crates/legacy/src/lib.rs"), None);
+        assert_eq!(owner("tests/fixture/src/lib.rs"), None);
+        assert_eq!(workspace.packages().len(), 4);
+
+        assert_eq!(Workspace::default().package_of("src/lib.rs"), None);
+    }
+
+    #[test]
+    fn packages_without_root_workspace() {
+        let workspace = Workspace::from_manifests(&[
+            manifest("a/Cargo.toml", "[package]\nname = \"foo\""),
+            manifest("b/Cargo.toml", "[package]\nname = \"foo\""),
+            manifest(
+                "c/Cargo.toml",
+                "[workspace]\nmembers = [\"x\"]\nexclude = [\"y\"]",
+            ),
+            manifest("c/x/Cargo.toml", "[package]\nname = \"x\""),
+            manifest("c/y/Cargo.toml", "[package]\nname = \"y\""),
+            manifest("c/z/Cargo.toml", "[package]\nname = \"z\""),
+        ]);
+
+        let owner = |path| workspace.package_of(path).map(|p| p.name.as_str());
+        assert_eq!(owner("a/src/lib.rs"), Some("foo"));
+        assert_eq!(owner("b/src/lib.rs"), Some("foo@b"));
+        assert_eq!(owner("c/x/src/lib.rs"), Some("x"));
+        assert_eq!(owner("c/y/src/lib.rs"), None);
+        assert_eq!(owner("c/z/src/lib.rs"), None);
+        assert_eq!(owner("src/lib.rs"), None);
+        assert_eq!(workspace.packages().len(), 3);
+    }
+}
@@ -0,0 +1,151 @@
+use crate::check::BudgetConfig;
+use crate::collector::CollectConfig;
+use anyhow::{bail, Context, Result};
+use clap::ValueEnum;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::Path;
+use tracing::info;
+
+/// Name of the config file looked up in the repository root
+pub const CONFIG_FILE: &str = "ifcount.toml";
+
+/// How the collected results are printed
+#[derive(Debug, Default, Clone, Copy, Deserialize, ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum OutputFormat {
+    /// Pretty-printed JSON (an array of results for bulk collection)
+    #[default]
+    Json,
+    /// One compact JSON result per line
+    JsonLines,
+}
+
+/// The contents of `ifcount.toml`
+#[derive(Debug, Default, Deserialize)]
+pub struct Config {
+    #[serde(flatten)]
+    pub collect: CollectConfig,
+    #[serde(default)]
+    pub format: OutputFormat,
+    #[serde(flatten)]
+    pub budgets: BudgetConfig,
+    /// the keys none of the other fields took, as the flattened ones can't be denied
+    #[serde(flatten)]
+    unknown: BTreeMap<String, toml::Value>,
+}
+
+impl Config {
+    pub fn load(path: &Path) -> Result<Self> {
+        let config = std::fs::read_to_string(path)
+            .with_context(|| format!("Reading config from {}", path.display()))?;
+        let config: Config = toml::from_str(&config)
+            .with_context(|| format!("Parsing config {}", path.display()))?;
+        config
+            .validate()
+            .with_context(|| format!("Invalid config {}", path.display()))?;
+        Ok(config)
+    }
+
+    /// Checks that there are no unknown keys, and that the collect config makes sense
+    pub fn validate(&self) -> Result<()> {
+        if let Some(key) = self.unknown.keys().next() {
+            bail!("Unknown key `{}`", key);
+        }
+        self.collect.validate()
+    }
+
+    /// Loads the config at `path` if given, otherwise the one in the repository root if there is one
+    pub fn find(path: Option<&Path>, repo_path: Option<&Path>) -> Result<Self> {
+        if let Some(path) = path {
+            return Self::load(path);
+        }
+        match repo_path.map(|p| p.join(CONFIG_FILE)) {
+            Some(path) if path.exists() => {
+                info!("Using config from {}", path.display());
+                Self::load(&path)
+            }
+            _ => Ok(Self::default()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Config;
+    use expect_test::expect;
+
+    #[test]
+    fn parse_config() {
+        let config: Config = toml::from_str(
+            r#"
+            collectors = ["complexity", "rca"]
+            exclude = ["vendor/**", "benches/**"]
+            observer = "histogram"
+            format = "json-lines"
+
+            [budgets]
+            "complexity.all_fn" = { max = 25 }
+
+            [queries.unwrap_calls]
+            query = '(ExprMethodCall method: "unwrap")'
+            per = "fn"
+            "#,
+        )
+        .unwrap();
+        config.validate().unwrap();
+
+        expect![[r#"
+            Config {
+                collect: CollectConfig {
+                    collectors: Some(
+                        [
+                            "complexity",
+                            "rca",
+                        ],
+                    ),
+                    skip_collectors: [],
+                    include: [],
+                    exclude: [
+                        "vendor/**",
+                        "benches/**",
+                    ],
+                    observer: Histogram,
+                    fetch: Archive,
+                    queries: {
+                        "unwrap_calls": QueryMetric {
+                            query: Query(
+                                "(ExprMethodCall method: \"unwrap\")",
+                            ),
+                            per: Fn,
+                            description: None,
+                        },
+                    },
+                    cache_dir: None,
+                },
+                format: JsonLines,
+                budgets: BudgetConfig {
+                    budgets: {
+                        "complexity.all_fn": {
+                            Max: 25.0,
+                        },
+                    },
+                },
+                unknown: {},
+            }
+        "#]]
+        .assert_debug_eq(&config);
+    }
+
+    #[test]
+    fn unknown_keys() {
+        for (config, key) in [
+            (r#"exclud = ["vendor/**"]"#, "exclud"),
+            (r#"obsrever = "histogram""#, "obsrever"),
+        ] {
+            let config: Config = toml::from_str(config).unwrap();
+            let err = config.validate().unwrap_err();
+            assert_eq!(err.to_string(), format!("Unknown key `{}`", key));
+        }
+    }
+}
@@ -0,0 +1,332 @@
+use crate::check::{format_value, Statistic};
+use crate::collector::{metric_values, Location, RepoResult};
+use anyhow::{bail, Context, Result};
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write;
+
+/// The statistics compared for every metric
+const STATISTICS: [Statistic; 6] = [
+    Statistic::Count,
+    Statistic::Sum,
+    Statistic::Avg,
+    Statistic::Percentile(50),
+    Statistic::Percentile(95),
+    Statistic::Max,
+];
+/// How many items to list per metric in the markdown report
+const MAX_ITEMS: usize = 20;
+
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct Change {
+    pub base: Option<f64>,
+    pub head: Option<f64>,
+    pub delta: f64,
+}
+
+impl Change {
+    fn new(base: Option<f64>, head: Option<f64>) -> Self {
+        Self {
+            base,
+            head,
+            delta: head.unwrap_or(0.0) - base.unwrap_or(0.0),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct StatisticDiff {
+    pub statistic: String,
+    #[serde(flatten)]
+    pub change: Change,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MetricDiff {
+    pub metric: String,
+    /// Only the statistics that have changed
+    pub statistics: Vec<StatisticDiff>,
+}
+
+/// A file (when `item` is `None`) or an item in it which got a larger value of a metric
+#[derive(Debug, Serialize)]
+pub struct ItemDiff {
+    pub metric: String,
+    pub path: String,
+    pub item: Option<String>,
+    #[serde(flatten)]
+    pub change: Change,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RepoDiff {
+    pub base: String,
+    pub head: String,
+    pub metrics: Vec<MetricDiff>,
+    pub items: Vec<ItemDiff>,
+}
+
+fn statistics(value: Option<&Value>) -> [Option<f64>; STATISTICS.len()] {
+    let values = value.and_then(metric_values);
+    STATISTICS.map(|statistic| {
+        let values = values.as_deref()?;
+        statistic
+            .compute(values, || bail!("not computed in diffs"))
+            .expect("BUG: failed to compute a statistic")
+    })
+}
+
+fn diff_metrics(base: &RepoResult, head: &RepoResult) -> Vec<MetricDiff> {
+    let names = base
+        .metrics
+        .keys()
+        .chain(head.metrics.keys())
+        .collect::<BTreeSet<_>>();
+
+    names
+        .into_iter()
+        .filter_map(|name| {
+            let base = statistics(base.metrics.get(name));
+            let head = statistics(head.metrics.get(name));
+            let statistics = STATISTICS
+                .iter()
+                .zip(base.into_iter().zip(head))
+                .filter(|(_, (base, head))| base != head)
+                .map(|(statistic, (base, head))| StatisticDiff {
+                    statistic: statistic.to_string(),
+                    change: Change::new(base, head),
+                })
+                .collect::<Vec<_>>();
+
+            (!statistics.is_empty()).then(|| MetricDiff {
+                metric: name.clone(),
+                statistics,
+            })
+        })
+        .collect()
+}
+
+/// Sums up the observations of a metric per file and per item
+fn item_values(
+    result: &RepoResult,
+    metric: &str,
+) -> Result<BTreeMap<(String, Option<String>), f64>> {
+    let values = result.values(metric).unwrap_or_default();
+    let locations = result.locations(metric)?;
+    if values.len() != locations.len() {
+        bail!("No locations were collected for `{}`", metric);
+    }
+
+    let mut items = BTreeMap::new();
+    for (value, location) in values.into_iter().zip(locations) {
+        let Some(Location { path, item, .. }) = location else {
+            continue;
+        };
+        *items.entry((path.clone(), None)).or_default() += value;
+        if item.is_some() {
+            *items.entry((path, item)).or_default() += value;
+        }
+    }
+    Ok(items)
+}
+
+fn diff_items(base: &RepoResult, head: &RepoResult, metric: &str) -> Result<Vec<ItemDiff>> {
+    if !base.metrics.contains_key(metric) && !head.metrics.contains_key(metric) {
+        bail!("Unknown metric `{}`", metric);
+    }
+    let base = item_values(base, metric)?;
+    let head = item_values(head, metric)?;
+
+    let mut items = head
+        .into_iter()
+        .map(|(key, head)| (Change::new(base.get(&key).copied(), Some(head)), key))
+        .filter(|(change, _)| change.delta > 0.0)
+        .map(|(change, (path, item))| ItemDiff {
+            metric: metric.to_string(),
+            path,
+            item,
+            change,
+        })
+        .collect::<Vec<_>>();
+    items.sort_by(|a, b| b.change.delta.total_cmp(&a.change.delta));
+
+    Ok(items)
+}
+
+/// Compares the metrics of two revisions
+///
+/// `item_metrics` are the (located) metrics for which the files and items that got larger values are listed.
+pub fn diff(base: &RepoResult, head: &RepoResult, item_metrics: &[String]) -> Result<RepoDiff> {
+    let mut items = Vec::new();
+    for metric in item_metrics {
+        items.extend(
+            diff_items(base, head, metric)
+                .with_context(|| format!("Comparing items of `{}`", metric))?,
+        );
+    }
+
+    Ok(RepoDiff {
+        base: base.meta.commit.clone(),
+        head: head.meta.commit.clone(),
+        metrics: diff_metrics(base, head),
+        items,
+    })
+}
+
+fn format_option(value: Option<f64>) -> String {
+    value.map_or_else(|| "-".to_string(), format_value)
+}
+
+fn format_delta(delta: f64) -> String {
+    if delta > 0.0 {
+        format!("+{}", format_value(delta))
+    } else {
+        format_value(delta)
+    }
+}
+
+fn short_commit(commit: &str) -> &str {
+    commit.get(..10).unwrap_or(commit)
+}
+
+/// Renders the diff as a markdown document, suitable for a PR comment
+pub fn markdown_report(diff: &RepoDiff) -> String {
+    let mut report = String::new();
+    let w = &mut report;
+
+    writeln!(
+        w,
+        "## ifcount: `{}` → `{}`\n",
+        short_commit(&diff.base),
+        short_commit(&diff.head)
+    )
+    .unwrap();
+
+    if diff.metrics.is_empty() {
+        writeln!(w, "No metrics have changed.").unwrap();
+    } else {
+        writeln!(w, "| metric | statistic | base | head | delta |").unwrap();
+        writeln!(w, "|---|---|---:|---:|---:|").unwrap();
+        for metric in &diff.metrics {
+            for StatisticDiff { statistic, change } in &metric.statistics {
+                writeln!(
+                    w,
+                    "| `{}` | {} | {} | {} | {} |",
+                    metric.metric,
+                    statistic,
+                    format_option(change.base),
+                    format_option(change.head),
+                    format_delta(change.delta)
+                )
+                .unwrap();
+            }
+        }
+    }
+
+    let mut items_by_metric = BTreeMap::<_, Vec<_>>::new();
+    for item in &diff.items {
+        items_by_metric.entry(&item.metric).or_default().push(item);
+    }
+    for (metric, items) in items_by_metric {
+        writeln!(w, "\n### Larger `{}`\n", metric).unwrap();
+        writeln!(w, "| file | item | base | head | delta |").unwrap();
+        writeln!(w, "|---|---|---:|---:|---:|").unwrap();
+        for item in items.iter().take(MAX_ITEMS) {
+            writeln!(
+                w,
+                "| `{}` | {} | {} | {} | {} |",
+                item.path,
+                item.item
+                    .as_ref()
+                    .map_or_else(String::new, |i| format!("`{}`", i)),
+                format_option(item.change.base),
+                format_option(item.change.head),
+                format_delta(item.change.delta)
+            )
+            .unwrap();
+        }
+        if items.len() > MAX_ITEMS {
+            writeln!(w, "\n...and {} more", items.len() - MAX_ITEMS).unwrap();
+        }
+    }
+
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{diff, markdown_report};
+    use crate::collector::RepoResult;
+    use expect_test::expect;
+    use serde_json::json;
+
+    fn location(path: &str, item: &str) -> serde_json::Value {
+        json!({
+            "path": path,
+            "item": item,
+            "start": {"line": 1, "column": 0},
+            "end": {"line": 2, "column": 1},
+        })
+    }
+
+    #[test]
+    fn diff_revisions() {
+        let base: RepoResult = serde_json::from_value(json!({
+            "meta": {"url": "<LOCAL>", "commit": "0123456789abcdef"},
+            "metrics": {
+                "complexity.all_fn": [1, 3],
+                "if_count": 4,
+                "removed": [1],
+            },
+            "locations": {
+                "complexity.all_fn": [location("src/a.rs", "foo"), location("src/b.rs", "bar")],
+            },
+        }))
+        .unwrap();
+        let head: RepoResult = serde_json::from_value(json!({
+            "meta": {"url": "<LOCAL>", "commit": "fedcba9876543210"},
+            "metrics": {
+                "complexity.all_fn": [2, 3, 5],
+                "if_count": 4,
+            },
+            "locations": {
+                "complexity.all_fn": [
+                    location("src/a.rs", "foo"),
+                    location("src/b.rs", "bar"),
+                    location("src/a.rs", "baz"),
+                ],
+            },
+        }))
+        .unwrap();
+
+        let diff = diff(&base, &head, &["complexity.all_fn".to_string()]).unwrap();
+        expect![[r#"
+            ## ifcount: `0123456789` → `fedcba9876`
+
+            | metric | statistic | base | head | delta |
+            |---|---|---:|---:|---:|
+            | `complexity.all_fn` | count | 2 | 3 | +1 |
+            | `complexity.all_fn` | sum | 4 | 10 | +6 |
+            | `complexity.all_fn` | avg | 2 | 3.33 | +1.33 |
+            | `complexity.all_fn` | p50 | 1 | 3 | +2 |
+            | `complexity.all_fn` | p95 | 3 | 5 | +2 |
+            | `complexity.all_fn` | max | 3 | 5 | +2 |
+            | `removed` | count | 1 | - | -1 |
+            | `removed` | sum | 1 | - | -1 |
+            | `removed` | avg | 1 | - | -1 |
+            | `removed` | p50 | 1 | - | -1 |
+            | `removed` | p95 | 1 | - | -1 |
+            | `removed` | max | 1 | - | -1 |
+
+            ### Larger `complexity.all_fn`
+
+            | file | item | base | head | delta |
+            |---|---|---:|---:|---:|
+            | `src/a.rs` |  | 1 | 7 | +6 |
+            | `src/a.rs` | `baz` | - | 5 | +5 |
+            | `src/a.rs` | `foo` | 1 | 2 | +1 |
+        "#]]
+        .assert_eq(&markdown_report(&diff));
+    }
+}
@@ -0,0 +1,49 @@
+//! Collects metrics of Rust code: the number of ifs, cognitive complexity, function depth and so on
+//!
+//! The `ifcount` binary is a thin wrapper around this crate, so everything it does can be done in-process:
+//!
+//! ```
+//! use ifcount::{Analyzer, CollectConfig, FileText, MetricValue};
+//!
+//! let analyzer = Analyzer::new(&CollectConfig::default());
+//! let result = analyzer.collect_files(vec![FileText::new(
+//!     "src/lib.rs",
+//!     "fn foo(a: bool) { if a {} }".to_string(),
+//! )])?;
+//! assert_eq!(result.metric("if_count"), Some(MetricValue::Number(1.0)));
+//! # Ok::<(), anyhow::Error>(())
+//! ```
+//!
+//! The items exported from the crate root and from `metrics` are the stable API.
+//! Custom collectors are written with the `metrics` module and passed to `Analyzer::with_collector`.
+
+pub mod check;
+mod collector;
+pub mod config;
+pub mod diff;
+mod stack;
+
+pub use collector::{
+    bulk_collect_github_repos, collect_github_repo, collect_local_repo, collect_local_revision,
+    history_commits, metric_list, render_metric_list, Analyzer, CollectConfig, Coverage,
+    Diagnostics, FetchStrategy, FileText, GithubError, GithubRepo, GithubUrls, HistoryCollector,
+    LimitedCrab, ListFormat, Location, MetricValue, ObserverKind, Position, Query, QueryMetric,
+    QueryScope, RepoMetadata, RepoResult, SkippedFile, Stage,
+};
+
+/// What's needed to write a custom collector
+///
+/// A collector gets the nodes of the syntax tree of every file from a `syn::visit::Visit` wrapped into a
+/// `VisitorCollector` (or from a lower-level `NodeVisitor`), and observes the values of its metrics with an `Observer`.
+/// The observer should be picked according to `CollectConfig::observer`.
+///
+/// The nodes are of the fork of syn the crate parses with, which is re-exported as `metrics::syn`.
+pub mod metrics {
+    pub use crate::collector::metrics::schema::{MetricInfo, MetricSchema, Scope, Unit};
+    pub use crate::collector::metrics::util::{
+        FloatHist, Hist, Located, MacroArgs, Monoid, Node, NodeVisitor, Observer, Unaggregated,
+        Visiting, VisitorCollector, Walk, WalkContext,
+    };
+    pub use crate::collector::metrics::{FileVisitor, MetricCollector, MetricCollectorBox};
+    pub use syn;
+}
@@ -1,13 +1,13 @@
-mod collector;
-mod stack;
-
-use crate::collector::LimitedCrab;
-use anyhow::{Context, Result};
-use clap::{Parser, Subcommand};
+use anyhow::{bail, Context, Result};
+use clap::{Args, Parser, Subcommand, ValueEnum};
 use directories::ProjectDirs;
+use ifcount::config::{Config, OutputFormat, CONFIG_FILE};
+use ifcount::{
+    check, diff, CollectConfig, GithubUrls, LimitedCrab, ListFormat, ObserverKind, RepoResult,
+};
 use std::io::Write;
-use std::path::PathBuf;
-use tracing::warn;
+use std::path::{Path, PathBuf};
+use tracing::{info, warn};
 use tracing_indicatif::IndicatifLayer;
 use tracing_subscriber::layer::SubscriberExt;
 use tracing_subscriber::util::SubscriberInitExt;
@@ -28,95 +28,312 @@ const BANNER: &str = r#"
 
 "#;
 
+#[derive(Debug, Args)]
+struct CollectOptions {
+    /// Also report the source location (file, item and span) of every observation
+    #[clap(long)]
+    located: bool,
+    /// Path to the config [default: <REPO_PATH>/ifcount.toml for local repos, if it exists]
+    #[clap(long)]
+    config: Option<PathBuf>,
+    /// Overrides the output format from the config (`history` always prints JSON lines)
+    #[clap(long, value_enum)]
+    format: Option<OutputFormat>,
+    /// Compute the metrics of all the files, instead of reusing the ones cached by the previous runs
+    #[clap(long)]
+    no_cache: bool,
+}
+
+impl CollectOptions {
+    fn config(&self, repo_path: Option<&Path>, dirs: &ProjectDirs) -> Result<Config> {
+        let mut config = Config::find(self.config.as_deref(), repo_path)?;
+        if self.located {
+            config.collect.observer = ObserverKind::Located;
+        }
+        if !self.no_cache {
+            config.collect.cache_dir = Some(metric_cache_dir(dirs));
+        }
+        if let Some(format) = self.format {
+            config.format = format;
+        }
+        Ok(config)
+    }
+}
+
+fn print_results(results: &[RepoResult], format: OutputFormat, bulk: bool) -> Result<()> {
+    match format {
+        OutputFormat::Json if bulk => println!(
+            "{}",
+            serde_json::to_string_pretty(results).context("Serializing results")?
+        ),
+        OutputFormat::Json => {
+            for result in results {
+                println!(
+                    "{}",
+                    serde_json::to_string_pretty(result).context("Serializing results")?
+                );
+            }
+        }
+        OutputFormat::JsonLines => {
+            for result in results {
+                println!(
+                    "{}",
+                    serde_json::to_string(result).context("Serializing results")?
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+#[derive(Debug, Default, Clone, Copy, ValueEnum)]
+enum DiffFormat {
+    /// Tables suitable for a PR comment
+    #[default]
+    Markdown,
+    Json,
+}
+
 #[derive(Debug, Subcommand)]
 enum CliCommand {
     /// Collect metrics from a checked out repository
     CollectLocalRepo {
         /// Path to the repository to collect metrics for
         repo_path: PathBuf,
+        #[clap(flatten)]
+        options: CollectOptions,
+    },
+    /// Check a checked out repository against the metric budgets
+    ///
+    /// Exits with a non-zero code if any of the budgets is exceeded
+    Check {
+        /// Path to the repository to check
+        repo_path: PathBuf,
+        /// Path to the config with the budgets [default: <REPO_PATH>/ifcount.toml]
+        #[clap(long)]
+        config: Option<PathBuf>,
+    },
+    /// Compare metrics of two revisions of a checked out git repository
+    Diff {
+        base_ref: String,
+        head_ref: String,
+        /// Path to the repository
+        #[clap(long, default_value = ".")]
+        repo_path: PathBuf,
+        #[clap(long, value_enum, default_value_t)]
+        format: DiffFormat,
+        /// Metrics to list the files and items with larger values for [default: complexity.all_fn, if complexity is
+        /// collected]
+        #[clap(long = "items")]
+        item_metrics: Option<Vec<String>>,
+        /// Path to the config [default: <REPO_PATH>/ifcount.toml, if it exists]
+        #[clap(long)]
+        config: Option<PathBuf>,
+    },
+    /// Collect metrics for the commits in the history of a checked out git repository
+    ///
+    /// Prints one JSON result per line, oldest commit first
+    History {
+        /// Path to the repository
+        #[clap(long, default_value = ".")]
+        repo_path: PathBuf,
+        /// The commit to walk the first-parent history from
+        #[clap(long, default_value = "HEAD")]
+        rev: String,
+        /// Only collect every Nth commit, counting from `rev`
+        #[clap(long, default_value_t = 1)]
+        every: usize,
+        /// Only collect the tagged commits
+        #[clap(long, conflicts_with = "every")]
+        tags: bool,
+        #[clap(flatten)]
+        options: CollectOptions,
     },
     /// Collect metrics from a github repository
     CollectGithubRepo {
+        /// `owner/repo`, or `owner/repo@ref` to collect a branch, a tag or a commit instead of the default branch
         repo_name: String,
+        #[clap(flatten)]
+        options: CollectOptions,
+        #[clap(flatten)]
+        github: GithubOptions,
     },
     BulkCollectGithubRepos {
+        /// A file with a repository per line, in the same format as for `collect-github-repo`
         list_path: PathBuf,
+        #[clap(flatten)]
+        options: CollectOptions,
+        #[clap(flatten)]
+        github: GithubOptions,
     },
-    /// Get a list of supported metrics
-    ///
-    /// Work internally by running against `DCNick3/ifcount`
+    /// Get a list of supported metrics, with their descriptions and units
     ListMetrics {
-        #[clap(long)]
-        latex: bool,
+        #[clap(long, value_enum, default_value_t)]
+        format: ListFormat,
     },
 }
 
-async fn make_crab(dirs: &ProjectDirs) -> Result<LimitedCrab> {
+/// The items of `diff` are listed for it when `--items` is not given, unless the complexity is not collected
+const DEFAULT_ITEM_METRIC: (&str, &str) = ("complexity", "complexity.all_fn");
+
+/// The metrics of the files are cached by their content, see `ifcount::Analyzer`
+fn metric_cache_dir(dirs: &ProjectDirs) -> PathBuf {
+    dirs.cache_dir().join("metric-cache")
+}
+
+#[derive(Debug, Args)]
+struct GithubOptions {
+    /// Base URL of the GitHub API, `https://<host>/api/v3` for GitHub Enterprise
+    #[clap(long, env = "IFCOUNT_GITHUB_API_URL", default_value = GithubUrls::DEFAULT_API)]
+    github_api_url: String,
+    /// Base URL of the raw file contents, `https://<host>/raw` for GitHub Enterprise
+    #[clap(long, env = "IFCOUNT_GITHUB_RAW_URL", default_value = GithubUrls::DEFAULT_RAW)]
+    github_raw_url: String,
+}
+
+fn make_crab(dirs: &ProjectDirs, github: GithubOptions) -> Result<LimitedCrab> {
     let token = if let Ok(github_token) = std::env::var("GITHUB_TOKEN") {
         Some(github_token)
     } else {
-        // most of the time we'll be talking to `raw.githubusercontent.com`, so it's not that bad if we don't have a token
+        // public repositories can be read without a token, only the rate limit is lower
         warn!("GITHUB_TOKEN not set, not authenticating when talking to GitHub API");
         None
     };
     let cache_path = dirs.cache_dir().join("gh-cache");
+    let urls = GithubUrls {
+        api: github.github_api_url,
+        raw: github.github_raw_url,
+    };
 
-    LimitedCrab::new(token, cache_path)
-        .await
-        .context("Creating octocrab")
+    LimitedCrab::new(token, cache_path, urls).context("Creating octocrab")
 }
 
 impl CliCommand {
     pub async fn run(self, dirs: &ProjectDirs) -> Result<()> {
         match self {
-            CliCommand::CollectLocalRepo { repo_path } => {
-                let result = collector::collect_local_repo(&repo_path)?;
+            CliCommand::CollectLocalRepo { repo_path, options } => {
+                let config = options.config(Some(&repo_path), dirs)?;
+                let result = ifcount::collect_local_repo(&repo_path, &config.collect)?;
 
-                println!(
-                    "{}",
-                    serde_json::to_string_pretty(&result).context("Serializing results")?
-                );
+                print_results(&[result], config.format, false)
+            }
+            CliCommand::Check { repo_path, config } => {
+                let config_path = config.unwrap_or_else(|| repo_path.join(CONFIG_FILE));
+                let Config {
+                    collect, budgets, ..
+                } = Config::load(&config_path)?;
+
+                // collect the locations to be able to point at the offending code
+                let collect = CollectConfig {
+                    observer: ObserverKind::Located,
+                    cache_dir: Some(metric_cache_dir(dirs)),
+                    ..collect
+                };
+                let result = ifcount::collect_local_repo(&repo_path, &collect)?;
+
+                let violations = check::check_budgets(&budgets, &result.metrics, &result.locations)
+                    .context("Checking budgets")?;
+                if !violations.is_empty() {
+                    print!("{}", check::report(&violations));
+                    bail!("{} budget(s) exceeded", violations.len());
+                }
+
+                info!("All {} budget(s) are met", budgets.budgets.len());
 
                 Ok(())
             }
-            CliCommand::CollectGithubRepo { repo_name } => {
-                let crab = make_crab(dirs).await?;
+            CliCommand::Diff {
+                base_ref,
+                head_ref,
+                repo_path,
+                format,
+                item_metrics,
+                config,
+            } => {
+                let config = CollectConfig {
+                    observer: ObserverKind::Located,
+                    cache_dir: Some(metric_cache_dir(dirs)),
+                    ..Config::find(config.as_deref(), Some(&repo_path))?.collect
+                };
+                let collect = |rev: &str| {
+                    ifcount::collect_local_revision(&repo_path, rev, &config)
+                        .with_context(|| format!("Collecting metrics at {}", rev))
+                };
+                let base = collect(&base_ref)?;
+                let head = collect(&head_ref)?;
 
-                let result = collector::collect_github_repo(&crab, &repo_name)
-                    .await
-                    .context("Collecting metrics")?;
+                let item_metrics = item_metrics.unwrap_or_else(|| {
+                    let (collector, metric) = DEFAULT_ITEM_METRIC;
+                    config
+                        .is_enabled(collector)
+                        .then(|| metric.to_string())
+                        .into_iter()
+                        .collect()
+                });
+                let diff = diff::diff(&base, &head, &item_metrics)?;
 
-                println!(
-                    "{}",
-                    serde_json::to_string_pretty(&result).context("Serializing results")?
-                );
+                match format {
+                    DiffFormat::Markdown => print!("{}", diff::markdown_report(&diff)),
+                    DiffFormat::Json => println!(
+                        "{}",
+                        serde_json::to_string_pretty(&diff).context("Serializing diff")?
+                    ),
+                }
 
                 Ok(())
             }
-            CliCommand::ListMetrics { latex } => {
-                let crab = make_crab(dirs).await?;
+            CliCommand::History {
+                repo_path,
+                rev,
+                every,
+                tags,
+                options,
+            } => {
+                let commits = ifcount::history_commits(&repo_path, &rev, every, tags)?;
+                let config = options.config(Some(&repo_path), dirs)?;
+                let mut collector = ifcount::HistoryCollector::new(&repo_path, config.collect)?;
+
+                let mut stdout = std::io::stdout().lock();
+                for (i, commit) in commits.iter().enumerate() {
+                    info!("Collecting {} ({}/{})", commit, i + 1, commits.len());
+                    let result = collector
+                        .collect(commit)
+                        .with_context(|| format!("Collecting metrics at {}", commit))?;
+
+                    serde_json::to_writer(&mut stdout, &result).context("Serializing results")?;
+                    writeln!(stdout).context("Writing results")?;
+                    stdout.flush().context("Writing results")?;
+                }
 
-                let result = collector::collect_github_repo(&crab, "DCNick3/ifcount")
+                Ok(())
+            }
+            CliCommand::CollectGithubRepo {
+                repo_name,
+                options,
+                github,
+            } => {
+                let config = options.config(None, dirs)?;
+                let crab = make_crab(dirs, github)?;
+
+                let result = ifcount::collect_github_repo(&crab, &repo_name, &config.collect)
                     .await
                     .context("Collecting metrics")?;
 
-                let metric_list = result.metrics.keys().collect::<Vec<_>>();
-
-                for metric in metric_list {
-                    if latex {
-                        println!(
-                            "\\metric{{{}}}{{INSERT HERE}}",
-                            crowbook_text_processing::escape::tex(metric)
-                        );
-                    } else {
-                        println!("{}", metric);
-                    }
-                }
+                print_results(&[result], config.format, false)
+            }
+            CliCommand::ListMetrics { format } => {
+                let metrics = ifcount::metric_list(&CollectConfig::default());
+                print!("{}", ifcount::render_metric_list(&metrics, format)?);
 
                 Ok(())
             }
-            CliCommand::BulkCollectGithubRepos { list_path } => {
-                let crab = make_crab(dirs).await?;
+            CliCommand::BulkCollectGithubRepos {
+                list_path,
+                options,
+                github,
+            } => {
+                let config = options.config(None, dirs)?;
+                let crab = make_crab(dirs, github)?;
 
                 let repo_list = std::fs::read_to_string(&list_path).context("Reading repo list")?;
                 let repo_list = repo_list
@@ -125,16 +342,12 @@ impl CliCommand {
                     .filter(|line| !line.is_empty())
                     .collect::<Vec<_>>();
 
-                let results = collector::bulk_collect_github_repos(&crab, &repo_list)
-                    .await
-                    .context("Collecting metrics")?;
-
-                println!(
-                    "{}",
-                    serde_json::to_string_pretty(&results).context("Serializing results")?
-                );
+                let results =
+                    ifcount::bulk_collect_github_repos(&crab, &repo_list, &config.collect)
+                        .await
+                        .context("Collecting metrics")?;
 
-                Ok(())
+                print_results(&results, config.format, true)
             }
         }
     }
@@ -0,0 +1,327 @@
+//! `collect_github_repo` against a local stand-in for GitHub, serving a few canned repositories
+
+use flate2::write::GzEncoder;
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{Body, Request, Response, Server, StatusCode};
+use ifcount::{
+    collect_github_repo, CollectConfig, FetchStrategy, GithubError, GithubUrls, LimitedCrab,
+};
+use serde_json::{json, Value};
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+
+const HEAD: &str = "796ae0c921825b812fd0f25bdda2dd42005806c5";
+const TAG: &str = "1d2a3a8e3d6b2f0a53d0b9d8fb6f2ae3fd07e2d1";
+
+const CARGO_TOML: &str = "[package]\nname = \"demo\"\nversion = \"0.1.0\"\n";
+const LIB_RS: &str = "mod util;\nfn a(x: bool) { if x {} }\n";
+const UTIL_RS: &str = "fn b(y: bool) { if y {} else if !y {} }\n";
+/// `export-ignore`d, so it's in the tree of the tag but not in its tarball
+const IGNORED_RS: &str = "fn c(z: bool) { if z {} }\n";
+
+struct StandIn {
+    /// whether `src/lib.rs` has already failed once, to check that the server errors are retried
+    lib_failed: AtomicBool,
+    /// how many times the tarball was asked for, to check that it's cached
+    tarballs: AtomicUsize,
+}
+
+fn commit(sha: &str) -> Value {
+    json!({
+        "sha": sha,
+        "node_id": "C_1",
+        "url": "",
+        "html_url": "",
+        "comments_url": "",
+        "commit": {
+            "message": "Initial commit",
+            "comment_count": 0,
+            "url": "",
+            "tree": { "sha": "", "url": "" },
+            "committer": { "name": "octo", "email": "octo@example.com", "date": "2023-09-05T18:32:09Z" },
+        },
+        "parents": [],
+    })
+}
+
+fn repo_info(name: &str) -> Value {
+    json!({
+        "id": 1,
+        "name": name,
+        "url": format!("https://ghe.example.com/api/v3/repos/octo/{}", name),
+        "default_branch": "main",
+        "ssh_url": format!("git@ghe.example.com:octo/{}.git", name),
+        "stargazers_count": 3,
+        "watchers_count": 3,
+        "forks_count": 1,
+        "open_issues_count": 0,
+        "size": 12,
+    })
+}
+
+fn tree_item(path: &str, type_: &str, sha: &str, size: Option<usize>) -> Value {
+    json!({ "path": path, "mode": "100644", "type": type_, "sha": sha, "size": size })
+}
+
+fn tree(sha: &str, items: Vec<Value>, truncated: bool) -> Value {
+    json!({ "sha": sha, "url": "https://ghe.example.com/tree", "tree": items, "truncated": truncated })
+}
+
+/// The `.tar.gz` GitHub would make of the commit with the tag
+fn tarball() -> Vec<u8> {
+    let mut builder = tar::Builder::new(GzEncoder::new(Vec::new(), Default::default()));
+    for (path, content) in [("Cargo.toml", CARGO_TOML), ("src/lib.rs", "fn a() {}\n")] {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(content.len() as u64);
+        header.set_mode(0o644);
+        header.set_cksum();
+        let path = format!("octo-demo-1d2a3a8/{}", path);
+        builder
+            .append_data(&mut header, path, content.as_bytes())
+            .unwrap();
+    }
+    builder.into_inner().unwrap().finish().unwrap()
+}
+
+fn json_response(status: StatusCode, value: Value) -> Response<Body> {
+    Response::builder()
+        .status(status)
+        .header("content-type", "application/json")
+        .body(Body::from(value.to_string()))
+        .unwrap()
+}
+
+impl StandIn {
+    fn respond(&self, request: &Request<Body>) -> Response<Body> {
+        let ok = |value| json_response(StatusCode::OK, value);
+        let host = request.headers()["host"].to_str().unwrap();
+        let path = request.uri().path();
+        let query = request.uri().query().unwrap_or("");
+
+        if let Some(route) = path.strip_prefix("/api/v3") {
+            return match (route, query) {
+                ("/repos/octo/demo", _) => ok(repo_info("demo")),
+                ("/repos/octo/empty", _) => ok(repo_info("empty")),
+                ("/repos/octo/demo/commits", "per_page=1") => ok(json!([commit(HEAD)])),
+                ("/repos/octo/demo/commits", _) if query.starts_with("sha=") => {
+                    let mut response = ok(json!([commit(HEAD)]));
+                    let link = format!(
+                        "<http://{host}/api/v3/repositories/1/commits?{query}&page=2>; rel=\"next\", \
+                         <http://{host}/api/v3/repositories/1/commits?{query}&page=42>; rel=\"last\""
+                    );
+                    response.headers_mut().insert("link", link.parse().unwrap());
+                    response
+                }
+                ("/repos/octo/empty/commits", _) => json_response(
+                    StatusCode::CONFLICT,
+                    json!({ "message": "Git Repository is empty." }),
+                ),
+                ("/repos/octo/demo/commits/v1.0", _) => ok(commit(TAG)),
+                // a tag, not a branch
+                ("/repos/octo/demo/git/matching-refs/heads/v1.0", _) => ok(json!([])),
+                // the recursive listing is too big, so it's truncated
+                (r, "recursive=true") if r == format!("/repos/octo/demo/git/trees/{HEAD}") => {
+                    ok(tree(
+                        HEAD,
+                        vec![tree_item(
+                            "Cargo.toml",
+                            "blob",
+                            "c1",
+                            Some(CARGO_TOML.len()),
+                        )],
+                        true,
+                    ))
+                }
+                (r, "") if r == format!("/repos/octo/demo/git/trees/{HEAD}") => ok(tree(
+                    HEAD,
+                    vec![
+                        tree_item("Cargo.toml", "blob", "c1", Some(CARGO_TOML.len())),
+                        tree_item("README.md", "blob", "r1", Some(10)),
+                        tree_item("src", "tree", "s1", None),
+                    ],
+                    false,
+                )),
+                ("/repos/octo/demo/git/trees/s1", "recursive=true") => ok(tree(
+                    "s1",
+                    vec![
+                        tree_item("lib.rs", "blob", "l1", Some(LIB_RS.len())),
+                        tree_item("util", "tree", "u1", None),
+                        tree_item("util/mod.rs", "blob", "u2", Some(UTIL_RS.len())),
+                    ],
+                    false,
+                )),
+                (r, "recursive=true") if r == format!("/repos/octo/demo/git/trees/{TAG}") => {
+                    ok(tree(
+                        TAG,
+                        vec![
+                            tree_item("Cargo.toml", "blob", "c1", Some(CARGO_TOML.len())),
+                            tree_item("src", "tree", "s2", None),
+                            tree_item("src/ignored.rs", "blob", "i1", Some(IGNORED_RS.len())),
+                            tree_item("src/lib.rs", "blob", "l2", Some(10)),
+                        ],
+                        false,
+                    ))
+                }
+                (r, _) if r == format!("/repos/octo/demo/tarball/{TAG}") => {
+                    self.tarballs.fetch_add(1, Ordering::SeqCst);
+                    Response::builder()
+                        .status(StatusCode::FOUND)
+                        .header(
+                            "location",
+                            format!("http://{host}/codeload/octo/demo/{TAG}"),
+                        )
+                        .body(Body::empty())
+                        .unwrap()
+                }
+                _ => json_response(StatusCode::NOT_FOUND, json!({ "message": "Not Found" })),
+            };
+        }
+
+        if path == format!("/codeload/octo/demo/{TAG}") {
+            return Response::new(Body::from(tarball()));
+        }
+        if path == format!("/raw/octo/demo/{TAG}/src/ignored.rs") {
+            return Response::new(Body::from(IGNORED_RS));
+        }
+        let Some(file) = path.strip_prefix(&format!("/raw/octo/demo/{HEAD}/")) else {
+            return json_response(StatusCode::NOT_FOUND, json!({ "message": "Not Found" }));
+        };
+        let content = match file {
+            "Cargo.toml" => CARGO_TOML,
+            "src/lib.rs" if !self.lib_failed.swap(true, Ordering::SeqCst) => {
+                return Response::builder()
+                    .status(StatusCode::BAD_GATEWAY)
+                    .body(Body::empty())
+                    .unwrap()
+            }
+            "src/lib.rs" => LIB_RS,
+            "src/util/mod.rs" => UTIL_RS,
+            _ => {
+                return Response::builder()
+                    .status(StatusCode::NOT_FOUND)
+                    .body(Body::empty())
+                    .unwrap()
+            }
+        };
+        Response::new(Body::from(content))
+    }
+}
+
+/// Starts the stand-in, and makes a client for it with an empty cache
+fn start(test: &str) -> (LimitedCrab, PathBuf, Arc<StandIn>) {
+    let stand_in = Arc::new(StandIn {
+        lib_failed: AtomicBool::new(false),
+        tarballs: AtomicUsize::new(0),
+    });
+    let served = stand_in.clone();
+    let make_service = make_service_fn(move |_| {
+        let stand_in = served.clone();
+        async move {
+            Ok::<_, Infallible>(service_fn(move |request| {
+                let response = stand_in.respond(&request);
+                async move { Ok::<_, Infallible>(response) }
+            }))
+        }
+    });
+    let server = Server::bind(&SocketAddr::from(([127, 0, 0, 1], 0))).serve(make_service);
+    let address = server.local_addr();
+    tokio::spawn(server);
+
+    let cache_dir =
+        std::env::temp_dir().join(format!("ifcount-github-{}-{}", test, std::process::id()));
+    let _ = std::fs::remove_dir_all(&cache_dir);
+    let urls = GithubUrls {
+        // with a path, as for GitHub Enterprise
+        api: format!("http://{}/api/v3/", address),
+        raw: format!("http://{}/raw", address),
+    };
+    let crab = LimitedCrab::new(None, cache_dir.clone(), urls).unwrap();
+    (crab, cache_dir, stand_in)
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn blobs_of_a_truncated_tree() {
+    let (crab, cache_dir, _) = start("blobs");
+    let config = CollectConfig {
+        fetch: FetchStrategy::Blobs,
+        ..CollectConfig::default()
+    };
+
+    let result = collect_github_repo(&crab, "octo/demo", &config)
+        .await
+        .unwrap();
+    assert_eq!(result.meta.url, "git@ghe.example.com:octo/demo.git");
+    assert_eq!(result.meta.commit, HEAD);
+    assert_eq!(result.meta.branch.as_deref(), Some("main"));
+    assert_eq!(result.meta.reference, None);
+    assert_eq!(
+        result.meta.timestamp.as_deref(),
+        Some("2023-09-05T18:32:09Z")
+    );
+    // the files of `src` are there even though the first listing was truncated
+    assert!(!result.diagnostics.listing_truncated);
+    assert_eq!(result.diagnostics.coverage.files_seen, 2);
+    assert_eq!(result.values("if_count"), Some(vec![3.0]));
+    assert_eq!(result.values("repo.commit_count"), Some(vec![42.0]));
+    assert_eq!(result.values("repo.stars"), Some(vec![3.0]));
+
+    std::fs::remove_dir_all(cache_dir).unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn archive_at_a_tag() {
+    let (crab, cache_dir, _) = start("archive");
+
+    let result = collect_github_repo(&crab, "octo/demo@v1.0", &CollectConfig::default())
+        .await
+        .unwrap();
+    assert_eq!(result.meta.commit, TAG);
+    assert_eq!(result.meta.reference.as_deref(), Some("v1.0"));
+    assert_eq!(result.meta.branch, None);
+    // the file left out of the tarball is downloaded on its own
+    assert_eq!(result.diagnostics.coverage.files_seen, 2);
+    assert!(!result.diagnostics.listing_truncated);
+    assert_eq!(result.values("if_count"), Some(vec![1.0]));
+
+    std::fs::remove_dir_all(cache_dir).unwrap();
+}
+
+// on the default single-threaded runtime, which the archive must not block
+#[tokio::test]
+async fn archive_is_cached() {
+    let (crab, cache_dir, stand_in) = start("archive-cached");
+
+    let first = collect_github_repo(&crab, "octo/demo@v1.0", &CollectConfig::default())
+        .await
+        .unwrap();
+    let second = collect_github_repo(&crab, "octo/demo@v1.0", &CollectConfig::default())
+        .await
+        .unwrap();
+    assert_eq!(stand_in.tarballs.load(Ordering::SeqCst), 1);
+    assert_eq!(second.diagnostics.coverage.files_seen, 2);
+    assert_eq!(second.values("if_count"), first.values("if_count"));
+
+    std::fs::remove_dir_all(cache_dir).unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn empty_repository() {
+    let (crab, cache_dir, _) = start("empty");
+
+    let error = collect_github_repo(&crab, "octo/empty", &CollectConfig::default())
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(
+            error.downcast_ref::<GithubError>(),
+            Some(GithubError::EmptyRepository)
+        ),
+        "{:#}",
+        error
+    );
+
+    let _ = std::fs::remove_dir_all(cache_dir);
+}